@@ -1,407 +1,1001 @@
-use bitcoin::block::Header;
-use bitcoin::consensus::encode;
-use bitcoin::Transaction;
-use misfit_core::block::breakers::block as BlockBreaker;
-use misfit_core::transaction::breakers as TxBreaker;
-use misfit_core::block::generator::GenerateBlock;
-use misfit_core::block::random::block::BlockParams;
-use misfit_core::block::decoder;
-use misfit_core::regtest_pack::regtest::RegtestManager;
-use misfit_core::transaction::generator::GenerateTx;
-use misfit_core::transaction::random::input::InputParams;
-use misfit_core::transaction::random::script::{ScriptParams, ScriptTypes};
-use misfit_core::transaction::random::transaction::TxParams;
+//! Stable facade over the generators, breakers and decoders.
+//!
+//! Every function returns typed values: `Transaction`, `Block` and `Header`
+//! for the building blocks, and the serializable types of `crate::report`
+//! for the results printed by the CLI.
+
 use std::collections::HashSet;
 
+use bitcoin::{
+    absolute::LockTime,
+    block::{Header, Version},
+    blockdata::constants::genesis_block,
+    consensus::encode,
+    hashes::Hash,
+    params::Params,
+    secp256k1::Secp256k1,
+    Block, Network, OutPoint, PrivateKey, ScriptBuf, Sequence, Transaction, TxMerkleNode,
+};
+use rand::Rng;
+
+use crate::block::breakers::bits::BitsProcessor;
+use crate::block::breakers::block::{BlockField, BlockProcessor, ProcessingConfig};
+use crate::block::breakers::header_chain::{HeaderChainBreak, HeaderChainProcessor};
+use crate::block::breakers::merkle_tree::{MerkleTreeProcessor, INNER_NODE_TX_SIZE};
+use crate::block::breakers::timestamp::{TimestampProcessor, TimestampScenario};
+use crate::block::decoder;
+use crate::block::generator::{
+    ChainParams, GenerateBlock, GenerateChain, GenerateHeaderChain, HeaderChainParams,
+};
+use crate::block::random::block::BlockParams;
+use crate::block::random::header::{HeaderParams, RandomHeader};
+use crate::block::version_bits::{self, Deployment, VERSIONBITS_TOP_BITS};
+use crate::p2p::breakers::{EnvelopeBreak, EnvelopeProcessor};
+use crate::p2p::compact_block::{CompactBlock, CompactBlockBreak, CompactBlockProcessor};
+use crate::p2p::message::{MessageCommand, WireMessage, WireObject, MESSAGE_HEADER_SIZE};
+use crate::regtest_pack::regtest::RegtestManager;
+use crate::report::{
+    BlockReport, BreakBlockReport, BreakTransactionReport, BrokenHeaderReport, ChainReport,
+    CompactBlockReport, HeaderChainReport, HeaderReport, MerkleProofReport, MultisigReport,
+    MutationReport, RetargetReport, StateTransitionReport, TapLeafReport, TapscriptReport,
+    TimestampScenarioReport, TimestampScenariosReport, TransactionReport, TransactionsReport,
+    VersionBitsReport, WireMessageReport,
+};
+use crate::rng;
+use crate::transaction::breakers::flags::InvalidationFlag;
+use crate::transaction::breakers::nested::NestedSegwitBreak;
+use crate::transaction::breakers::taproot::{TaprootBreak, TaprootProcessor};
+use crate::transaction::breakers::transaction::TransactionInvalidator;
+use crate::transaction::generator::GenerateTx;
+use crate::transaction::random::coinbase::COINBASE_MATURITY;
+use crate::transaction::random::input::InputParams;
+use crate::transaction::random::multisig::{
+    Multisig, MultisigWrapping, MAX_P2SH_MULTISIG_KEYS, MAX_PUBKEYS_PER_MULTISIG,
+};
+use crate::transaction::random::script::{ScriptParams, ScriptTypes};
+use crate::transaction::random::taproot::{
+    RandomTapScriptSpend, TapLeafParams, TapScriptSpend, TapTreeParams,
+};
+use crate::transaction::random::transaction::{TxInfo, TxParams};
+use crate::{Error, Result};
+
+// Length of a hex-encoded 80-byte block header
+const HEADER_HEX_LEN: usize = 160;
+
+// Blocks with their heights, in chain order
+type Chain = Vec<(Block, u32)>;
+
 pub struct Generator {}
 
 impl Generator {
-    pub fn block(tx_count: u32) -> String {
-        let mut txs: Vec<Transaction> = vec![];
-        let mut raw_tx: Vec<String> = vec![];
-        let mut tx_ids: Vec<String> = vec![];
-
-        for _c in 0..tx_count {
-        let mut tx_params = TxParams::default();
-        let tx_input_params = InputParams {
-            script_params: Some(ScriptParams {
-                script_type: Some(ScriptTypes::P2WPKH),
-                private_key: None,
+    /// Generate `count` valid transactions spending P2WPKH outputs.
+    ///
+    /// `seed` reseeds the shared generator once, before the first one.
+    pub fn transactions(count: u32, seed: Option<u64>) -> Vec<Transaction> {
+        Self::transaction_infos(count, seed)
+            .into_iter()
+            .map(|info| info.transaction)
+            .collect()
+    }
+
+    /// Like `transactions`, also returning the outputs each one spends.
+    pub fn transaction_infos(count: u32, mut seed: Option<u64>) -> Vec<TxInfo> {
+        (0..count)
+            .map(|_| {
+                let tx_params = TxParams {
+                    input: Some(InputParams {
+                        script_params: Some(ScriptParams {
+                            script_type: Some(ScriptTypes::P2WPKH),
+                            private_key: None,
+                        }),
+                        ..Default::default()
+                    }),
+                    seed: seed.take(),
+                    ..Default::default()
+                };
+
+                GenerateTx::valid_random_with_info(tx_params).expect("inputs of random value cover any fee")
+            })
+            .collect()
+    }
+
+    /// Generate a transaction whose input spends a P2TR output through the
+    /// leaf at `leaf_index` of a tree of `leaves`, both random by default,
+    /// broken by `breakage` if given.
+    pub fn tapscript_spend(
+        leaves: Option<Vec<TapLeafParams>>,
+        leaf_index: Option<usize>,
+        breakage: Option<TaprootBreak>,
+        seed: Option<u64>,
+    ) -> Result<(TxInfo, TapScriptSpend)> {
+        if let Some(seed) = seed {
+            rng::set_seed(seed);
+        }
+        if leaves.as_ref().is_some_and(Vec::is_empty) {
+            return Err(Error::InvalidConfig("a script tree needs a leaf".to_string()));
+        }
+
+        let mut spend = TapScriptSpend::random(TapTreeParams {
+            leaves,
+            leaf_index,
+            ..Default::default()
+        });
+        if spend.leaf_index >= spend.leaves.len() {
+            return Err(Error::InvalidConfig(format!(
+                "leaf {} out of {} leaves",
+                spend.leaf_index,
+                spend.leaves.len()
+            )));
+        }
+        // Tree breakers change the output, so they go first
+        let tree_broken =
+            breakage.is_some_and(|breakage| TaprootProcessor::break_tree(&mut spend, breakage));
+
+        let mut info = GenerateTx::valid_random_with_info(TxParams {
+            input: Some(InputParams {
+                script_params: Some(ScriptParams {
+                    script_type: Some(ScriptTypes::P2TRSCRIPTPATH(spend.clone())),
+                    private_key: None,
+                }),
+                ..Default::default()
             }),
             ..Default::default()
-        };
+        })?;
+        if let Some(breakage) = breakage {
+            let witness = &mut info.transaction.input[0].witness;
+            if !tree_broken && !TaprootProcessor::break_witness(witness, breakage) {
+                return Err(Error::InvalidConfig(format!(
+                    "cannot apply {} to a spend through {}",
+                    breakage.name(),
+                    spend.leaf().script.to_asm_string()
+                )));
+            }
+        }
+        Ok((info, spend))
+    }
 
-        tx_params.input = Some(tx_input_params);
+    pub fn tapscript(
+        leaves: Option<Vec<TapLeafParams>>,
+        leaf_index: Option<usize>,
+        breakage: Option<TaprootBreak>,
+        seed: Option<u64>,
+    ) -> Result<TapscriptReport> {
+        let (info, spend) = Self::tapscript_spend(leaves, leaf_index, breakage, seed)?;
+        let spend_info = spend.spend_info();
+
+        Ok(TapscriptReport {
+            internal_key: spend_info.internal_key().to_string(),
+            output_key: spend_info.output_key().to_string(),
+            merkle_root: spend_info
+                .merkle_root()
+                .map(|root| root.to_string())
+                .unwrap_or_default(),
+            leaves: spend
+                .leaves
+                .iter()
+                .map(|leaf| TapLeafReport {
+                    script: leaf.script.to_asm_string(),
+                    leaf_hash: leaf.leaf_hash().to_string(),
+                    threshold: leaf.threshold,
+                })
+                .collect(),
+            spent_leaf: spend.leaf_index,
+            control_block: hex::encode(spend.control_block().serialize()),
+            prevout_script: info.prevouts[0].script_pubkey.to_hex_string(),
+            prevout_value: info.prevouts[0].value.to_sat(),
+            mutation: breakage.map(|breakage| {
+                MutationReport::new(
+                    breakage,
+                    breakage.description(),
+                    Some(breakage.expected_failure()),
+                )
+            }),
+            transaction: TransactionReport::from(&info.transaction),
+        })
+    }
 
-        let tx_info = GenerateTx::valid_random(tx_params);
-        let raw_transaction = hex::encode(encode::serialize(&tx_info)).to_string();
-        let txid = tx_info.compute_txid().to_string();
+    /// Generate a transaction whose input spends a `threshold`-of-`keys`
+    /// multisig output paid to through `wrapping`.
+    pub fn multisig_spend(
+        threshold: usize,
+        keys: usize,
+        wrapping: MultisigWrapping,
+        seed: Option<u64>,
+    ) -> Result<(TxInfo, Multisig)> {
+        if threshold == 0 || threshold > keys || keys > MAX_PUBKEYS_PER_MULTISIG {
+            return Err(Error::InvalidConfig(format!(
+                "cannot build a {threshold}-of-{keys} multisig, need 1 <= m <= n <= {MAX_PUBKEYS_PER_MULTISIG}"
+            )));
+        }
+        // The redeem script is pushed whole, and pushes stop at 520 bytes
+        if wrapping == MultisigWrapping::P2SH && keys > MAX_P2SH_MULTISIG_KEYS {
+            return Err(Error::InvalidConfig(format!(
+                "a P2SH redeem script fits at most {MAX_P2SH_MULTISIG_KEYS} keys"
+            )));
+        }
+        if let Some(seed) = seed {
+            rng::set_seed(seed);
+        }
 
-        txs.push(tx_info);
-        raw_tx.push(raw_transaction);
-        tx_ids.push(txid);
-}
+        let multisig = Multisig::new(threshold, keys, wrapping);
+        let info = GenerateTx::valid_random_with_info(TxParams {
+            input: Some(InputParams {
+                script_params: Some(ScriptParams {
+                    script_type: Some(ScriptTypes::MULTISIG(multisig.clone())),
+                    private_key: None,
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })?;
+        Ok((info, multisig))
+    }
+
+    pub fn multisig(
+        threshold: usize,
+        keys: usize,
+        wrapping: MultisigWrapping,
+        seed: Option<u64>,
+    ) -> Result<MultisigReport> {
+        let (info, multisig) = Self::multisig_spend(threshold, keys, wrapping, seed)?;
+        let secp = Secp256k1::new();
+
+        Ok(MultisigReport {
+            wrapping: wrapping.name().to_string(),
+            threshold,
+            pubkeys: multisig
+                .keys
+                .iter()
+                .map(|key| key.public_key(&secp).to_string())
+                .collect(),
+            script: multisig.script().to_asm_string(),
+            redeem_script: multisig.redeem_script().map(|script| script.to_hex_string()),
+            prevout_script: info.prevouts[0].script_pubkey.to_hex_string(),
+            prevout_value: info.prevouts[0].value.to_sat(),
+            transaction: TransactionReport::from(&info.transaction),
+        })
+    }
+
+    /// Generate a valid block holding a coinbase followed by `tx_count`
+    /// transactions, together with its height. The coinbase claims the
+    /// subsidy plus the fees of the transactions.
+    pub fn random_block(tx_count: u32, seed: Option<u64>) -> (Block, u32) {
+        if let Some(seed) = seed {
+            rng::set_seed(seed);
+        }
+
+        let infos = Self::transaction_infos(tx_count, None);
+        let fees = infos.iter().filter_map(TxInfo::fee).sum();
+
+        GenerateBlock::valid_random(BlockParams {
+            txs: Some(infos.into_iter().map(|info| info.transaction).collect()),
+            fees: Some(fees),
+            ..Default::default()
+        })
+    }
 
-        let (block, height) = GenerateBlock::valid_random(BlockParams {
-            header: None,
-            txs: Some(txs),
-            height: None,
+    /// Generate `length` blocks on top of the regtest genesis block, each
+    /// pointing at the previous one, with their heights.
+    pub fn random_chain(length: usize, seed: Option<u64>) -> Vec<(Block, u32)> {
+        GenerateChain::valid_random(ChainParams {
+            length: Some(length),
+            seed,
+            ..Default::default()
+        })
+    }
+
+    /// Generate 100 blocks on top of the regtest genesis block and a block
+    /// on their tip set up for `scenario`, for a node whose adjusted time is
+    /// `now`. The chain is long enough for its first coinbase to mature, so
+    /// lock time cases spend an output that exists.
+    pub fn timestamp_scenario(
+        scenario: TimestampScenario,
+        now: u32,
+        seed: Option<u64>,
+    ) -> Result<(Chain, (Block, u32))> {
+        let (chain, private_key) = Self::timestamp_chain(seed);
+        let block = Self::timestamp_block(&chain, &private_key, scenario, now)?;
+        Ok((chain, block))
+    }
+
+    /// Build a block on the tip of `chain`, or on the regtest genesis block
+    /// when it is empty, set up for `scenario`, for a node whose adjusted
+    /// time is `now`.
+    ///
+    /// The transaction of the lock time cases spends the first mature
+    /// coinbase of `chain`, which must pay to `private_key`.
+    pub fn timestamp_block(
+        chain: &[(Block, u32)],
+        private_key: &PrivateKey,
+        scenario: TimestampScenario,
+        now: u32,
+    ) -> Result<(Block, u32)> {
+        let genesis = [(genesis_block(Network::Regtest), 0)];
+        let chain = if chain.is_empty() { &genesis[..] } else { chain };
+        let headers: Vec<Header> = chain.iter().map(|(block, _)| block.header).collect();
+        let median_time_past = TimestampProcessor::median_time_past(&headers).unwrap_or(0);
+        let (tip, tip_height) = &chain[chain.len() - 1];
+
+        let mut infos = vec![];
+        if let Some(lock_time) = scenario.lock_time(median_time_past) {
+            let script_pubkey = ScriptBuf::new_p2wpkh(
+                &private_key.public_key(&Secp256k1::new()).wpubkey_hash().unwrap(),
+            );
+            let (coinbase, vout) = chain
+                .iter()
+                .filter(|(_, height)| height + COINBASE_MATURITY <= tip_height + 1)
+                .find_map(|(block, _)| {
+                    let coinbase = block.txdata.first()?;
+                    let vout = coinbase
+                        .output
+                        .iter()
+                        .position(|output| output.script_pubkey == script_pubkey)?;
+                    Some((coinbase, vout))
+                })
+                .ok_or_else(|| {
+                    Error::InvalidConfig(format!(
+                        "{scenario:?} needs a coinbase paying to the key, \
+                         {COINBASE_MATURITY} blocks below the new one"
+                    ))
+                })?;
+
+            // A lock time only counts while some input's sequence is not final
+            infos.push(GenerateTx::valid_random_with_info(TxParams {
+                lock_time: Some(LockTime::from_consensus(lock_time)),
+                input: Some(InputParams {
+                    outpoint: Some(OutPoint::new(coinbase.compute_txid(), vout as u32)),
+                    script: Some((script_pubkey, ScriptTypes::P2WPKH)),
+                    value: Some(coinbase.output[vout].value),
+                    private_key: Some(*private_key),
+                    sequence: Some(Sequence::ENABLE_LOCKTIME_NO_RBF),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            })?);
+        }
+        let fees = infos.iter().filter_map(TxInfo::fee).sum();
+
+        let header = Header::random(HeaderParams {
+            version: Some(Version::from_consensus(VERSIONBITS_TOP_BITS)),
+            prev_blockhash: Some(tip.block_hash()),
+            merkle_root: Some(TxMerkleNode::all_zeros()),
+            time: Some(scenario.block_time(median_time_past, tip.header.time, now)),
+            ..Default::default()
         });
+        Ok(GenerateBlock::valid_random(BlockParams {
+            header: Some(header),
+            txs: Some(infos.into_iter().map(|info| info.transaction).collect()),
+            height: Some(tip_height + 1),
+            target: Some(tip.header.bits),
+            fees: Some(fees),
+            ..Default::default()
+        }))
+    }
 
-        [
-            format!("{:#?} ", block.header),
-            format!("Block Height: {height}"),
-            format!("Block Header encoded: {:#?}", encode::serialize_hex(&block.header)),
-            format!("Raw txs: {raw_tx:#?}"),
-            format!("TxID: {tx_ids:#?}"),
-        ]
-        .join("\n---\n")
-    }
-
-    pub fn transaction(count: u32) -> String {
-        let mut raw_tx: Vec<String> = vec![];
-        let mut txid: Vec<String> = vec![];
-
-        for _c in 0..count {
-        let mut tx_params = TxParams::default();
-        let tx_input_params = InputParams {
-            script_params: Some(ScriptParams {
-                script_type: Some(ScriptTypes::P2WPKH),
-                private_key: None,
-            }),
+    /// Blocks for every timestamp scenario on one shared chain of 100
+    /// blocks; `now` defaults to the current time.
+    pub fn timestamp_scenarios(
+        now: Option<u32>,
+        seed: Option<u64>,
+    ) -> Result<TimestampScenariosReport> {
+        let now = now.unwrap_or_else(TimestampProcessor::now);
+        let (chain, private_key) = Self::timestamp_chain(seed);
+        let headers: Vec<Header> = chain.iter().map(|(block, _)| block.header).collect();
+        let median_time_past = TimestampProcessor::median_time_past(&headers).unwrap_or(0);
+
+        let scenarios = TimestampScenario::ALL
+            .iter()
+            .map(|scenario| {
+                let (block, height) = Self::timestamp_block(&chain, &private_key, *scenario, now)?;
+                Ok(TimestampScenarioReport {
+                    scenario: MutationReport::new(
+                        scenario,
+                        scenario.description(),
+                        scenario.expected_failure(),
+                    ),
+                    time: block.header.time,
+                    lock_time: scenario.lock_time(median_time_past),
+                    block: BlockReport::new(&block, height),
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(TimestampScenariosReport {
+            now,
+            median_time_past,
+            chain: chain.iter().map(|(block, height)| BlockReport::new(block, *height)).collect(),
+            scenarios,
+        })
+    }
+
+    // Chain whose first coinbase, paying to the returned key, matures in the
+    // block on its tip
+    fn timestamp_chain(seed: Option<u64>) -> (Chain, PrivateKey) {
+        if let Some(seed) = seed {
+            rng::set_seed(seed);
+        }
+        let private_key = rng::private_key();
+        let chain = GenerateChain::valid_random(ChainParams {
+            length: Some(COINBASE_MATURITY as usize),
+            private_key: Some(private_key),
             ..Default::default()
+        });
+        (chain, private_key)
+    }
+
+    /// Generate `length` blocks on top of the regtest genesis block where
+    /// the first `signalling_per_period` blocks of every regtest signalling
+    /// period signal for `bit`; all of them by default.
+    pub fn random_signalling_chain(
+        bit: u8,
+        length: usize,
+        signalling_per_period: Option<u32>,
+        seed: Option<u64>,
+    ) -> Vec<(Block, u32)> {
+        let deployment = Deployment::regtest(bit);
+        let signalling_per_period = signalling_per_period.unwrap_or(deployment.period);
+        let signalling = version_bits::signalling_version(&[bit]);
+        let bare = Version::from_consensus(VERSIONBITS_TOP_BITS);
+        // Heights start at 1 on top of the genesis block
+        let versions = (1..=length as u32)
+            .map(|height| {
+                if height % deployment.period < signalling_per_period {
+                    signalling
+                } else {
+                    bare
+                }
+            })
+            .collect();
+
+        GenerateChain::valid_random(ChainParams {
+            length: Some(length),
+            versions: Some(versions),
+            seed,
+            ..Default::default()
+        })
+    }
+
+    /// A signalling chain for `bit` with the heights at which the regtest
+    /// deployment changes state, e.g. 432 blocks take it to active.
+    pub fn version_bits_chain(
+        bit: u8,
+        length: usize,
+        signalling_per_period: Option<u32>,
+        seed: Option<u64>,
+    ) -> VersionBitsReport {
+        let deployment = Deployment::regtest(bit);
+        let chain = Self::random_signalling_chain(bit, length, signalling_per_period, seed);
+
+        let mut headers = vec![genesis_block(Network::Regtest).header];
+        headers.extend(chain.iter().map(|(block, _)| block.header));
+        let states = deployment.states(&headers);
+        let transitions = states
+            .iter()
+            .enumerate()
+            .skip(1)
+            .filter(|(height, state)| states[height - 1] != **state)
+            .map(|(height, state)| StateTransitionReport {
+                height: height as u32,
+                state: state.to_string(),
+            })
+            .collect();
+
+        VersionBitsReport {
+            bit,
+            period: deployment.period,
+            threshold: deployment.threshold,
+            signalling_per_period: signalling_per_period.unwrap_or(deployment.period),
+            transitions,
+            chain: ChainReport {
+                blocks: chain.iter().map(|(block, height)| BlockReport::new(block, *height)).collect(),
+            },
+        }
+    }
+
+    /// Generate `length` headers on top of the regtest genesis header,
+    /// optionally breaking the one at `break_at`; the headers after it are
+    /// linked to it and mined again.
+    pub fn random_header_chain(
+        length: usize,
+        break_at: Option<(usize, HeaderChainBreak)>,
+        seed: Option<u64>,
+    ) -> Result<Vec<Header>> {
+        let mut headers = GenerateHeaderChain::valid_random(HeaderChainParams {
+            length: Some(length),
+            seed,
+            ..Default::default()
+        });
+        if let Some((index, breakage)) = break_at {
+            if !HeaderChainProcessor::break_at(&mut headers, index, breakage) {
+                return Err(Error::InvalidConfig(format!(
+                    "cannot break header {index} of a chain of {length}"
+                )));
+            }
+        }
+        Ok(headers)
+    }
+
+    /// A header chain serialized as a P2P `headers` message payload, or as
+    /// bare 80-byte headers when `flat` is set.
+    pub fn header_chain(
+        length: usize,
+        break_at: Option<(usize, HeaderChainBreak)>,
+        flat: bool,
+        seed: Option<u64>,
+    ) -> Result<HeaderChainReport> {
+        let headers = Self::random_header_chain(length, break_at, seed)?;
+        let payload = if flat {
+            decoder::BlockUtils::encode_headers_flat(&headers)
+        } else {
+            decoder::BlockUtils::encode_headers_message(&headers)
         };
 
-        tx_params.input = Some(tx_input_params);
+        Ok(HeaderChainReport {
+            count: headers.len(),
+            encoding: if flat { "flat" } else { "headers" }.to_string(),
+            tip_hash: headers
+                .last()
+                .map(|header| header.block_hash())
+                .unwrap_or_else(|| genesis_block(Network::Regtest).block_hash())
+                .to_string(),
+            broken: break_at.map(|(index, breakage)| BrokenHeaderReport {
+                index,
+                hash: headers[index].block_hash().to_string(),
+                mutation: MutationReport::new(
+                    breakage,
+                    breakage.description(),
+                    // Bare headers are submitted one at a time
+                    Some(if flat {
+                        breakage.expected_failure()
+                    } else {
+                        breakage.expected_failure_in_message(index)
+                    }),
+                ),
+            }),
+            payload_hex: hex::encode(payload),
+        })
+    }
+
+    pub fn chain(length: usize, seed: Option<u64>) -> ChainReport {
+        let blocks = Self::random_chain(length, seed)
+            .iter()
+            .map(|(block, height)| BlockReport::new(block, *height))
+            .collect();
 
-        let tx_info = GenerateTx::valid_random(tx_params);
-        let raw_transaction = hex::encode(encode::serialize(&tx_info)).to_string();
-        let tx_id = tx_info.compute_txid().to_string();
+        ChainReport { blocks }
+    }
 
-        raw_tx.push(raw_transaction);
-        txid.push(tx_id);
+    pub fn block(tx_count: u32, seed: Option<u64>) -> BlockReport {
+        let (block, height) = Self::random_block(tx_count, seed);
+        BlockReport::new(&block, height)
     }
 
-        [
-            format!("Raw Transactions: {raw_tx:#?}"),
-            format!("TXIDs: {txid:#?}"),
-        ]
-        .join("\n---\n")
+    pub fn transaction(count: u32, seed: Option<u64>) -> TransactionsReport {
+        let transactions = Self::transactions(count, seed)
+            .iter()
+            .map(TransactionReport::from)
+            .collect();
+
+        TransactionsReport { transactions }
     }
 
-    pub fn decode_raw_transaction(
-        raw_tx: String,
-    ) -> Result<Transaction, Box<dyn std::error::Error>> {
+    pub fn decode_raw_transaction(raw_tx: String) -> Result<Transaction> {
         let decoder = decoder::BitcoinTransactionDecoder::new();
         decoder.decode_hex(&raw_tx)
     }
 
-    pub fn decoder_block_header(
-        block_header: String,
-    ) -> Result<Header, Box<dyn std::error::Error>> {
+    pub fn decoder_block_header(block_header: String) -> Result<Header> {
         decoder::BlockUtils::decode_header_from_hex(&block_header)
     }
-    pub fn regtest_invocation(name_of_wallet: &str, mode_of_cli: &str) -> RegtestManager {
-        RegtestManager::new(name_of_wallet, mode_of_cli)
-    }
 
-    pub fn break_transaction(transaction: String, cli_flags: Vec<String>) -> String {
-        // Convert CLI flags to InvalidationFlag HashSet
-        let invalidation_flags = Self::parse_cli_flags_to_invalidation_flags(cli_flags);
+    /// Decode a full serialized block, header and transactions.
+    pub fn decode_full_block(raw_block: String) -> Result<Block> {
+        decoder::BlockUtils::decode_block_from_hex(raw_block.trim())
+    }
 
-        if invalidation_flags.is_empty() {
-            return "No invalidation flags specified. Use 'help' for usage information."
-                .to_string();
+    /// Bits required after a difficulty adjustment window whose first and
+    /// last blocks have `first_time` and `last_time`, with the bits one
+    /// compact unit off on either side.
+    pub fn retarget(
+        last_bits: u32,
+        first_time: u32,
+        last_time: u32,
+        network: Network,
+    ) -> RetargetReport {
+        let params = Params::new(network);
+        let timespan = last_time as i64 - first_time as i64;
+        let required_bits = BitsProcessor::retarget(last_bits, first_time, last_time, network);
+
+        RetargetReport {
+            network: network.to_string(),
+            last_bits,
+            timespan,
+            clamped_timespan: (timespan.max(0) as u64)
+                .clamp(params.pow_target_timespan / 4, params.pow_target_timespan * 4),
+            required_bits,
+            one_unit_easier: BitsProcessor::one_unit_easier(required_bits),
+            one_unit_harder: BitsProcessor::one_unit_harder(required_bits),
         }
+    }
+
+    /// Forge an SPV proof for a txid the block does not have, by posing the
+    /// halves of its first 64-byte transaction as two leaves.
+    ///
+    /// Break a block with `--insert-64-byte-tx` to get one that has such a
+    /// transaction.
+    pub fn forge_merkle_proof(raw_block: String) -> Result<MerkleProofReport> {
+        let block = Self::decode_full_block(raw_block)?;
+        let index = block
+            .txdata
+            .iter()
+            .position(|tx| encode::serialize(tx).len() == INNER_NODE_TX_SIZE)
+            .ok_or_else(|| Error::InvalidConfig("block has no 64-byte transaction".to_string()))?;
+        let (merkle_block, forged_txid) = MerkleTreeProcessor::forge_inner_node_proof(&block, index)
+            .ok_or_else(|| Error::InvalidConfig("could not forge a merkle proof".to_string()))?;
+
+        Ok(MerkleProofReport {
+            forged_txid: forged_txid.to_string(),
+            inner_tx_index: index,
+            inner_txid: block.txdata[index].compute_txid().to_string(),
+            merkle_root: block.header.merkle_root.to_string(),
+            merkle_block_hex: encode::serialize_hex(&merkle_block),
+        })
+    }
 
-        // Decode the transaction
-        let decoded_tx = match Self::decode_raw_transaction(transaction.clone()) {
-            Ok(tx) => tx,
-            Err(e) => return format!("Error decoding transaction: {e}"),
+    /// Wrap a raw transaction, block or 80-byte header in a P2P message
+    /// of `command` for `network`, optionally breaking its envelope.
+    pub fn wrap_message(
+        raw: String,
+        command: MessageCommand,
+        network: Network,
+        breakage: Option<EnvelopeBreak>,
+    ) -> Result<WireMessageReport> {
+        let raw = raw.trim();
+        let object = if raw.len() == HEADER_HEX_LEN {
+            WireObject::Header(Self::decoder_block_header(raw.to_string())?)
+        } else if let Ok(tx) = Self::decode_raw_transaction(raw.to_string()) {
+            WireObject::Transaction(tx)
+        } else {
+            WireObject::Block(Self::decode_full_block(raw.to_string())?)
         };
 
-        // Create invalid version based on specified flags
-        let invalid_tx = TxBreaker::transaction::TransactionInvalidator::invalidate(
-            decoded_tx,
-            &invalidation_flags,
-        );
+        let mut message = WireMessage::encode(&object, command, network)?;
+        if let Some(breakage) = breakage {
+            EnvelopeProcessor::process(&mut message, breakage);
+        }
 
-        // Build the result string
-        let mut result = String::new();
+        Ok(WireMessageReport {
+            network: network.to_string(),
+            magic: hex::encode(&message[..4]),
+            command: String::from_utf8_lossy(&message[4..16])
+                .trim_end_matches('\0')
+                .to_string(),
+            length: EnvelopeProcessor::length(&message),
+            checksum: hex::encode(&message[20..MESSAGE_HEADER_SIZE]),
+            size: message.len(),
+            mutation: breakage.map(|breakage| {
+                MutationReport::new(
+                    breakage,
+                    breakage.description(),
+                    Some(breakage.expected_failure()),
+                )
+            }),
+            message_hex: hex::encode(message),
+        })
+    }
 
-        // List which fields are being invalidated
-        result.push_str("Invalidating the following fields:\n");
+    /// Generate a block of a coinbase and `tx_count` transactions relayed
+    /// as a BIP152 compact block, prefilling the coinbase and the
+    /// transactions at `prefill`.
+    pub fn random_compact_block(
+        tx_count: u32,
+        prefill: &[usize],
+        seed: Option<u64>,
+    ) -> Result<CompactBlock> {
+        let (block, _) = Self::random_block(tx_count, seed);
+        CompactBlock::from_block(block, rng::rng().random(), prefill)
+    }
 
-        if invalidation_flags.contains(&TxBreaker::flags::InvalidationFlag::All) {
-            result.push_str("  - ALL FIELDS\n");
-        } else {
-            for flag in &invalidation_flags {
-                match flag {
-                    TxBreaker::flags::InvalidationFlag::Version => {
-                        result.push_str("  - Transaction Version\n")
-                    }
-                    TxBreaker::flags::InvalidationFlag::InputTxid => {
-                        result.push_str("  - Input TXIDs\n")
-                    }
-                    TxBreaker::flags::InvalidationFlag::InputVout => {
-                        result.push_str("  - Input Vouts\n")
-                    }
-                    TxBreaker::flags::InvalidationFlag::InputScriptSig => {
-                        result.push_str("  - Input Script Signatures\n")
-                    }
-                    TxBreaker::flags::InvalidationFlag::InputSequence => {
-                        result.push_str("  - Input Sequences\n")
-                    }
-                    TxBreaker::flags::InvalidationFlag::OutputAmount => {
-                        result.push_str("  - Output Amounts\n")
-                    }
-                    TxBreaker::flags::InvalidationFlag::OutputScriptPubKey => {
-                        result.push_str("  - Output Script PubKeys\n")
-                    }
-                    TxBreaker::flags::InvalidationFlag::WitnessData => {
-                        result.push_str("  - Witness Data\n")
-                    }
-                    TxBreaker::flags::InvalidationFlag::Locktime => {
-                        result.push_str("  - Locktime\n")
-                    }
-                    _ => {}
-                }
+    /// A compact block with its `getblocktxn` and `blocktxn` payloads,
+    /// optionally broken.
+    pub fn compact_block(
+        tx_count: u32,
+        prefill: &[usize],
+        breakage: Option<CompactBlockBreak>,
+        seed: Option<u64>,
+    ) -> Result<CompactBlockReport> {
+        let mut compact_block = Self::random_compact_block(tx_count, prefill, seed)?;
+        if let Some(breakage) = breakage {
+            if !CompactBlockProcessor::process(&mut compact_block, breakage) {
+                return Err(Error::InvalidConfig(format!(
+                    "cannot apply {} to a compact block with {} short IDs",
+                    breakage.name(),
+                    compact_block.compact.short_ids.len()
+                )));
             }
         }
 
-        // Display results
-        result.push_str(&format!("\nInputed Transaction:\n{transaction}\n\n"));
-        result.push_str(&format!("Invalidated Transaction:\n{invalid_tx:#?}"));
-        result.push_str(&format!(
-            "Invalidated Raw Transaction:\n{:#?}\n\n",
-            encode::serialize_hex(&invalid_tx)
-        ));
+        Ok(CompactBlockReport {
+            block_hash: compact_block.block.block_hash().to_string(),
+            nonce: compact_block.compact.nonce,
+            short_ids: compact_block.compact.short_ids.iter().map(hex::encode).collect(),
+            prefilled: CompactBlock::prefilled_indexes(&compact_block.compact),
+            requested: compact_block.request.indexes.clone(),
+            mutation: breakage.map(|breakage| {
+                MutationReport::new(
+                    breakage,
+                    breakage.description(),
+                    Some(breakage.expected_failure()),
+                )
+            }),
+            block_hex: encode::serialize_hex(&compact_block.block),
+            cmpctblock_hex: encode::serialize_hex(&compact_block.compact),
+            getblocktxn_hex: encode::serialize_hex(&compact_block.request),
+            blocktxn_hex: encode::serialize_hex(&compact_block.response),
+        })
+    }
+
+    pub fn regtest_invocation(name_of_wallet: &str, mode_of_cli: &str) -> RegtestManager {
+        RegtestManager::new(name_of_wallet, mode_of_cli)
+    }
+
+    /// Apply the breakages in `flags` to `transaction`.
+    pub fn invalidate_transaction(
+        transaction: Transaction,
+        flags: &HashSet<InvalidationFlag>,
+        seed: Option<u64>,
+    ) -> Result<Transaction> {
+        if flags.is_empty() {
+            return Err(Error::NoFlags);
+        }
+
+        if let Some(seed) = seed {
+            rng::set_seed(seed);
+        }
+
+        TransactionInvalidator::invalidate(transaction, flags)
+    }
+
+    /// Apply the breakages of `config` to `header`.
+    pub fn break_header(header: &Header, config: ProcessingConfig) -> Result<Header> {
+        if config.fields_to_modify.is_empty() {
+            return Err(Error::NoFlags);
+        }
+
+        Ok(BlockProcessor::new(config).process_block_header(header))
+    }
 
-        result
+    /// Apply the header and transaction breakages of `config` to `block`.
+    pub fn break_full_block(block: &Block, config: ProcessingConfig) -> Result<Block> {
+        if config.fields_to_modify.is_empty() {
+            return Err(Error::NoFlags);
+        }
+
+        BlockProcessor::new(config).process_block(block)
     }
 
+    /// Decode `transaction` and break it according to CLI-style `cli_flags`.
+    pub fn break_transaction(
+        transaction: String,
+        cli_flags: Vec<String>,
+        seed: Option<u64>,
+    ) -> Result<BreakTransactionReport> {
+        let invalidation_flags = Self::parse_cli_flags_to_invalidation_flags(cli_flags)?;
+        if invalidation_flags.is_empty() {
+            return Err(Error::NoFlags);
+        }
+
+        let decoded_tx = Self::decode_raw_transaction(transaction.clone())?;
+        let invalid_tx = Self::invalidate_transaction(decoded_tx, &invalidation_flags, seed)?;
+
+        // List which fields are being invalidated, in a stable order
+        let mutations = InvalidationFlag::FIELDS
+            .iter()
+            .filter(|flag| {
+                invalidation_flags.contains(&InvalidationFlag::All)
+                    || invalidation_flags.contains(flag)
+            })
+            .chain(
+                TaprootBreak::WITNESS
+                    .map(InvalidationFlag::Taproot)
+                    .iter()
+                    .chain(NestedSegwitBreak::ALL.map(InvalidationFlag::NestedSegwit).iter())
+                    .filter(|flag| invalidation_flags.contains(flag)),
+            )
+            .map(|flag| MutationReport::new(flag, flag.description(), flag.expected_failure()))
+            .collect();
+
+        Ok(BreakTransactionReport {
+            mutations,
+            original_hex: transaction,
+            broken: TransactionReport::from(&invalid_tx),
+        })
+    }
+
+    /// Decode `raw_block`, either an 80-byte header or a full serialized
+    /// block, and break it according to CLI-style `cli_flags` and `cli_config`.
     pub fn break_block(
-        block_header: String,
+        raw_block: String,
         cli_flags: Vec<String>,
         cli_config: Vec<String>,
-    ) -> String {
-        // Parse CLI flags to BlockField vector
-        let block_fields = Self::parse_cli_flags_to_block_fields(cli_flags);
-
+    ) -> Result<BreakBlockReport> {
+        let block_fields = Self::parse_cli_flags_to_block_fields(cli_flags)?;
         if block_fields.is_empty() {
-            return "No invalidation flags specified. Use 'help' for usage information."
-                .to_string();
+            return Err(Error::NoFlags);
         }
 
-        // Parse configuration options
         let processing_config =
-            Self::parse_cli_config_to_processing_config(cli_config, block_fields);
+            Self::parse_cli_config_to_processing_config(cli_config, block_fields)?;
 
-        // Decode the block header
-        let decoded_header = match Self::decoder_block_header(block_header.clone()) {
-            Ok(header) => header,
-            Err(e) => return format!("Error decoding block header: {e}"),
+        // A header alone can only have its own fields broken
+        let raw_block = raw_block.trim().to_string();
+        let (original_block, full_block) = if raw_block.len() == HEADER_HEX_LEN {
+            let header = Self::decoder_block_header(raw_block)?;
+            (decoder::BlockUtils::create_minimal_block_from_header(header), false)
+        } else {
+            (Self::decode_full_block(raw_block)?, true)
         };
+        let fields = &processing_config.fields_to_modify;
+        if !full_block && fields.iter().any(BlockField::is_body) {
+            return Err(Error::InvalidConfig(
+                "transaction breakages need a full block, got an 80-byte header".to_string(),
+            ));
+        }
 
-        // Create block from header for processing
-        let original_block =
-            decoder::BlockUtils::create_minimal_block_from_header(decoded_header);
-
-        // Process the block using BlockProcessor
-        let processor = BlockBreaker::BlockProcessor::new(processing_config.clone());
-        let broken_block = processor.process_block(&original_block);
-
-        // Build the result string
-        let mut result = String::new();
+        let broken_block = Self::break_full_block(&original_block, processing_config.clone())?;
 
         // List which fields are being invalidated
-        result.push_str("Breaking the following block fields:\n");
-
-        if processing_config
-            .fields_to_modify
-            .contains(&BlockBreaker::BlockField::All)
-        {
-            result.push_str("  - ALL FIELDS\n");
-        } else {
-            for field in &processing_config.fields_to_modify {
-                match field {
-                    BlockBreaker::BlockField::Version => result.push_str("  - Block Version\n"),
-                    BlockBreaker::BlockField::PrevBlockHash => {
-                        result.push_str("  - Previous Block Hash\n")
-                    }
-                    BlockBreaker::BlockField::MerkleRoot => result.push_str("  - Merkle Root\n"),
-                    BlockBreaker::BlockField::Timestamp => result.push_str("  - Timestamp\n"),
-                    BlockBreaker::BlockField::Bits => result.push_str("  - Difficulty Bits\n"),
-                    BlockBreaker::BlockField::Nonce => result.push_str("  - Nonce\n"),
-                    _ => {}
-                }
-            }
-        }
+        let mutations = BlockField::FIELDS
+            .iter()
+            .filter(|field| fields.contains(&BlockField::All) || fields.contains(field))
+            .chain(
+                BlockField::EXPLICIT_HEADER_FIELDS
+                    .iter()
+                    .chain(BlockField::BODY_FIELDS.iter())
+                    .filter(|field| fields.contains(field)),
+            )
+            .map(|field| {
+                // Every block this tool generates is on regtest
+                let expected_failure =
+                    field.expected_failure_for(&broken_block.header, Network::Regtest);
+                MutationReport::new(field, field.description(), expected_failure)
+            })
+            .collect();
 
         // Add configuration info
+        let mut settings = vec![];
         if let Some(version_override) = processing_config.version_override {
-            result.push_str(&format!("  - Version Override: {version_override}\n"));
+            settings.push(format!("Version Override: {version_override}"));
         }
         if let Some(timestamp_offset) = processing_config.timestamp_offset {
-            result.push_str(&format!(
-                "  - Timestamp Offset: {timestamp_offset} seconds\n"
-            ));
+            settings.push(format!("Timestamp Offset: {timestamp_offset} seconds"));
         }
         if !processing_config.randomize_hashes {
-            result.push_str("  - Using zero hashes instead of random\n");
+            settings.push("Using zero hashes instead of random".to_string());
+        }
+        if let Some(seed) = processing_config.seed {
+            settings.push(format!("Seed: {seed}"));
         }
 
-        // Display original header info
-        result.push_str("\nOriginal Block Header:\n");
-        result.push_str(&format!(
-            "  Version: {}\n",
-            decoded_header.version.to_consensus()
-        ));
-        result.push_str(&format!(
-            "  Previous Block: {}\n",
-            decoded_header.prev_blockhash
-        ));
-        result.push_str(&format!("  Merkle Root: {}\n", decoded_header.merkle_root));
-        result.push_str(&format!("  Timestamp: {}\n", decoded_header.time));
-        result.push_str(&format!(
-            "  Bits: 0x{:08x}\n",
-            decoded_header.bits.to_consensus()
-        ));
-        result.push_str(&format!("  Nonce: {}\n", decoded_header.nonce));
-        result.push_str(&format!("  Block Hash: {}\n", decoded_header.block_hash()));
-
-        // Display broken header info
-        result.push_str("\nBroken Block Header:\n");
-        result.push_str(&format!(
-            "  Version: {}\n",
-            broken_block.header.version.to_consensus()
-        ));
-        result.push_str(&format!(
-            "  Previous Block: {}\n",
-            broken_block.header.prev_blockhash
-        ));
-        result.push_str(&format!(
-            "  Merkle Root: {}\n",
-            broken_block.header.merkle_root
-        ));
-        result.push_str(&format!("  Timestamp: {}\n", broken_block.header.time));
-        result.push_str(&format!(
-            "  Bits: 0x{:08x}\n",
-            broken_block.header.bits.to_consensus()
-        ));
-        result.push_str(&format!("  Nonce: {}\n", broken_block.header.nonce));
-        result.push_str(&format!(
-            "  Block Hash: {}\n",
-            broken_block.header.block_hash()
-        ));
-
-        // Display hex representation of broken header
-        let broken_header_hex = hex::encode(encode::serialize(&broken_block.header));
-        result.push_str(&format!(
-            "\nBroken Block Header (Hex):\n{broken_header_hex}\n"
-        ));
-
-        result
+        Ok(BreakBlockReport {
+            mutations,
+            settings,
+            original_header: HeaderReport::from(&original_block.header),
+            broken_header: HeaderReport::from(&broken_block.header),
+            broken_block_hex: full_block.then(|| encode::serialize_hex(&broken_block)),
+        })
     }
 
+    /// Parse `--version`-style flags into transaction breakages.
     pub fn parse_cli_flags_to_invalidation_flags(
         cli_flags: Vec<String>,
-    ) -> HashSet<TxBreaker::flags::InvalidationFlag> {
+    ) -> Result<HashSet<InvalidationFlag>> {
         let mut flags = HashSet::new();
 
         for flag in cli_flags {
             let invalidation_flag = match flag.as_str() {
-                "--version" => Some(TxBreaker::flags::InvalidationFlag::Version),
-                "--txid" => Some(TxBreaker::flags::InvalidationFlag::InputTxid),
-                "--vout" => Some(TxBreaker::flags::InvalidationFlag::InputVout),
-                "--script-sig" => Some(TxBreaker::flags::InvalidationFlag::InputScriptSig),
-                "--sequence" => Some(TxBreaker::flags::InvalidationFlag::InputSequence),
-                "--amount" => Some(TxBreaker::flags::InvalidationFlag::OutputAmount),
-                "--script-pubkey" => Some(TxBreaker::flags::InvalidationFlag::OutputScriptPubKey),
-                "--witness" => Some(TxBreaker::flags::InvalidationFlag::WitnessData),
-                "--locktime" => Some(TxBreaker::flags::InvalidationFlag::Locktime),
-                "--all" => Some(TxBreaker::flags::InvalidationFlag::All),
-                _ => {
-                    println!("Warning: Unknown flag '{flag}' ignored");
-                    None
-                }
+                "--version" => InvalidationFlag::Version,
+                "--txid" => InvalidationFlag::InputTxid,
+                "--vout" => InvalidationFlag::InputVout,
+                "--script-sig" => InvalidationFlag::InputScriptSig,
+                "--sequence" => InvalidationFlag::InputSequence,
+                "--amount" => InvalidationFlag::OutputAmount,
+                "--script-pubkey" => InvalidationFlag::OutputScriptPubKey,
+                "--witness" => InvalidationFlag::WitnessData,
+                "--locktime" => InvalidationFlag::Locktime,
+                "--all" => InvalidationFlag::All,
+                other => match other.strip_prefix("--").map(str::parse) {
+                    Some(Ok(
+                        flag @ (InvalidationFlag::Taproot(_) | InvalidationFlag::NestedSegwit(_)),
+                    )) => flag,
+                    _ => return Err(Error::InvalidFlag(flag)),
+                },
             };
 
-            if let Some(flag) = invalidation_flag {
-                flags.insert(flag);
-            }
+            flags.insert(invalidation_flag);
         }
 
-        flags
+        Ok(flags)
     }
 
+    /// Parse `--prev-hash`-style flags into block header and transaction breakages.
     pub fn parse_cli_flags_to_block_fields(
         cli_flags: Vec<String>,
-    ) -> Vec<BlockBreaker::BlockField> {
+    ) -> Result<Vec<BlockField>> {
         let mut fields = Vec::new();
 
         for flag in cli_flags {
             let block_field = match flag.as_str() {
-                "--version" => Some(BlockBreaker::BlockField::Version),
-                "--prev-hash" => Some(BlockBreaker::BlockField::PrevBlockHash),
-                "--merkle-root" => Some(BlockBreaker::BlockField::MerkleRoot),
-                "--timestamp" => Some(BlockBreaker::BlockField::Timestamp),
-                "--bits" => Some(BlockBreaker::BlockField::Bits),
-                "--nonce" => Some(BlockBreaker::BlockField::Nonce),
-                "--all" => Some(BlockBreaker::BlockField::All),
-                _ => {
-                    println!("Warning: Unknown block field flag '{flag}' ignored");
-                    None
-                }
+                "--version" => BlockField::Version,
+                "--prev-hash" => BlockField::PrevBlockHash,
+                "--merkle-root" => BlockField::MerkleRoot,
+                "--timestamp" => BlockField::Timestamp,
+                "--bits" => BlockField::Bits,
+                "--bits-easier" => BlockField::BitsOneUnitEasier,
+                "--bits-harder" => BlockField::BitsOneUnitHarder,
+                "--version-below-bip34" => BlockField::VersionBelowBip34,
+                "--version-below-bip66" => BlockField::VersionBelowBip66,
+                "--version-below-bip65" => BlockField::VersionBelowBip65,
+                "--nonce" => BlockField::Nonce,
+                "--all" => BlockField::All,
+                "--remove-coinbase" => BlockField::RemoveCoinbase,
+                "--reorder-txs" => BlockField::ReorderTxs,
+                "--duplicate-tx" => BlockField::DuplicateTx,
+                "--tamper-tx" => BlockField::TamperTx,
+                "--duplicate-tail-txs" => BlockField::DuplicateTailTxs,
+                "--insert-64-byte-tx" => BlockField::InsertInnerNodeTx,
+                "--coinbase-overpay" => BlockField::CoinbaseOverpay,
+                "--coinbase-wrong-height" => BlockField::CoinbaseWrongHeight,
+                "--coinbase-no-height" => BlockField::CoinbaseMissingHeight,
+                "--coinbase-short-script" => BlockField::CoinbaseShortScript,
+                "--coinbase-long-script" => BlockField::CoinbaseLongScript,
+                "--coinbase-prevout" => BlockField::CoinbaseNonNullPrevout,
+                "--multiple-coinbases" => BlockField::MultipleCoinbases,
+                "--coinbase-no-witness-nonce" => BlockField::CoinbaseMissingWitnessNonce,
+                "--commitment-wrong-hash" => BlockField::WitnessCommitmentWrongHash,
+                "--commitment-missing" => BlockField::WitnessCommitmentMissing,
+                "--commitment-not-last" => BlockField::WitnessCommitmentNotLast,
+                "--commitment-wrong-magic" => BlockField::WitnessCommitmentWrongMagic,
+                "--commitment-nonce-size" => BlockField::WitnessReservedValueSize,
+                _ => return Err(Error::InvalidFlag(flag)),
             };
 
-            if let Some(field) = block_field {
-                fields.push(field);
-            }
+            fields.push(block_field);
         }
 
-        fields
+        Ok(fields)
     }
 
+    /// Parse `--version-override=N`, `--timestamp-offset=N`, `--seed=N` and
+    /// `--zero-hashes` into a header processing config for `fields`.
     pub fn parse_cli_config_to_processing_config(
         cli_config: Vec<String>,
-        fields: Vec<BlockBreaker::BlockField>,
-    ) -> BlockBreaker::ProcessingConfig {
-        let mut config = BlockBreaker::ProcessingConfig {
+        fields: Vec<BlockField>,
+    ) -> Result<ProcessingConfig> {
+        let mut config = ProcessingConfig {
             fields_to_modify: fields,
             version_override: None,
             timestamp_offset: None,
             randomize_hashes: true, // default to random hashes
+            seed: None,
         };
 
         for config_option in cli_config {
-            if config_option.starts_with("--version-override=") {
-                if let Some(value_str) = config_option.strip_prefix("--version-override=") {
-                    if let Ok(value) = value_str.parse::<i32>() {
-                        config.version_override = Some(value);
-                    } else {
-                        println!(
-                            "Warning: Invalid version override value '{value_str}' ignored"
-                        );
-                    }
-                }
-            } else if config_option.starts_with("--timestamp-offset=") {
-                if let Some(value_str) = config_option.strip_prefix("--timestamp-offset=") {
-                    if let Ok(value) = value_str.parse::<i64>() {
-                        config.timestamp_offset = Some(value);
-                    } else {
-                        println!(
-                            "Warning: Invalid timestamp offset value '{value_str}' ignored"
-                        );
-                    }
-                }
+            let invalid = || Error::InvalidConfig(config_option.clone());
+
+            if let Some(value_str) = config_option.strip_prefix("--version-override=") {
+                config.version_override = Some(value_str.parse().map_err(|_| invalid())?);
+            } else if let Some(value_str) = config_option.strip_prefix("--timestamp-offset=") {
+                config.timestamp_offset = Some(value_str.parse().map_err(|_| invalid())?);
+            } else if let Some(value_str) = config_option.strip_prefix("--seed=") {
+                config.seed = Some(value_str.parse().map_err(|_| invalid())?);
             } else if config_option == "--zero-hashes" {
                 config.randomize_hashes = false;
             } else {
-                println!("Warning: Unknown config option '{config_option}' ignored");
+                return Err(invalid());
             }
         }
 
-        config
+        Ok(config)
     }
 }
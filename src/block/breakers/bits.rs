@@ -1,4 +1,12 @@
-use bitcoin::pow::CompactTarget;
+use bitcoin::{
+    block::Header,
+    params::Params,
+    pow::{CompactTarget, Target, Work},
+    Network,
+};
+
+// Denominator a difficulty factor is rounded to
+const FACTOR_SCALE: u64 = 1 << 16;
 
 /// Processor for difficulty bits field modifications
 pub struct BitsProcessor;
@@ -33,7 +41,7 @@ impl BitsProcessor {
     /// Generate random difficulty bits
     pub fn generate_random_bits() -> u32 {
         use rand::Rng;
-        let mut rng = rand::rng();
+        let mut rng = crate::rng::rng();
         
         // Generate within reasonable difficulty range
         let exponent = rng.random_range(0x1d..=0x20); // Reasonable exponent range
@@ -42,61 +50,106 @@ impl BitsProcessor {
         (exponent << 24) | mantissa
     }
 
-    /// Increment difficulty (make mining harder)
+    /// Divide the target by `factor` (make mining harder)
     pub fn increase_difficulty(bits: u32, factor: f64) -> u32 {
-        let target = Self::bits_to_target(bits);
-        let new_target = (target as f64 / factor) as u32;
-        Self::target_to_bits(new_target.max(1)) // Ensure non-zero
+        let (numerator, denominator) = Self::factor_to_ratio(factor);
+        let target = Self::scale_target(Self::bits_to_target(bits), denominator, numerator);
+        // Keep the smallest non-zero target rather than an impossible one
+        let mut smallest = [0u8; 32];
+        smallest[31] = 1;
+        Self::target_to_bits(target.max(Target::from_be_bytes(smallest)))
     }
 
-    /// Decrease difficulty (make mining easier)
+    /// Multiply the target by `factor` (make mining easier)
     pub fn decrease_difficulty(bits: u32, factor: f64) -> u32 {
-        let target = Self::bits_to_target(bits);
-        let new_target = (target as f64 * factor) as u32;
-        Self::target_to_bits(new_target)
+        let (numerator, denominator) = Self::factor_to_ratio(factor);
+        let target = Self::scale_target(Self::bits_to_target(bits), numerator, denominator);
+        Self::target_to_bits(target)
     }
 
-    /// Convert compact bits representation to target value (simplified)
-    pub fn bits_to_target(bits: u32) -> u32 {
-        let exponent = (bits >> 24) & 0xff;
-        let mantissa = bits & 0xffffff;
-        
-        if exponent <= 3 {
-            mantissa >> (8 * (3 - exponent))
-        } else {
-            mantissa << (8 * (exponent - 3))
-        }
+    /// Expand compact bits to the full 256-bit target; negative values are zero
+    pub fn bits_to_target(bits: u32) -> Target {
+        Target::from_compact(CompactTarget::from_consensus(bits))
     }
 
-    /// Convert target value to compact bits representation (simplified)
-    pub fn target_to_bits(target: u32) -> u32 {
-        if target == 0 {
-            return 0;
+    /// Compact a target, truncating it to the three most significant bytes
+    pub fn target_to_bits(target: Target) -> u32 {
+        target.to_compact_lossy().to_consensus()
+    }
+
+    /// Expected number of hashes to find a block meeting the bits
+    pub fn bits_to_work(bits: u32) -> Work {
+        Self::bits_to_target(bits).to_work()
+    }
+
+    /// Bits Bitcoin Core requires after a difficulty adjustment window whose
+    /// first block has `first_time` and whose last block has `last_time`.
+    ///
+    /// As in `CalculateNextWorkRequired`, the timespan only covers the 2015
+    /// intervals between the first and last block of the 2016-block window,
+    /// is clamped to between a quarter and four times two weeks, and the new
+    /// target is capped at the network's proof-of-work limit. Networks
+    /// without retargeting, like regtest, keep `last_bits`.
+    pub fn retarget(last_bits: u32, first_time: u32, last_time: u32, network: Network) -> u32 {
+        let params = Params::new(network);
+        let timespan = (last_time as i64 - first_time as i64).max(0) as u64;
+        // Bits over the limit never get this far in Core, and would overflow here
+        let last_target = Self::bits_to_target(last_bits).min(params.max_attainable_target);
+        CompactTarget::from_next_work_required(last_target.to_compact_lossy(), timespan, &params)
+            .to_consensus()
+    }
+
+    /// Bits required after `window`, the headers of a full difficulty
+    /// adjustment window in order; None when it is not exactly that long.
+    pub fn retarget_window(window: &[Header], network: Network) -> Option<u32> {
+        let interval = Params::new(network).difficulty_adjustment_interval();
+        match window {
+            [first, .., last] if window.len() as u64 == interval => {
+                Some(Self::retarget(last.bits.to_consensus(), first.time, last.time, network))
+            }
+            _ => None,
         }
+    }
 
-        let mut size = (32 - target.leading_zeros()) as u8;
-        let mut compact = if size <= 3 {
-            target << (8 * (3 - size as u32))
+    /// Bits one mantissa unit easier than `bits`, at the same exponent when
+    /// the mantissa allows it
+    pub fn one_unit_easier(bits: u32) -> u32 {
+        let (exponent, mantissa) = (bits >> 24, bits & 0x00ff_ffff);
+        if mantissa >= 0x7f_ffff {
+            // 0x800000 would be negative, continue one exponent up
+            ((exponent + 1) << 24) | 0x00_8000
         } else {
-            target >> (8 * (size as u32 - 3))
-        };
-
-        // If the sign bit is set, we need to adjust
-        if (compact & 0x00800000) != 0 {
-            compact >>= 8;
-            size += 1;
+            (exponent << 24) | (mantissa + 1)
         }
+    }
 
-        ((size as u32) << 24) | compact
+    /// Bits one mantissa unit harder than `bits`, at the same exponent when
+    /// the mantissa allows it
+    pub fn one_unit_harder(bits: u32) -> u32 {
+        let (exponent, mantissa) = (bits >> 24, bits & 0x00ff_ffff);
+        if mantissa == 0 {
+            return bits;
+        }
+        if mantissa <= 0x00_8000 && exponent > 3 {
+            // Below 0x008000 the same target is written one exponent down
+            ((exponent - 1) << 24) | ((mantissa << 8) - 1)
+        } else {
+            (exponent << 24) | mantissa.saturating_sub(1)
+        }
     }
 
-    /// Validate bits format
+    /// Validate bits format: Bitcoin Core rejects negative, overflowing and
+    /// zero targets
     pub fn is_valid_bits(bits: u32) -> bool {
-        let exponent = (bits >> 24) & 0xff;
-        let mantissa = bits & 0xffffff;
-        
-        // Basic validation: exponent should be reasonable, mantissa non-zero for valid target
-        (0x03..=0x20).contains(&exponent) && mantissa != 0
+        let exponent = bits >> 24;
+        let mantissa = bits & 0x007f_ffff;
+        let negative = bits & 0x0080_0000 != 0 && mantissa != 0;
+        let overflow = mantissa != 0
+            && (exponent > 34
+                || (mantissa > 0xff && exponent > 33)
+                || (mantissa > 0xffff && exponent > 32));
+
+        !negative && !overflow && Self::bits_to_target(bits) != Target::ZERO
     }
 
     /// Check if bits represent minimum difficulty
@@ -119,6 +172,49 @@ impl BitsProcessor {
             DifficultyLevel::VeryHard => 0x1c0fffff,
         }
     }
+
+    // Round a positive factor to a ratio with FACTOR_SCALE as denominator
+    fn factor_to_ratio(factor: f64) -> (u64, u64) {
+        let numerator = (factor * FACTOR_SCALE as f64).round();
+        ((numerator as u64).max(1), FACTOR_SCALE)
+    }
+
+    // target * numerator / denominator on 64-bit limbs, saturating at the
+    // largest 256-bit value
+    fn scale_target(target: Target, numerator: u64, denominator: u64) -> Target {
+        let bytes = target.to_be_bytes();
+        let limbs: Vec<u64> = bytes
+            .chunks(8)
+            .map(|chunk| u64::from_be_bytes(chunk.try_into().unwrap()))
+            .collect();
+
+        let mut product = [0u64; 5];
+        let mut carry = 0u128;
+        for (index, limb) in limbs.iter().enumerate().rev() {
+            let value = *limb as u128 * numerator as u128 + carry;
+            product[index + 1] = value as u64;
+            carry = value >> 64;
+        }
+        product[0] = carry as u64;
+
+        let denominator = denominator.max(1) as u128;
+        let mut quotient = [0u64; 5];
+        let mut remainder = 0u128;
+        for (index, limb) in product.iter().enumerate() {
+            let value = (remainder << 64) | *limb as u128;
+            quotient[index] = (value / denominator) as u64;
+            remainder = value % denominator;
+        }
+
+        if quotient[0] != 0 {
+            return Target::from_be_bytes([0xff; 32]);
+        }
+        let mut scaled = [0u8; 32];
+        for (index, limb) in quotient[1..].iter().enumerate() {
+            scaled[index * 8..(index + 1) * 8].copy_from_slice(&limb.to_be_bytes());
+        }
+        Target::from_be_bytes(scaled)
+    }
 }
 
 /// Difficulty levels for testing
@@ -1,7 +1,14 @@
 use bitcoin::blockdata::block::{Block, Header};
+use bitcoin::{params::Params, Network};
 use crate::block::decoder::BlockUtils;
+use crate::{rng, Error, Result};
 
-use super::header::HeaderProcessor;
+use crate::block::miner::Miner;
+
+use super::{
+    bits::BitsProcessor, body::BodyProcessor, coinbase::CoinbaseProcessor, header::HeaderProcessor,
+    merkle_tree::MerkleTreeProcessor, witness_commitment::WitnessCommitmentProcessor,
+};
 
 // Re-export the enum and config from the original design
 #[derive(Debug, Clone, PartialEq)]
@@ -12,7 +19,184 @@ pub enum BlockField {
     Timestamp,
     Bits,
     Nonce,
+    /// Header fields only; body breakages must be requested explicitly.
     All,
+    BitsOneUnitEasier,
+    BitsOneUnitHarder,
+    VersionBelowBip34,
+    VersionBelowBip66,
+    VersionBelowBip65,
+    RemoveCoinbase,
+    ReorderTxs,
+    DuplicateTx,
+    TamperTx,
+    DuplicateTailTxs,
+    InsertInnerNodeTx,
+    CoinbaseOverpay,
+    CoinbaseWrongHeight,
+    CoinbaseMissingHeight,
+    CoinbaseShortScript,
+    CoinbaseLongScript,
+    CoinbaseNonNullPrevout,
+    MultipleCoinbases,
+    CoinbaseMissingWitnessNonce,
+    WitnessCommitmentWrongHash,
+    WitnessCommitmentMissing,
+    WitnessCommitmentNotLast,
+    WitnessCommitmentWrongMagic,
+    WitnessReservedValueSize,
+}
+
+impl BlockField {
+    /// Every field `All` stands for.
+    pub const FIELDS: [BlockField; 6] = [
+        Self::Version,
+        Self::PrevBlockHash,
+        Self::MerkleRoot,
+        Self::Timestamp,
+        Self::Bits,
+        Self::Nonce,
+    ];
+
+    /// Header breakages `All` leaves out, which must be requested explicitly.
+    pub const EXPLICIT_HEADER_FIELDS: [BlockField; 5] = [
+        Self::BitsOneUnitEasier,
+        Self::BitsOneUnitHarder,
+        Self::VersionBelowBip34,
+        Self::VersionBelowBip66,
+        Self::VersionBelowBip65,
+    ];
+
+    /// Breakages of the transaction list, which need a full block.
+    pub const BODY_FIELDS: [BlockField; 19] = [
+        Self::RemoveCoinbase,
+        Self::ReorderTxs,
+        Self::DuplicateTx,
+        Self::TamperTx,
+        Self::DuplicateTailTxs,
+        Self::InsertInnerNodeTx,
+        Self::CoinbaseOverpay,
+        Self::CoinbaseWrongHeight,
+        Self::CoinbaseMissingHeight,
+        Self::CoinbaseShortScript,
+        Self::CoinbaseLongScript,
+        Self::CoinbaseNonNullPrevout,
+        Self::MultipleCoinbases,
+        Self::CoinbaseMissingWitnessNonce,
+        Self::WitnessCommitmentWrongHash,
+        Self::WitnessCommitmentMissing,
+        Self::WitnessCommitmentNotLast,
+        Self::WitnessCommitmentWrongMagic,
+        Self::WitnessReservedValueSize,
+    ];
+
+    pub fn is_body(&self) -> bool {
+        Self::BODY_FIELDS.contains(self)
+    }
+
+    /// Whether the field is a header breakage meant to fail a contextual
+    /// check, which blocks are re-mined to so proof of work still passes.
+    pub fn is_contextual_header(&self) -> bool {
+        matches!(self, Self::Version | Self::Timestamp)
+            || Self::EXPLICIT_HEADER_FIELDS.contains(self)
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Version => "Block Version",
+            Self::PrevBlockHash => "Previous Block Hash",
+            Self::MerkleRoot => "Merkle Root",
+            Self::Timestamp => "Timestamp",
+            Self::Bits => "Difficulty Bits",
+            Self::Nonce => "Nonce",
+            Self::All => "All Fields",
+            Self::BitsOneUnitEasier => "Difficulty Bits One Unit Easier",
+            Self::BitsOneUnitHarder => "Difficulty Bits One Unit Harder",
+            Self::VersionBelowBip34 => "Version Below BIP34 Minimum",
+            Self::VersionBelowBip66 => "Version Below BIP66 Minimum",
+            Self::VersionBelowBip65 => "Version Below BIP65 Minimum",
+            Self::RemoveCoinbase => "Remove Coinbase",
+            Self::ReorderTxs => "Transaction Order",
+            Self::DuplicateTx => "Duplicate Transaction",
+            Self::TamperTx => "Tampered Transaction",
+            Self::DuplicateTailTxs => "Duplicated Tail Transactions (CVE-2012-2459)",
+            Self::InsertInnerNodeTx => "64-byte Transaction",
+            Self::CoinbaseOverpay => "Coinbase Amount",
+            Self::CoinbaseWrongHeight => "Coinbase Height",
+            Self::CoinbaseMissingHeight => "Missing Coinbase Height",
+            Self::CoinbaseShortScript => "Short Coinbase Script",
+            Self::CoinbaseLongScript => "Long Coinbase Script",
+            Self::CoinbaseNonNullPrevout => "Coinbase Prevout",
+            Self::MultipleCoinbases => "Multiple Coinbases",
+            Self::CoinbaseMissingWitnessNonce => "Coinbase Witness Reserved Value",
+            Self::WitnessCommitmentWrongHash => "Witness Commitment Hash",
+            Self::WitnessCommitmentMissing => "Missing Witness Commitment",
+            Self::WitnessCommitmentNotLast => "Witness Commitment Position",
+            Self::WitnessCommitmentWrongMagic => "Witness Commitment Magic",
+            Self::WitnessReservedValueSize => "Witness Reserved Value Size",
+        }
+    }
+
+    /// Bitcoin Core reject reason the mutation is expected to trigger once
+    /// proof of work passes, see `expected_failure_for`.
+    pub fn expected_failure(&self) -> Option<&'static str> {
+        match self {
+            Self::All => None,
+            // An override can set any version, so only the default is labelled
+            Self::Version => Some("bad-version"),
+            Self::VersionBelowBip34 | Self::VersionBelowBip66 | Self::VersionBelowBip65 => {
+                Some("bad-version")
+            }
+            Self::PrevBlockHash => Some("prev-blk-not-found"),
+            Self::MerkleRoot => Some("bad-txnmrklroot"),
+            Self::Timestamp => Some("time-too-new"),
+            Self::Bits | Self::BitsOneUnitEasier | Self::BitsOneUnitHarder => {
+                Some("bad-diffbits")
+            }
+            Self::Nonce => Some("high-hash"),
+            Self::RemoveCoinbase | Self::ReorderTxs => Some("bad-cb-missing"),
+            Self::DuplicateTx => Some("bad-txns-duplicate"),
+            Self::TamperTx => Some("bad-txnmrklroot"),
+            Self::DuplicateTailTxs => Some("bad-txns-duplicate"),
+            // Only relay policy rejects it, as tx-size-small
+            Self::InsertInnerNodeTx => None,
+            Self::CoinbaseOverpay => Some("bad-cb-amount"),
+            Self::CoinbaseWrongHeight | Self::CoinbaseMissingHeight => Some("bad-cb-height"),
+            Self::CoinbaseShortScript | Self::CoinbaseLongScript => Some("bad-cb-length"),
+            Self::CoinbaseNonNullPrevout => Some("bad-cb-missing"),
+            Self::MultipleCoinbases => Some("bad-cb-multiple"),
+            Self::CoinbaseMissingWitnessNonce | Self::WitnessReservedValueSize => {
+                Some("bad-witness-nonce-size")
+            }
+            Self::WitnessCommitmentWrongHash | Self::WitnessCommitmentNotLast => {
+                Some("bad-witness-merkle-match")
+            }
+            Self::WitnessCommitmentMissing | Self::WitnessCommitmentWrongMagic => {
+                Some("unexpected-witness")
+            }
+        }
+    }
+
+    /// Like `expected_failure`, for a header field of a block broken into
+    /// `header` on `network`. Core checks proof of work before anything
+    /// else, so a header missing its target, or with bits past the
+    /// proof-of-work limit, fails as high-hash whatever the field; one that
+    /// meets it leaves a broken nonce nothing to fail.
+    pub fn expected_failure_for(&self, header: &Header, network: Network) -> Option<&'static str> {
+        if self.is_body() {
+            return self.expected_failure();
+        }
+        let bits = header.bits.to_consensus();
+        let target = header.target();
+        let meets_target = BitsProcessor::is_valid_bits(bits)
+            && target <= Params::new(network).max_attainable_target
+            && header.validate_pow(target).is_ok();
+        match self {
+            _ if !meets_target => Some("high-hash"),
+            Self::Nonce => None,
+            _ => self.expected_failure(),
+        }
+    }
 }
 
 // Configuration for block processing
@@ -22,6 +206,8 @@ pub struct ProcessingConfig {
     pub version_override: Option<i32>,
     pub timestamp_offset: Option<i64>, // seconds to add/subtract
     pub randomize_hashes: bool,
+    /// Reseeds the shared generator before random replacement values are drawn.
+    pub seed: Option<u64>,
 }
 
 
@@ -50,6 +236,127 @@ impl BlockProcessor {
 
     // Process the entire block header based on configuration
     pub fn process_block_header(&self, header: &Header) -> Header {
+        if let Some(seed) = self.config.seed {
+            rng::set_seed(seed);
+        }
+
+        self.process_header_fields(header)
+    }
+
+    // Process an entire block: body breakages first, as they recompute the
+    // witness commitment and merkle root, then commitment breakages, then the
+    // header fields. Fails on a breakage the block gives no hold to, rather
+    // than returning it unchanged.
+    pub fn process_block(&self, block: &Block) -> Result<Block> {
+        if let Some(seed) = self.config.seed {
+            rng::set_seed(seed);
+        }
+
+        let mut modified_block = block.clone();
+        let mut body_changed = false;
+        for field in &self.config.fields_to_modify {
+            let body = &mut modified_block;
+            let applied = match field {
+                BlockField::RemoveCoinbase => {
+                    BodyProcessor::remove_coinbase(body);
+                    true
+                }
+                BlockField::ReorderTxs => {
+                    BodyProcessor::reorder_txs(body);
+                    true
+                }
+                BlockField::DuplicateTx => {
+                    BodyProcessor::duplicate_tx(body);
+                    true
+                }
+                // Keeps the header, so the block no longer matches it
+                BlockField::TamperTx => {
+                    BodyProcessor::tamper_tx(body);
+                    true
+                }
+                // Keeps the header, which the mutated block still matches
+                BlockField::DuplicateTailTxs => MerkleTreeProcessor::duplicate_tail(body),
+                BlockField::InsertInnerNodeTx => {
+                    MerkleTreeProcessor::insert_inner_node_tx(body);
+                    true
+                }
+                BlockField::CoinbaseOverpay => CoinbaseProcessor::overpay(body),
+                BlockField::CoinbaseWrongHeight => CoinbaseProcessor::wrong_height(body),
+                BlockField::CoinbaseMissingHeight => CoinbaseProcessor::missing_height(body),
+                BlockField::CoinbaseShortScript => CoinbaseProcessor::short_script(body),
+                BlockField::CoinbaseLongScript => CoinbaseProcessor::long_script(body),
+                BlockField::CoinbaseNonNullPrevout => CoinbaseProcessor::non_null_prevout(body),
+                BlockField::MultipleCoinbases => CoinbaseProcessor::multiple_coinbases(body),
+                BlockField::CoinbaseMissingWitnessNonce => {
+                    CoinbaseProcessor::missing_witness_nonce(body)
+                }
+                _ => continue,
+            };
+            if !applied {
+                return Err(Self::not_applicable(field));
+            }
+            if !matches!(field, BlockField::TamperTx | BlockField::DuplicateTailTxs) {
+                body_changed = true;
+            }
+        }
+        if body_changed {
+            BodyProcessor::refresh_commitments(&mut modified_block);
+        }
+
+        let mut commitment_changed = false;
+        for field in &self.config.fields_to_modify {
+            let body = &mut modified_block;
+            let applied = match field {
+                BlockField::WitnessCommitmentWrongHash => {
+                    WitnessCommitmentProcessor::wrong_hash(body)
+                }
+                BlockField::WitnessCommitmentMissing => WitnessCommitmentProcessor::remove(body),
+                BlockField::WitnessCommitmentNotLast => WitnessCommitmentProcessor::not_last(body),
+                BlockField::WitnessCommitmentWrongMagic => {
+                    WitnessCommitmentProcessor::wrong_magic(body)
+                }
+                BlockField::WitnessReservedValueSize => {
+                    WitnessCommitmentProcessor::reserved_value_size(body)
+                }
+                _ => continue,
+            };
+            if !applied {
+                return Err(Self::not_applicable(field));
+            }
+            commitment_changed = true;
+        }
+        if commitment_changed {
+            BodyProcessor::refresh_merkle_root(&mut modified_block);
+        }
+
+        // Re-mine blocks that met their target, so proof of work is not what
+        // rejects them
+        if (body_changed || commitment_changed)
+            && block.header.validate_pow(block.header.target()).is_ok()
+        {
+            Miner::mine(&mut modified_block, block.header.bits);
+        }
+        modified_block.header = self.process_header_fields(&modified_block.header);
+
+        // Contextual header breakages should not fail proof of work first, so
+        // re-mine to them unless other header fields are broken as well
+        let fields = &self.config.fields_to_modify;
+        if fields.iter().any(BlockField::is_contextual_header)
+            && fields.iter().all(|field| field.is_body() || field.is_contextual_header())
+            && block.header.validate_pow(block.header.target()).is_ok()
+        {
+            let bits = modified_block.header.bits;
+            Miner::mine(&mut modified_block, bits);
+        }
+
+        Ok(modified_block)
+    }
+
+    fn not_applicable(field: &BlockField) -> Error {
+        Error::InvalidConfig(format!("cannot apply {} to this block", field.description()))
+    }
+
+    fn process_header_fields(&self, header: &Header) -> Header {
         HeaderProcessor::process_header(
             header,
             self.config.version_override,
@@ -59,16 +366,6 @@ impl BlockProcessor {
         )
     }
 
-    // Process an entire block
-    pub fn process_block(&self, block: &Block) -> Block {
-        let modified_header = self.process_block_header(&block.header);
-        
-        Block {
-            header: modified_header,
-            txdata: block.txdata.clone(),
-        }
-    }
-
     // Update configuration
     pub fn update_config(&mut self, new_config: ProcessingConfig) {
         self.config = new_config;
@@ -110,12 +407,12 @@ impl BlockProcessor {
 pub struct BlockBreaker;
 
 impl BlockBreaker {
-    pub fn break_all_fields(block: &Block) -> Block {
+    pub fn break_all_fields(block: &Block) -> Result<Block> {
         let processor = BlockProcessor::with_default_config();
         processor.process_block(block)
     }
 
-    pub fn break_specific_fields(block: &Block, fields: Vec<BlockField>) -> Block {
+    pub fn break_specific_fields(block: &Block, fields: Vec<BlockField>) -> Result<Block> {
         let config = ProcessingConfig {
             fields_to_modify: fields,
             ..Default::default()
@@ -124,7 +421,7 @@ impl BlockBreaker {
         processor.process_block(block)
     }
 
-    pub fn break_with_config(block: &Block, config: ProcessingConfig) -> Block {
+    pub fn break_with_config(block: &Block, config: ProcessingConfig) -> Result<Block> {
         let processor = BlockProcessor::new(config);
         processor.process_block(block)
     }
@@ -140,7 +437,7 @@ impl BlockBreaker {
     }
 
     // Break single field with default settings
-    pub fn break_single_field(block: &Block, field: BlockField) -> Block {
+    pub fn break_single_field(block: &Block, field: BlockField) -> Result<Block> {
         Self::break_specific_fields(block, vec![field])
     }
 
@@ -151,12 +448,13 @@ impl BlockBreaker {
         version_override: Option<i32>,
         timestamp_offset: Option<i64>,
         randomize_hashes: bool,
-    ) -> Block {
+    ) -> Result<Block> {
         let config = ProcessingConfig {
             fields_to_modify: fields,
             version_override,
             timestamp_offset,
             randomize_hashes,
+            seed: None,
         };
         Self::break_with_config(block, config)
     }
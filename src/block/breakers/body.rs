@@ -0,0 +1,106 @@
+use bitcoin::{hashes::Hash, Amount, Block, ScriptBuf, TxMerkleNode};
+use rand::Rng;
+
+use crate::block::decoder::WITNESS_COMMITMENT_HEADER;
+use crate::rng;
+
+/// Processor for modifications of the transactions of a block
+pub struct BodyProcessor;
+
+impl BodyProcessor {
+    /// Drop the coinbase, leaving a regular transaction first
+    pub fn remove_coinbase(block: &mut Block) {
+        if block.txdata.first().is_some_and(|tx| tx.is_coinbase()) {
+            block.txdata.remove(0);
+        }
+        Self::refresh_commitments(block);
+    }
+
+    /// Rotate the transactions by one so the coinbase is no longer first
+    pub fn reorder_txs(block: &mut Block) {
+        if !block.txdata.is_empty() {
+            block.txdata.rotate_left(1);
+        }
+        Self::refresh_commitments(block);
+    }
+
+    /// Insert copies of a random transaction right after it, so that two of
+    /// them form a pair starting at an even position
+    ///
+    /// Core's mutated merkle check only sees duplicates hashed together,
+    /// i.e. an aligned pair; any other copy gets past it and fails later as
+    /// `bad-txns-inputs-missingorspent`. A transaction at an odd position
+    /// therefore gets two copies, the pair after it.
+    pub fn duplicate_tx(block: &mut Block) {
+        if block.txdata.is_empty() {
+            return;
+        }
+        let index = Self::random_index(block);
+        let copies = 1 + index % 2;
+        for _ in 0..copies {
+            block.txdata.insert(index + 1, block.txdata[index].clone());
+        }
+        Self::refresh_commitments(block);
+    }
+
+    /// Change an output value of a random transaction by one satoshi without
+    /// touching the header, so the claimed merkle root no longer matches
+    pub fn tamper_tx(block: &mut Block) {
+        if block.txdata.is_empty() {
+            return;
+        }
+        let index = Self::random_index(block);
+        if let Some(output) = block.txdata[index].output.first_mut() {
+            output.value = match output.value.checked_sub(Amount::ONE_SAT) {
+                Some(value) => value,
+                None => Amount::ONE_SAT,
+            };
+        }
+    }
+
+    /// Recompute the witness commitment and the merkle root after the
+    /// transaction list changed, so only the intended rule is broken
+    pub fn refresh_commitments(block: &mut Block) {
+        let reserved_value = block
+            .txdata
+            .first()
+            .and_then(|coinbase| coinbase.input.first())
+            .and_then(|input| input.witness.nth(0))
+            .map(<[u8]>::to_vec);
+
+        if let (Some(witness_root), Some(reserved_value)) = (block.witness_root(), reserved_value) {
+            let commitment = Block::compute_witness_commitment(&witness_root, &reserved_value);
+            if let Some(output) = block.txdata[0]
+                .output
+                .iter_mut()
+                .rev()
+                .find(|output| {
+                    let script = output.script_pubkey.as_bytes();
+                    script.len() >= 38 && script.starts_with(&WITNESS_COMMITMENT_HEADER)
+                })
+            {
+                let mut script = output.script_pubkey.to_bytes();
+                script[6..38].copy_from_slice(commitment.as_byte_array());
+                output.script_pubkey = ScriptBuf::from_bytes(script);
+            }
+        }
+
+        Self::refresh_merkle_root(block);
+    }
+
+    /// Recompute only the merkle root, keeping a deliberately broken witness
+    /// commitment as it is
+    pub fn refresh_merkle_root(block: &mut Block) {
+        block.header.merkle_root = block
+            .compute_merkle_root()
+            .unwrap_or_else(TxMerkleNode::all_zeros);
+    }
+
+    // Prefer transactions other than the coinbase when there are any
+    fn random_index(block: &Block) -> usize {
+        match block.txdata.len() {
+            1 => 0,
+            len => rng::rng().random_range(1..len),
+        }
+    }
+}
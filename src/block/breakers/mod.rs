@@ -2,4 +2,10 @@ pub mod version;
 pub mod header;
 pub mod merkle_root;
 pub mod bits;
-pub mod block;
\ No newline at end of file
+pub mod block;
+pub mod body;
+pub mod coinbase;
+pub mod witness_commitment;
+pub mod merkle_tree;
+pub mod timestamp;
+pub mod header_chain;
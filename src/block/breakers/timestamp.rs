@@ -0,0 +1,99 @@
+use bitcoin::block::Header;
+
+/// Number of previous blocks whose median timestamp a block must exceed.
+pub const MEDIAN_TIME_SPAN: usize = 11;
+
+/// Seconds a block may be ahead of the node's adjusted time (2 hours).
+pub const MAX_FUTURE_BLOCK_TIME: u32 = 2 * 60 * 60;
+
+/// Timestamp and lock time cases at the edges of the time rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimestampScenario {
+    AtMedianTimePast,
+    AfterMedianTimePast,
+    AtMaxFutureTime,
+    PastMaxFutureTime,
+    /// Final by the block's own time, not by its median-time-past (BIP113)
+    LockTimeAtMedianTimePast,
+    LockTimeBeforeMedianTimePast,
+}
+
+impl TimestampScenario {
+    pub const ALL: [TimestampScenario; 6] = [
+        Self::AtMedianTimePast,
+        Self::AfterMedianTimePast,
+        Self::AtMaxFutureTime,
+        Self::PastMaxFutureTime,
+        Self::LockTimeAtMedianTimePast,
+        Self::LockTimeBeforeMedianTimePast,
+    ];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::AtMedianTimePast => "Timestamp equal to median-time-past",
+            Self::AfterMedianTimePast => "Timestamp one second after median-time-past",
+            Self::AtMaxFutureTime => "Timestamp exactly two hours ahead",
+            Self::PastMaxFutureTime => "Timestamp two hours and one second ahead",
+            Self::LockTimeAtMedianTimePast => "Lock time equal to median-time-past",
+            Self::LockTimeBeforeMedianTimePast => "Lock time one second before median-time-past",
+        }
+    }
+
+    /// Bitcoin Core reject reason the block is expected to trigger, if any.
+    pub fn expected_failure(&self) -> Option<&'static str> {
+        match self {
+            Self::AtMedianTimePast => Some("time-too-old"),
+            Self::PastMaxFutureTime => Some("time-too-new"),
+            Self::LockTimeAtMedianTimePast => Some("bad-txns-nonfinal"),
+            Self::AfterMedianTimePast | Self::AtMaxFutureTime | Self::LockTimeBeforeMedianTimePast => {
+                None
+            }
+        }
+    }
+
+    /// Timestamp of a block on a tip with `tip_time` and `median_time_past`,
+    /// for a node whose adjusted time is `now`. Lock time cases use a
+    /// timestamp ten minutes after the tip, well past median-time-past, so
+    /// only BIP113 tells them apart.
+    pub fn block_time(&self, median_time_past: u32, tip_time: u32, now: u32) -> u32 {
+        match self {
+            Self::AtMedianTimePast => median_time_past,
+            Self::AfterMedianTimePast => median_time_past + 1,
+            Self::AtMaxFutureTime => now + MAX_FUTURE_BLOCK_TIME,
+            Self::PastMaxFutureTime => now + MAX_FUTURE_BLOCK_TIME + 1,
+            Self::LockTimeAtMedianTimePast | Self::LockTimeBeforeMedianTimePast => tip_time + 600,
+        }
+    }
+
+    /// Lock time of the extra transaction the lock time cases add. A
+    /// transaction is final once its lock time is below median-time-past.
+    pub fn lock_time(&self, median_time_past: u32) -> Option<u32> {
+        match self {
+            Self::LockTimeAtMedianTimePast => Some(median_time_past),
+            Self::LockTimeBeforeMedianTimePast => Some(median_time_past.saturating_sub(1)),
+            _ => None,
+        }
+    }
+}
+
+/// Processor for chain-aware timestamp values
+pub struct TimestampProcessor;
+
+impl TimestampProcessor {
+    /// Median timestamp of the last 11 headers, or of all of them when there
+    /// are fewer, as `GetMedianTimePast` picks it; None without headers
+    pub fn median_time_past(headers: &[Header]) -> Option<u32> {
+        let start = headers.len().saturating_sub(MEDIAN_TIME_SPAN);
+        let mut times: Vec<u32> = headers[start..].iter().map(|header| header.time).collect();
+        times.sort_unstable();
+        times.get(times.len() / 2).copied()
+    }
+
+    /// Current Unix time
+    pub fn now() -> u32 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as u32
+    }
+}
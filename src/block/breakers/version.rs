@@ -1,11 +1,30 @@
 use bitcoin::blockdata::block::Version;
 
+use crate::block::version_bits::{BIP34_MIN_VERSION, BIP65_MIN_VERSION, BIP66_MIN_VERSION};
+
 pub struct VersionProcessor;
 
 impl VersionProcessor {
-    /// Process the version of the block with optional override
-    pub fn process_version(_version: i32, version_override: Option<i32>) -> i32 {
-        version_override.unwrap_or(0x3FFFFFFF)
+    /// Process the version of the block with optional override. Without
+    /// one, the version drops below the BIP65 minimum, or one lower when it
+    /// already is below, so it fails as bad-version.
+    pub fn process_version(version: i32, version_override: Option<i32>) -> i32 {
+        version_override.unwrap_or_else(|| version.min(BIP65_MIN_VERSION).saturating_sub(1))
+    }
+
+    /// Highest version BIP34 rejects
+    pub fn below_bip34() -> i32 {
+        BIP34_MIN_VERSION - 1
+    }
+
+    /// Highest version BIP66 rejects
+    pub fn below_bip66() -> i32 {
+        BIP66_MIN_VERSION - 1
+    }
+
+    /// Highest version BIP65 rejects
+    pub fn below_bip65() -> i32 {
+        BIP65_MIN_VERSION - 1
     }
 
     /// Convert consensus version to Bitcoin Version type
@@ -27,7 +46,7 @@ impl VersionProcessor {
     /// Generate a random valid version
     pub fn generate_random_version() -> i32 {
         use rand::Rng;
-        let mut rng = rand::rng();
+        let mut rng = crate::rng::rng();
         rng.random_range(1..=0x3FFFFFFF)
     }
 
@@ -0,0 +1,107 @@
+use bitcoin::{Block, ScriptBuf, TxOut, Witness};
+use rand::Rng;
+
+use crate::block::decoder::WITNESS_COMMITMENT_HEADER;
+use crate::rng;
+
+/// Processor for modifications of the BIP141 witness commitment
+///
+/// Each mutation returns false, leaving the block alone, when it has no
+/// commitment, and relies on the caller to recompute the merkle root
+/// afterwards.
+pub struct WitnessCommitmentProcessor;
+
+impl WitnessCommitmentProcessor {
+    /// Flip a bit of the committed witness root
+    pub fn wrong_hash(block: &mut Block) -> bool {
+        let Some(output) = Self::commitment_output(block) else {
+            return false;
+        };
+        let mut script = output.script_pubkey.to_bytes();
+        script[rng::rng().random_range(6..38)] ^= 0x01;
+        output.script_pubkey = ScriptBuf::from_bytes(script);
+        true
+    }
+
+    /// Drop every commitment output, keeping the witnesses
+    pub fn remove(block: &mut Block) -> bool {
+        let Some(coinbase) = block.txdata.first_mut().filter(|tx| tx.is_coinbase()) else {
+            return false;
+        };
+        let outputs = coinbase.output.len();
+        coinbase
+            .output
+            .retain(|output| !Self::is_commitment(&output.script_pubkey));
+        coinbase.output.len() < outputs
+    }
+
+    /// Append a commitment to the wrong root after the right one, which Core
+    /// ignores as only the last matching output counts
+    pub fn not_last(block: &mut Block) -> bool {
+        let Some(mut decoy) = Self::commitment_output(block).cloned() else {
+            return false;
+        };
+        let mut script = decoy.script_pubkey.to_bytes();
+        script[6..38].iter_mut().for_each(|byte| *byte = !*byte);
+        decoy.script_pubkey = ScriptBuf::from_bytes(script);
+        block.txdata[0].output.push(decoy);
+        true
+    }
+
+    /// Corrupt the 0xaa21a9ed tag so the commitment is no longer recognised
+    pub fn wrong_magic(block: &mut Block) -> bool {
+        let Some(output) = Self::commitment_output(block) else {
+            return false;
+        };
+        let mut script = output.script_pubkey.to_bytes();
+        script[5] ^= 0xff;
+        output.script_pubkey = ScriptBuf::from_bytes(script);
+        true
+    }
+
+    /// Grow the witness reserved value to 33 bytes. Its size is only checked
+    /// against a commitment.
+    pub fn reserved_value_size(block: &mut Block) -> bool {
+        if Self::commitment_output(block).is_none() {
+            return false;
+        }
+        let coinbase = &mut block.txdata[0];
+        let mut reserved_value = coinbase.input[0]
+            .witness
+            .nth(0)
+            .map(<[u8]>::to_vec)
+            .unwrap_or_else(|| vec![0; 32]);
+        reserved_value.push(0x00);
+        coinbase.input[0].witness = Witness::from_slice(&[reserved_value]);
+        true
+    }
+
+    /// Whether the coinbase carries a commitment
+    pub fn has_commitment(block: &Block) -> bool {
+        block
+            .txdata
+            .first()
+            .filter(|tx| tx.is_coinbase())
+            .is_some_and(|tx| {
+                tx.output
+                    .iter()
+                    .any(|output| Self::is_commitment(&output.script_pubkey))
+            })
+    }
+
+    // Last output of the coinbase carrying a commitment, the one Core checks
+    fn commitment_output(block: &mut Block) -> Option<&mut TxOut> {
+        block
+            .txdata
+            .first_mut()
+            .filter(|tx| tx.is_coinbase())?
+            .output
+            .iter_mut()
+            .rev()
+            .find(|output| Self::is_commitment(&output.script_pubkey))
+    }
+
+    fn is_commitment(script: &ScriptBuf) -> bool {
+        script.len() >= 38 && script.as_bytes().starts_with(&WITNESS_COMMITMENT_HEADER)
+    }
+}
@@ -1,9 +1,17 @@
 use bitcoin::{
-    consensus::deserialize,
+    consensus::{deserialize, encode::VarInt, serialize},
     Transaction,
     blockdata::block::{Block, Header},
 };
 use bitcoin::consensus::Decodable;
+use bitcoin::opcodes::{Class, ClassifyContext};
+use bitcoin::script::{read_scriptint, Instruction};
+
+use crate::{Error, Result};
+
+/// `OP_RETURN`, a 36-byte push and the 0xaa21a9ed tag starting a BIP141
+/// witness commitment output.
+pub const WITNESS_COMMITMENT_HEADER: [u8; 6] = [0x6a, 0x24, 0xaa, 0x21, 0xa9, 0xed];
 
 #[derive(Default)]
 pub struct BitcoinTransactionDecoder;
@@ -14,14 +22,14 @@ impl BitcoinTransactionDecoder {
     }
 
     /// Decode a hex string directly to bitcoin::Transaction
-    pub fn decode_hex(&self, hex_string: &str) -> Result<Transaction, Box<dyn std::error::Error>> {
+    pub fn decode_hex(&self, hex_string: &str) -> Result<Transaction> {
         let clean_hex = hex_string.trim().replace(" ", "").to_lowercase();
         let bytes = hex::decode(&clean_hex)?;
         self.decode_bytes(&bytes)
     }
 
     /// Decode bytes directly to bitcoin::Transaction
-    pub fn decode_bytes(&self, bytes: &[u8]) -> Result<Transaction, Box<dyn std::error::Error>> {
+    pub fn decode_bytes(&self, bytes: &[u8]) -> Result<Transaction> {
         let tx: Transaction = deserialize(bytes)?;
         Ok(tx)
     }
@@ -45,22 +53,42 @@ impl BitcoinTransactionDecoder {
 pub struct BlockUtils;
 impl BlockUtils {
     // Utility method to decode block header from hex string
-    pub fn decode_header_from_hex(hex_string: &str) -> Result<Header, Box<dyn std::error::Error>> {
+    pub fn decode_header_from_hex(hex_string: &str) -> Result<Header> {
         let bytes = hex::decode(hex_string)?;
         if bytes.len() != 80 {
-            return Err(format!("Invalid header length: expected 80 bytes, got {}", bytes.len()).into());
+            return Err(Error::Decode(format!(
+                "Invalid header length: expected 80 bytes, got {}",
+                bytes.len()
+            )));
         }
         let header = Header::consensus_decode(&mut &bytes[..])?;
         Ok(header)
     }
 
     // Utility method to decode block from hex string
-    pub fn decode_block_from_hex(hex_string: &str) -> Result<Block, Box<dyn std::error::Error>> {
+    pub fn decode_block_from_hex(hex_string: &str) -> Result<Block> {
         let bytes = hex::decode(hex_string)?;
         let block = Block::consensus_decode(&mut &bytes[..])?;
         Ok(block)
     }
 
+    /// Height pushed first by the coinbase script_sig, read the way Bitcoin
+    /// Core checks BIP34: small heights are `OP_1`..`OP_16`, others a
+    /// minimally encoded number. Unlike `Block::bip34_block_height` this does
+    /// not look at the block version.
+    pub fn bip34_height(block: &Block) -> Option<u64> {
+        let coinbase = block.txdata.first().filter(|tx| tx.is_coinbase())?;
+        let first = coinbase.input[0].script_sig.instructions_minimal().next()?.ok()?;
+        let height = match first {
+            Instruction::PushBytes(bytes) => read_scriptint(bytes.as_bytes()).ok()?,
+            Instruction::Op(op) => match op.classify(ClassifyContext::Legacy) {
+                Class::PushNum(n) => n.into(),
+                _ => return None,
+            },
+        };
+        u64::try_from(height).ok()
+    }
+
     // Create a minimal block from a header (for testing purposes)
     pub fn create_minimal_block_from_header(header: Header) -> Block {
         Block {
@@ -89,6 +117,22 @@ impl BlockUtils {
         hex::encode(bytes)
     }
 
+    // Encode headers as the payload of a P2P `headers` message: a count,
+    // then every header followed by a zero transaction count
+    pub fn encode_headers_message(headers: &[Header]) -> Vec<u8> {
+        let mut bytes = serialize(&VarInt::from(headers.len()));
+        for header in headers {
+            bytes.extend(serialize(header));
+            bytes.push(0x00);
+        }
+        bytes
+    }
+
+    // Encode headers back to back, 80 bytes each
+    pub fn encode_headers_flat(headers: &[Header]) -> Vec<u8> {
+        headers.iter().flat_map(serialize).collect()
+    }
+
 
 }
 
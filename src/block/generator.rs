@@ -1,35 +1,180 @@
-use bitcoin::Block;
-use super::random::block::{BlockParams, RandomBlock};
-use bitcoin::OutPoint;
-
-use crate::transaction::{
-    generator::GenerateTx,
-    random::{
-        input::InputParams,
-        transaction::{TxParams},
-    },
+use bitcoin::{
+    block::{Header, Version},
+    blockdata::constants::genesis_block,
+    hashes::Hash,
+    Block, CompactTarget, Network, PrivateKey, Transaction, TxMerkleNode,
 };
+use rand::Rng;
+
+use super::miner::{Miner, REGTEST_BITS};
+use super::random::{
+    block::{BlockParams, RandomBlock},
+    header::{HeaderParams, RandomHeader},
+};
+
+use crate::rng;
+
+use super::version_bits::VERSIONBITS_TOP_BITS;
+
+use crate::transaction::random::coinbase::{CoinbaseParams, RandomCoinbase};
 
 pub struct GenerateBlock {}
 
 impl GenerateBlock {
     pub fn valid_random(mut params: BlockParams) -> (Block, u32) {
-    let input_params = InputParams {
-        outpoint: Some(OutPoint::null()),
-        ..Default::default()
-    };
-
-    let coinbase_params = TxParams {
-        input: Some(input_params),
-        ..Default::default()
-    };
+    if let Some(seed) = params.seed.take() {
+        rng::set_seed(seed);
+    }
 
-    let coinbase_info = GenerateTx::valid_random(coinbase_params);
+    let height = params
+        .height
+        .unwrap_or_else(|| rng::rng().random_range(1..10_000_000));
+    params.height = Some(height);
 
     let mut txs = params.txs.take().unwrap_or_default();
-    txs.insert(0, coinbase_info);
+    let segwit = txs
+        .iter()
+        .any(|tx| tx.input.iter().any(|input| !input.witness.is_empty()));
+
+    let coinbase = Transaction::random_coinbase(CoinbaseParams {
+        height: Some(height),
+        network: params.network.take(),
+        fees: params.fees.take(),
+        private_key: params.private_key.take(),
+        segwit,
+        ..Default::default()
+    });
+
+    txs.insert(0, coinbase);
     params.txs = Some(txs);
 
-    Block::random(params)   
+    let target = params
+        .target
+        .take()
+        .unwrap_or(CompactTarget::from_consensus(REGTEST_BITS));
+    let (mut block, height) = Block::random(params);
+    if let Some(merkle_root) = block.compute_merkle_root() {
+        block.header.merkle_root = merkle_root;
+    }
+    Miner::mine(&mut block, target);
+
+    (block, height)
+}
+}
+
+#[derive(Default)]
+pub struct ChainParams {
+    /// Number of blocks to generate on top of `genesis`; defaults to 1.
+    pub length: Option<usize>,
+    /// Block the chain builds on; defaults to the regtest genesis block.
+    pub genesis: Option<Block>,
+    /// Height of `genesis`; defaults to 0.
+    pub genesis_height: Option<u32>,
+    /// Compact target every block is mined to; defaults to regtest's.
+    pub target: Option<CompactTarget>,
+    /// Version of each block in order. Blocks past its end, or every block
+    /// without it, signal no deployment with the bare `001` top bits.
+    pub versions: Option<Vec<Version>>,
+    /// Key every coinbase pays to; defaults to a random one.
+    pub private_key: Option<PrivateKey>,
+    /// Reseeds the shared generator before anything is drawn.
+    pub seed: Option<u64>,
+}
+
+pub struct GenerateChain {}
+
+impl GenerateChain {
+    /// Blocks following `genesis`, each pointing at its parent, with the
+    /// next BIP34 height and a timestamp 1 to 20 minutes after its parent.
+    pub fn valid_random(params: ChainParams) -> Vec<(Block, u32)> {
+        if let Some(seed) = params.seed {
+            rng::set_seed(seed);
+        }
+
+        let genesis = params
+            .genesis
+            .unwrap_or_else(|| genesis_block(Network::Regtest));
+        let private_key = params.private_key.unwrap_or_else(rng::private_key);
+
+        let mut parent = (genesis.header, params.genesis_height.unwrap_or(0));
+        let versions = params.versions.unwrap_or_default();
+        (0..params.length.unwrap_or(1))
+            .map(|index| {
+                let (parent_header, parent_height) = parent;
+                // The bare top bits also satisfy the BIP34/65/66 minimums
+                let version = versions
+                    .get(index)
+                    .copied()
+                    .unwrap_or(Version::from_consensus(VERSIONBITS_TOP_BITS));
+                let header = Header::random(HeaderParams {
+                    version: Some(version),
+                    prev_blockhash: Some(parent_header.block_hash()),
+                    merkle_root: Some(TxMerkleNode::all_zeros()),
+                    time: Some(parent_header.time + rng::rng().random_range(60..=1_200)),
+                    ..Default::default()
+                });
+
+                let (block, height) = GenerateBlock::valid_random(BlockParams {
+                    header: Some(header),
+                    txs: None,
+                    height: Some(parent_height + 1),
+                    target: params.target,
+                    private_key: Some(private_key),
+                    seed: None,
+                    ..Default::default()
+                });
+
+                parent = (block.header, height);
+                (block, height)
+            })
+            .collect()
+    }
+}
+
+#[derive(Default)]
+pub struct HeaderChainParams {
+    /// Number of headers to generate on top of `genesis`; defaults to 1.
+    pub length: Option<usize>,
+    /// Header the chain builds on; defaults to the regtest genesis header.
+    pub genesis: Option<Header>,
+    /// Compact target every header is mined to; defaults to regtest's.
+    pub target: Option<CompactTarget>,
+    /// Reseeds the shared generator before anything is drawn.
+    pub seed: Option<u64>,
+}
+
+pub struct GenerateHeaderChain {}
+
+impl GenerateHeaderChain {
+    /// Headers following `genesis`, each pointing at its parent with a
+    /// timestamp 1 to 20 minutes after it. There are no blocks behind them,
+    /// so merkle roots are random.
+    pub fn valid_random(params: HeaderChainParams) -> Vec<Header> {
+        if let Some(seed) = params.seed {
+            rng::set_seed(seed);
+        }
+
+        let target = params
+            .target
+            .unwrap_or(CompactTarget::from_consensus(REGTEST_BITS));
+        let mut parent = params
+            .genesis
+            .unwrap_or_else(|| genesis_block(Network::Regtest).header);
+        (0..params.length.unwrap_or(1))
+            .map(|_| {
+                let mut rng = rng::rng();
+                let mut header = Header::random(HeaderParams {
+                    version: Some(Version::from_consensus(VERSIONBITS_TOP_BITS)),
+                    prev_blockhash: Some(parent.block_hash()),
+                    merkle_root: Some(TxMerkleNode::from_byte_array(rng.random())),
+                    time: Some(parent.time + rng.random_range(60..=1_200)),
+                    ..Default::default()
+                });
+                Miner::mine_header(&mut header, target);
+
+                parent = header;
+                header
+            })
+            .collect()
+    }
 }
-}
\ No newline at end of file
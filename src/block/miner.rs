@@ -0,0 +1,61 @@
+use bitcoin::{block::Header, Block, CompactTarget, ScriptBuf};
+
+/// Compact target of regtest, where about every other hash is valid.
+pub const REGTEST_BITS: u32 = 0x207fffff;
+
+/// Grinds blocks until their hash meets the target in their own `bits`
+pub struct Miner;
+
+impl Miner {
+    /// Set `bits` and search nonces, starting from the current one, until
+    /// `validate_pow` passes. Whenever the nonce space runs out, an
+    /// extra-nonce in the coinbase script_sig is bumped and the merkle root
+    /// recomputed; blocks without a coinbase bump the timestamp instead.
+    pub fn mine(block: &mut Block, bits: CompactTarget) {
+        block.header.bits = bits;
+        let target = block.header.target();
+        let start_nonce = block.header.nonce;
+        let coinbase_script = block
+            .txdata
+            .first()
+            .filter(|tx| tx.is_coinbase())
+            .map(|tx| tx.input[0].script_sig.to_bytes());
+        let mut extra_nonce: u32 = 0;
+
+        while block.header.validate_pow(target).is_err() {
+            block.header.nonce = block.header.nonce.wrapping_add(1);
+            if block.header.nonce != start_nonce {
+                continue;
+            }
+
+            extra_nonce = extra_nonce.wrapping_add(1);
+            match &coinbase_script {
+                Some(script) => {
+                    let mut script = script.clone();
+                    script.push(0x04);
+                    script.extend_from_slice(&extra_nonce.to_le_bytes());
+                    block.txdata[0].input[0].script_sig = ScriptBuf::from_bytes(script);
+                    if let Some(merkle_root) = block.compute_merkle_root() {
+                        block.header.merkle_root = merkle_root;
+                    }
+                }
+                None => block.header.time = block.header.time.wrapping_add(1),
+            }
+        }
+    }
+
+    /// Set `bits` and search nonces of a bare header until `validate_pow`
+    /// passes, bumping the timestamp whenever the nonce space runs out.
+    pub fn mine_header(header: &mut Header, bits: CompactTarget) {
+        header.bits = bits;
+        let target = header.target();
+        let start_nonce = header.nonce;
+
+        while header.validate_pow(target).is_err() {
+            header.nonce = header.nonce.wrapping_add(1);
+            if header.nonce == start_nonce {
+                header.time = header.time.wrapping_add(1);
+            }
+        }
+    }
+}
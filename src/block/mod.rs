@@ -1,4 +1,6 @@
 pub mod random;
 pub mod breakers;
 pub mod generator;
-pub mod decoder;
\ No newline at end of file
+pub mod decoder;
+pub mod miner;
+pub mod version_bits;
@@ -1,5 +1,7 @@
 use bitcoin::CompactTarget;
-use secp256k1::rand::{self, Rng};
+use rand::Rng;
+
+use crate::rng;
 
 pub trait RandomBits {
     fn random() -> CompactTarget;
@@ -7,6 +9,6 @@ pub trait RandomBits {
 
 impl RandomBits for CompactTarget {
     fn random() -> CompactTarget {
-        CompactTarget::from_consensus(rand::thread_rng().gen::<u32>())
+        CompactTarget::from_consensus(rng::rng().random::<u32>())
     }
 }
@@ -1,5 +1,7 @@
-use bitcoin::{block::{Header}, Block, Transaction};
-use secp256k1::rand::{self, Rng};
+use bitcoin::{block::{Header}, Amount, Block, CompactTarget, Network, PrivateKey, Transaction};
+use rand::Rng;
+
+use crate::rng;
 
 use crate::transaction::{
     generator::GenerateTx, 
@@ -16,6 +18,18 @@ pub struct BlockParams {
     pub header: Option<Header>,
     pub txs: Option<Vec<Transaction>>,
     pub height: Option<u32>,
+    /// Compact target `GenerateBlock::valid_random` mines to; defaults to
+    /// regtest's 0x207fffff.
+    pub target: Option<CompactTarget>,
+    /// Key the coinbase output of `GenerateBlock::valid_random` pays to,
+    /// through P2WPKH, so it can be spent later.
+    pub private_key: Option<PrivateKey>,
+    /// Fees paid by `txs`, claimed by the coinbase on top of the subsidy.
+    pub fees: Option<Amount>,
+    /// Network whose halving schedule sets the subsidy; defaults to regtest.
+    pub network: Option<Network>,
+    /// Reseeds the shared generator before anything is drawn.
+    pub seed: Option<u64>,
 }
 
 
@@ -27,17 +41,21 @@ pub trait RandomBlock {
 
 impl RandomBlock for Block {
     fn random(mut params: BlockParams) -> (Block, u32) {
-        let  block_height = params.height.unwrap_or_else(|| rand::thread_rng().gen_range(1..10_000_000));
+        if let Some(seed) = params.seed {
+            rng::set_seed(seed);
+        }
+
+        let  block_height = params.height.unwrap_or_else(|| rng::rng().random_range(1..10_000_000));
         params.height = Some(block_height);
             let tx_data = params.txs.unwrap_or_else(|| {
-            let random = rand::thread_rng().gen_range(1..10);
+            let random = rng::rng().random_range(1..10);
             let mut txs = vec![];
             for _ in 0..random {
             let tx_params = TxParams {
                 block_height: Some(block_height),
                 ..Default::default()
             };
-            let tx_info = GenerateTx::valid_random(tx_params);
+            let tx_info = GenerateTx::valid_random(tx_params).expect("inputs of random value cover any fee");
             txs.push(tx_info);
         }
             txs
@@ -3,7 +3,9 @@ use bitcoin::{
     hashes::Hash,
     Block, BlockHash, CompactTarget, Transaction, TxMerkleNode,
 };
-use secp256k1::rand::{self, Rng};
+use rand::Rng;
+
+use crate::rng;
 
 use super::{
     bits::RandomBits,
@@ -21,6 +23,8 @@ pub struct HeaderParams {
     pub bits: Option<CompactTarget>,
     pub nonce: Option<u32>,
     pub txs: Option<Vec<Transaction>>,
+    /// Reseeds the shared generator before anything is drawn.
+    pub seed: Option<u64>,
 }
 
 
@@ -30,6 +34,10 @@ pub trait RandomHeader {
 
 impl RandomHeader for Header {
     fn random(params: HeaderParams) -> Header {
+        if let Some(seed) = params.seed {
+            rng::set_seed(seed);
+        }
+
         Header {
             version: params.version.unwrap_or_else(Version::random),
             prev_blockhash: params.prev_blockhash.unwrap_or_else(|| {
@@ -51,11 +59,11 @@ impl RandomHeader for Header {
                 .unwrap_or_else(|| TxMerkleNode::random(MerkleRootParams { txs: params.txs })),
             time: params
                 .time
-                .unwrap_or_else(|| rand::thread_rng().gen::<u32>()),
+                .unwrap_or_else(|| rng::rng().random::<u32>()),
             bits: params.bits.unwrap_or_else(CompactTarget::random),
             nonce: params
                 .nonce
-                .unwrap_or_else(|| rand::thread_rng().gen::<u32>()),
+                .unwrap_or_else(|| rng::rng().random::<u32>()),
         }
     }
 }
@@ -1,5 +1,7 @@
 use bitcoin::{merkle_tree, Transaction, TxMerkleNode};
-use secp256k1::rand::{self, Rng};
+use rand::Rng;
+
+use crate::rng;
 
 use crate::transaction::{
     generator::GenerateTx,
@@ -28,11 +30,11 @@ impl MerkleRoot for TxMerkleNode {
 
     fn random(params: MerkleRootParams) -> TxMerkleNode {
         let txs = params.txs.unwrap_or_else(|| {
-            let random = rand::thread_rng().gen_range(1..10);
+            let random = rng::rng().random_range(1..10);
 
             let mut txs = vec![];
             for _ in 0..random {
-                let tx_info = GenerateTx::valid_random(TxParams::default());
+                let tx_info = GenerateTx::valid_random(TxParams::default()).expect("inputs of random value cover any fee");
                 txs.push(tx_info);
             }
 
@@ -1,5 +1,8 @@
 use bitcoin::block::Version;
-use secp256k1::rand::{self, Rng};
+use rand::Rng;
+
+use crate::block::version_bits::{signalling_version, VERSIONBITS_NUM_BITS};
+use crate::rng;
 
 pub trait RandomVersion {
     fn random() -> Version;
@@ -8,14 +11,20 @@ pub trait RandomVersion {
 impl RandomVersion for Version {
     fn random() -> Version {
         // Random standard
-        if rand::thread_rng().gen_bool(0.5) {
-            if rand::thread_rng().gen_bool(0.5) {
+        if rng::rng().random_bool(0.5) {
+            if rng::rng().random_bool(0.5) {
                 return Version::ONE;
             }
             return Version::TWO;
         }
 
+        // Random version bits signalling one deployment
+        if rng::rng().random_bool(0.5) {
+            let bit = rng::rng().random_range(0..VERSIONBITS_NUM_BITS);
+            return signalling_version(&[bit]);
+        }
+
         // Random non_standard
-        Version::from_consensus(rand::thread_rng().gen::<i32>())
+        Version::from_consensus(rng::rng().random::<i32>())
     }
 }
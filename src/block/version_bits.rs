@@ -0,0 +1,133 @@
+//! BIP9 version bits: signalling versions and deployment state tracking.
+
+use std::fmt;
+
+use bitcoin::block::{Header, Version};
+
+use super::breakers::timestamp::TimestampProcessor;
+
+/// Top three bits of a version that signals, `001`.
+pub const VERSIONBITS_TOP_BITS: i32 = 0x2000_0000;
+/// Mask selecting the top three bits of a version.
+pub const VERSIONBITS_TOP_MASK: i32 = 0xE000_0000_u32 as i32;
+/// Number of bits available to deployments below the top bits.
+pub const VERSIONBITS_NUM_BITS: u8 = 29;
+
+/// Lowest versions accepted once BIP34, BIP66 and BIP65 are active.
+pub const BIP34_MIN_VERSION: i32 = 2;
+pub const BIP66_MIN_VERSION: i32 = 3;
+pub const BIP65_MIN_VERSION: i32 = 4;
+
+/// Version with the `001` top bits and every bit in `bits` set; bits that
+/// do not fit below the top bits are ignored.
+pub fn signalling_version(bits: &[u8]) -> Version {
+    let version = bits
+        .iter()
+        .filter(|bit| **bit < VERSIONBITS_NUM_BITS)
+        .fold(VERSIONBITS_TOP_BITS, |version, bit| version | (1 << bit));
+    Version::from_consensus(version)
+}
+
+/// Whether `version` signals for `bit`, which needs the `001` top bits.
+pub fn signals(version: Version, bit: u8) -> bool {
+    let version = version.to_consensus();
+    bit < VERSIONBITS_NUM_BITS
+        && version & VERSIONBITS_TOP_MASK == VERSIONBITS_TOP_BITS
+        && version & (1 << bit) != 0
+}
+
+/// State of a deployment for a block, as in Bitcoin Core's `ThresholdState`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ThresholdState {
+    Defined,
+    Started,
+    LockedIn,
+    Active,
+    Failed,
+}
+
+impl fmt::Display for ThresholdState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Defined => "defined",
+            Self::Started => "started",
+            Self::LockedIn => "locked_in",
+            Self::Active => "active",
+            Self::Failed => "failed",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// A BIP9 deployment and the parameters of its signalling periods
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Deployment {
+    pub bit: u8,
+    /// Median-time-past from which signalling starts.
+    pub start_time: i64,
+    /// Median-time-past at which a deployment that did not lock in fails.
+    pub timeout: i64,
+    /// Lowest height a locked in deployment becomes active at.
+    pub min_activation_height: u32,
+    /// Blocks per signalling period.
+    pub period: u32,
+    /// Signalling blocks within a period needed to lock in.
+    pub threshold: u32,
+}
+
+impl Deployment {
+    /// Deployment on `bit` with regtest's parameters: it starts right away,
+    /// never times out, and locks in with 108 of 144 blocks signalling.
+    pub fn regtest(bit: u8) -> Self {
+        Self {
+            bit,
+            start_time: 0,
+            timeout: i64::MAX,
+            min_activation_height: 0,
+            period: 144,
+            threshold: 108,
+        }
+    }
+
+    /// State of the deployment for every block of `headers`, where
+    /// `headers[0]` is the genesis block. The state only changes at period
+    /// boundaries and depends on the period before.
+    pub fn states(&self, headers: &[Header]) -> Vec<ThresholdState> {
+        let period = self.period.max(1) as usize;
+        let mut states = Vec::with_capacity(headers.len());
+        let mut state = ThresholdState::Defined;
+
+        for height in 0..headers.len() {
+            if height > 0 && height % period == 0 {
+                let last = height - 1;
+                let median_time_past = TimestampProcessor::median_time_past(&headers[..=last])
+                    .unwrap_or(0) as i64;
+                state = match state {
+                    ThresholdState::Defined if median_time_past >= self.start_time => {
+                        ThresholdState::Started
+                    }
+                    ThresholdState::Started => {
+                        let count = headers[height - period..height]
+                            .iter()
+                            .filter(|header| signals(header.version, self.bit))
+                            .count() as u32;
+                        if count >= self.threshold {
+                            ThresholdState::LockedIn
+                        } else if median_time_past >= self.timeout {
+                            ThresholdState::Failed
+                        } else {
+                            ThresholdState::Started
+                        }
+                    }
+                    ThresholdState::LockedIn if height as u32 >= self.min_activation_height => {
+                        ThresholdState::Active
+                    }
+                    state => state,
+                };
+            }
+            states.push(state);
+        }
+
+        states
+    }
+}
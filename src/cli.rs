@@ -1,13 +1,38 @@
+use std::error::Error;
 use std::io;
 use std::io::Write;
-use clap::{Parser, Subcommand};
-use crate::api::Generator;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::fmt::Display;
+use clap::{Parser, Subcommand, ValueEnum};
+use misfit_core::block::breakers::header_chain::HeaderChainBreak;
+use misfit_core::p2p::breakers::EnvelopeBreak;
+use misfit_core::p2p::compact_block::CompactBlockBreak;
+use misfit_core::p2p::message::MessageCommand;
+use misfit_core::regtest_pack::regtest::RegtestManager;
+use misfit_core::transaction::breakers::taproot::TaprootBreak;
+use misfit_core::transaction::random::multisig::MultisigWrapping;
+use misfit_core::transaction::random::taproot::TapLeafParams;
+use misfit_core::report::{DecodedBlockReport, DecodedTransactionReport, HeaderReport};
+use serde::Serialize;
+use misfit_core::api::Generator;
+use bitcoin::Network;
 
 #[derive(Parser)]
 #[command(version, about, disable_help_subcommand = true)]
 pub struct Cli {
+    /// Runs once and exits; without a command the interactive shell is started.
     #[command(subcommand)]
-    pub command: Commands,
+    pub command: Option<Commands>,
+    /// Output format of command results.
+    #[arg(long, value_enum, global = true, default_value_t = OutputFormat::Text)]
+    pub format: OutputFormat,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum OutputFormat {
+    Text,
+    Json,
 }
 
 #[derive(Subcommand)]
@@ -15,6 +40,8 @@ pub enum Commands {
     Help,
     Clear,
     Exit,
+    #[command(alias = "shell")]
+    Interactive,
     #[command(name = "decode-transaction")]
     DecodeTransaction {
         raw_transaction: String
@@ -23,6 +50,14 @@ pub enum Commands {
     DecodeBlock {
         block_header: String
     },
+    #[command(name = "decode-full-block")]
+    DecodeFullBlock {
+        raw_block: String
+    },
+    #[command(name = "forge-merkle-proof")]
+    ForgeMerkleProof {
+        raw_block: String
+    },
     #[command(name = "break-transaction")]
     BreakTransaction {
         raw_transaction: String,
@@ -44,11 +79,30 @@ pub enum Commands {
         witness: bool,
         #[arg(long, help = "Invalidate transaction locktime")]
         locktime: bool,
+        #[arg(long = "tr-control-parity", help = "Flip the output key parity of taproot control blocks")]
+        tr_control_parity: bool,
+        #[arg(long = "tr-control-size", help = "Grow taproot control blocks by one byte")]
+        tr_control_size: bool,
+        #[arg(long = "tr-merkle-path", help = "Corrupt the merkle path of taproot control blocks")]
+        tr_merkle_path: bool,
+        #[arg(long = "tr-annex", help = "Append an annex to taproot witnesses")]
+        tr_annex: bool,
+        #[arg(long = "tr-sighash-default", help = "Append SIGHASH_DEFAULT to 64-byte Schnorr signatures")]
+        tr_sighash_default: bool,
+        #[arg(long = "nested-redeem-script", help = "Push another witness program than the P2SH hash commits to")]
+        nested_redeem_script: bool,
+        #[arg(long = "nested-witness-pubkey", help = "Swap the P2SH-P2WPKH witness key for another")]
+        nested_witness_pubkey: bool,
+        #[arg(long = "nested-witness-script", help = "Alter the P2SH-P2WSH witness script")]
+        nested_witness_script: bool,
         #[arg(long, help = "Invalidate all transaction fields")]
         all: bool,
+        #[arg(long, help = "Seed the random generator for reproducible output")]
+        seed: Option<u64>,
     },
     #[command(name = "break-block")]
     BreakBlock {
+        /// Block header, or full serialized block for transaction breakages
         block_header: String,
         #[arg(long, help = "Invalidate block version")]
         version: bool,
@@ -60,25 +114,168 @@ pub enum Commands {
         timestamp: bool,
         #[arg(long, help = "Invalidate difficulty bits")]
         bits: bool,
+        #[arg(long = "bits-easier", help = "Set bits one compact unit easier")]
+        bits_easier: bool,
+        #[arg(long = "bits-harder", help = "Set bits one compact unit harder")]
+        bits_harder: bool,
+        #[arg(long = "version-below-bip34", help = "Set version 1, below the BIP34 minimum")]
+        version_below_bip34: bool,
+        #[arg(long = "version-below-bip66", help = "Set version 2, below the BIP66 minimum")]
+        version_below_bip66: bool,
+        #[arg(long = "version-below-bip65", help = "Set version 3, below the BIP65 minimum")]
+        version_below_bip65: bool,
         #[arg(long, help = "Invalidate nonce")]
         nonce: bool,
-        #[arg(long, help = "Invalidate all block fields")]
+        #[arg(long, help = "Invalidate all block header fields")]
         all: bool,
+        #[arg(long = "remove-coinbase", help = "Remove the coinbase transaction")]
+        remove_coinbase: bool,
+        #[arg(long = "reorder-txs", help = "Move the coinbase away from the first position")]
+        reorder_txs: bool,
+        #[arg(long = "duplicate-tx", help = "Duplicate a transaction")]
+        duplicate_tx: bool,
+        #[arg(long = "tamper-tx", help = "Tamper with a transaction, keeping the header")]
+        tamper_tx: bool,
+        #[arg(long = "duplicate-tail-txs", help = "Repeat the trailing transactions, keeping the merkle root")]
+        duplicate_tail_txs: bool,
+        #[arg(long = "insert-64-byte-tx", help = "Insert a 64-byte transaction")]
+        insert_64_byte_tx: bool,
+        #[arg(long = "coinbase-overpay", help = "Overpay the block subsidy and fees by 1 sat")]
+        coinbase_overpay: bool,
+        #[arg(long = "coinbase-wrong-height", help = "Push the wrong BIP34 height in the coinbase")]
+        coinbase_wrong_height: bool,
+        #[arg(long = "coinbase-no-height", help = "Drop the BIP34 height from the coinbase")]
+        coinbase_no_height: bool,
+        #[arg(long = "coinbase-short-script", help = "Shorten the coinbase script_sig below 2 bytes")]
+        coinbase_short_script: bool,
+        #[arg(long = "coinbase-long-script", help = "Grow the coinbase script_sig above 100 bytes")]
+        coinbase_long_script: bool,
+        #[arg(long = "coinbase-prevout", help = "Give the coinbase a non-null prevout")]
+        coinbase_prevout: bool,
+        #[arg(long = "multiple-coinbases", help = "Add a second coinbase")]
+        multiple_coinbases: bool,
+        #[arg(long = "coinbase-no-witness-nonce", help = "Drop the coinbase witness reserved value")]
+        coinbase_no_witness_nonce: bool,
+        #[arg(long = "commitment-wrong-hash", help = "Commit to the wrong witness root")]
+        commitment_wrong_hash: bool,
+        #[arg(long = "commitment-missing", help = "Drop the witness commitment, keeping the witnesses")]
+        commitment_missing: bool,
+        #[arg(long = "commitment-not-last", help = "Follow the witness commitment with a wrong one")]
+        commitment_not_last: bool,
+        #[arg(long = "commitment-wrong-magic", help = "Corrupt the witness commitment magic bytes")]
+        commitment_wrong_magic: bool,
+        #[arg(long = "commitment-nonce-size", help = "Grow the witness reserved value to 33 bytes")]
+        commitment_nonce_size: bool,
         #[arg(long, help = "Override version with specific value")]
         version_override: Option<i32>,
         #[arg(long, help = "Add/subtract seconds to timestamp")]
         timestamp_offset: Option<i64>,
         #[arg(long, help = "Use zero hashes instead of random")]
         zero_hashes: bool,
+        #[arg(long, help = "Seed the random generator for reproducible output")]
+        seed: Option<u64>,
     },
     Tx {
         #[arg(default_value_t = 1)]
         txscount: u32,
         campuses: Vec<String>,
+        #[arg(long, help = "Seed the random generator for reproducible output")]
+        seed: Option<u64>,
     },
     Block {
         #[arg(default_value_t = 1)]
         txscount: u32,
+        #[arg(long, help = "Seed the random generator for reproducible output")]
+        seed: Option<u64>,
+    },
+    Chain {
+        #[arg(default_value_t = 1)]
+        length: usize,
+        #[arg(long, help = "Seed the random generator for reproducible output")]
+        seed: Option<u64>,
+    },
+    Retarget {
+        #[arg(value_parser = parse_bits, help = "Bits of the last block of the window, in hex")]
+        last_bits: u32,
+        #[arg(help = "Timestamp of the first block of the window")]
+        first_time: u32,
+        #[arg(help = "Timestamp of the last block of the window")]
+        last_time: u32,
+        #[arg(long, default_value_t = Network::Bitcoin, help = "Network whose consensus parameters apply")]
+        network: Network,
+    },
+    Timestamps {
+        #[arg(long, help = "Adjusted time of the node, defaults to now")]
+        now: Option<u32>,
+        #[arg(long, help = "Seed the random generator for reproducible output")]
+        seed: Option<u64>,
+    },
+    Signal {
+        #[arg(value_parser = clap::value_parser!(u8).range(0..29), help = "Deployment bit to signal, 0 to 28")]
+        bit: u8,
+        #[arg(default_value_t = 432, help = "Number of blocks on top of the regtest genesis")]
+        length: usize,
+        #[arg(long, help = "Signalling blocks per 144-block period, defaults to all")]
+        per_period: Option<u32>,
+        #[arg(long, help = "Seed the random generator for reproducible output")]
+        seed: Option<u64>,
+    },
+    Headers {
+        #[arg(default_value_t = 2000, help = "Number of headers on top of the regtest genesis")]
+        length: usize,
+        #[arg(long, requires = "breakage", help = "Position of the header to break, from 0")]
+        break_at: Option<usize>,
+        #[arg(long = "break", requires = "break_at", help = "How to break it: pow, prev-hash or bits")]
+        breakage: Option<HeaderChainBreak>,
+        #[arg(long, help = "Concatenate bare 80-byte headers instead of a headers message payload")]
+        flat: bool,
+        #[arg(long, help = "Write the raw bytes to this file instead of printing them")]
+        out: Option<PathBuf>,
+        #[arg(long, help = "Seed the random generator for reproducible output")]
+        seed: Option<u64>,
+    },
+    Multisig {
+        #[arg(default_value_t = 2, help = "Signatures a spend needs")]
+        threshold: usize,
+        #[arg(default_value_t = 3, help = "Keys in the script")]
+        keys: usize,
+        #[arg(long, default_value = "p2wsh", help = "Output type: bare, p2sh, p2wsh or p2sh-p2wsh")]
+        wrap: MultisigWrapping,
+        #[arg(long, help = "Seed the random generator for reproducible output")]
+        seed: Option<u64>,
+    },
+    Tapscript {
+        #[arg(long, value_delimiter = ',', help = "Leaves: checksig, multi:<k>-of-<n> or script:<hex>; random by default")]
+        leaves: Vec<TapLeafParams>,
+        #[arg(long, help = "Leaf to spend through, from 0; random by default")]
+        leaf: Option<usize>,
+        #[arg(long = "break", help = "Breaker: control-parity, control-size, merkle-path, annex, sighash-default, op-success or leaf-version")]
+        breakage: Option<TaprootBreak>,
+        #[arg(long, help = "Seed the random generator for reproducible output")]
+        seed: Option<u64>,
+    },
+    #[command(name = "compact-block")]
+    CompactBlock {
+        #[arg(default_value_t = 3, help = "Transactions after the coinbase")]
+        txscount: u32,
+        #[arg(long, value_delimiter = ',', help = "Sorted block positions to prefill besides the coinbase")]
+        prefill: Vec<usize>,
+        #[arg(long = "break", help = "Breaker: collision, prefill-range, blocktxn-missing or blocktxn-extra")]
+        breakage: Option<CompactBlockBreak>,
+        #[arg(long, help = "Seed the random generator for reproducible output")]
+        seed: Option<u64>,
+    },
+    Wrap {
+        #[arg(help = "Raw transaction, block or 80-byte header, in hex")]
+        raw: String,
+        #[arg(help = "Message to wrap it in: tx, block, headers, cmpctblock or inv")]
+        command: MessageCommand,
+        #[arg(long, default_value_t = Network::Regtest, help = "Network whose magic starts the message")]
+        network: Network,
+        #[arg(long = "break", help = "Envelope breaker: checksum, length, oversized or unknown-command")]
+        breakage: Option<EnvelopeBreak>,
+        #[arg(long, help = "Write the raw bytes to this file instead of printing them")]
+        out: Option<PathBuf>,
     },
     #[command(name = "regtest-start")]
     RegtestStart,
@@ -100,6 +297,14 @@ struct TransactionFlags {
     script_pubkey: bool,
     witness: bool,
     locktime: bool,
+    tr_control_parity: bool,
+    tr_control_size: bool,
+    tr_merkle_path: bool,
+    tr_annex: bool,
+    tr_sighash_default: bool,
+    nested_redeem_script: bool,
+    nested_witness_pubkey: bool,
+    nested_witness_script: bool,
     all: bool,
 }
 
@@ -109,14 +314,60 @@ struct BlockFlagsConfig {
     merkle_root: bool,
     timestamp: bool,
     bits: bool,
+    bits_easier: bool,
+    bits_harder: bool,
+    version_below_bip34: bool,
+    version_below_bip66: bool,
+    version_below_bip65: bool,
     nonce: bool,
     all: bool,
+    remove_coinbase: bool,
+    reorder_txs: bool,
+    duplicate_tx: bool,
+    tamper_tx: bool,
+    duplicate_tail_txs: bool,
+    insert_64_byte_tx: bool,
+    coinbase_overpay: bool,
+    coinbase_wrong_height: bool,
+    coinbase_no_height: bool,
+    coinbase_short_script: bool,
+    coinbase_long_script: bool,
+    coinbase_prevout: bool,
+    multiple_coinbases: bool,
+    coinbase_no_witness_nonce: bool,
+    commitment_wrong_hash: bool,
+    commitment_missing: bool,
+    commitment_not_last: bool,
+    commitment_wrong_magic: bool,
+    commitment_nonce_size: bool,
     version_override: Option<i32>,
     timestamp_offset: Option<i64>,
     zero_hashes: bool,
+    seed: Option<u64>,
+}
+
+pub fn handle() -> ExitCode {
+    let cli = Cli::parse();
+
+    match cli.command {
+        None | Some(Commands::Interactive) => {
+            interactive();
+            ExitCode::SUCCESS
+        }
+        Some(command) => {
+            let regtest_manager = Generator::regtest_invocation("bitcoinhos", "-regtest");
+            match run(command, cli.format, &regtest_manager) {
+                Ok(()) => ExitCode::SUCCESS,
+                Err(e) => {
+                    eprintln!("Error: {e} 🚨");
+                    ExitCode::FAILURE
+                }
+            }
+        }
+    }
 }
 
-pub fn handle() {
+fn interactive() {
     let regtest_manager = Generator::regtest_invocation("bitcoinhos", "-regtest");
 
     loop {
@@ -142,80 +393,231 @@ pub fn handle() {
         };
 
         match cli.command {
-            Commands::Help => help(),
-            Commands::DecodeTransaction { raw_transaction } => transaction_splitter(raw_transaction),
-            Commands::DecodeBlock { block_header } => block_splitter(block_header),
-            Commands::BreakTransaction { 
-                raw_transaction, 
-                version, 
-                txid, 
-                vout, 
-                script_sig, 
-                sequence, 
-                amount, 
-                script_pubkey, 
-                witness, 
-                locktime, 
-                all 
-            } => {
-                let flags = build_transaction_flags_vector(TransactionFlags {
-                    version,
-                    txid,
-                    vout,
-                    script_sig,
-                    sequence,
-                    amount,
-                    script_pubkey,
-                    witness,
-                    locktime,
-                    all,
-                });
-                break_transaction(raw_transaction, flags);
-            },
-            Commands::BreakBlock {
-                block_header,
+            Some(Commands::Exit) => break,
+            Some(Commands::Interactive) => println!("Already in interactive mode"),
+            Some(command) => {
+                if let Err(e) = run(command, cli.format, &regtest_manager) {
+                    eprintln!("Error: {e} 🚨");
+                }
+            }
+            None => {}
+        }
+    }
+    println!("Program finalized 👋");
+}
+
+/// Execute a single command, as used by both one-shot and interactive mode.
+pub fn run(
+    command: Commands,
+    format: OutputFormat,
+    regtest_manager: &RegtestManager,
+) -> Result<(), Box<dyn Error>> {
+    match command {
+        Commands::Help => help(),
+        Commands::DecodeTransaction { raw_transaction } => {
+            transaction_splitter(raw_transaction, format)?
+        }
+        Commands::DecodeBlock { block_header } => block_splitter(block_header, format)?,
+        Commands::DecodeFullBlock { raw_block } => full_block_splitter(raw_block, format)?,
+        Commands::ForgeMerkleProof { raw_block } => {
+            let report = Generator::forge_merkle_proof(raw_block)?;
+            print_report(&report, format, None)?;
+        }
+        Commands::BreakTransaction { 
+            raw_transaction, 
+            version, 
+            txid, 
+            vout, 
+            script_sig, 
+            sequence, 
+            amount, 
+            script_pubkey, 
+            witness, 
+            locktime, 
+            tr_control_parity,
+            tr_control_size,
+            tr_merkle_path,
+            tr_annex,
+            tr_sighash_default,
+            nested_redeem_script,
+            nested_witness_pubkey,
+            nested_witness_script,
+            all,
+            seed,
+        } => {
+            let flags = build_transaction_flags_vector(TransactionFlags {
+                version,
+                txid,
+                vout,
+                script_sig,
+                sequence,
+                amount,
+                script_pubkey,
+                witness,
+                locktime,
+                tr_control_parity,
+                tr_control_size,
+                tr_merkle_path,
+                tr_annex,
+                tr_sighash_default,
+                nested_redeem_script,
+                nested_witness_pubkey,
+                nested_witness_script,
+                all,
+            });
+            break_transaction(raw_transaction, flags, seed, format)?;
+        },
+        Commands::BreakBlock {
+            block_header,
+            version,
+            prev_hash,
+            merkle_root,
+            timestamp,
+            bits,
+            bits_easier,
+            bits_harder,
+            version_below_bip34,
+            version_below_bip66,
+            version_below_bip65,
+            nonce,
+            all,
+            remove_coinbase,
+            reorder_txs,
+            duplicate_tx,
+            tamper_tx,
+            duplicate_tail_txs,
+            insert_64_byte_tx,
+            coinbase_overpay,
+            coinbase_wrong_height,
+            coinbase_no_height,
+            coinbase_short_script,
+            coinbase_long_script,
+            coinbase_prevout,
+            multiple_coinbases,
+            coinbase_no_witness_nonce,
+            commitment_wrong_hash,
+            commitment_missing,
+            commitment_not_last,
+            commitment_wrong_magic,
+            commitment_nonce_size,
+            version_override,
+            timestamp_offset,
+            zero_hashes,
+            seed,
+        } => {
+            let (flags, config) = build_block_flags_and_config(BlockFlagsConfig {
                 version,
                 prev_hash,
                 merkle_root,
                 timestamp,
                 bits,
+                bits_easier,
+                bits_harder,
+                version_below_bip34,
+                version_below_bip66,
+                version_below_bip65,
                 nonce,
                 all,
+                remove_coinbase,
+                reorder_txs,
+                duplicate_tx,
+                tamper_tx,
+                duplicate_tail_txs,
+                insert_64_byte_tx,
+                coinbase_overpay,
+                coinbase_wrong_height,
+                coinbase_no_height,
+                coinbase_short_script,
+                coinbase_long_script,
+                coinbase_prevout,
+                multiple_coinbases,
+                coinbase_no_witness_nonce,
+                commitment_wrong_hash,
+                commitment_missing,
+                commitment_not_last,
+                commitment_wrong_magic,
+                commitment_nonce_size,
                 version_override,
                 timestamp_offset,
                 zero_hashes,
-            } => {
-                let (flags, config) = build_block_flags_and_config(BlockFlagsConfig {
-                    version,
-                    prev_hash,
-                    merkle_root,
-                    timestamp,
-                    bits,
-                    nonce,
-                    all,
-                    version_override,
-                    timestamp_offset,
-                    zero_hashes,
-                });
-                break_block(block_header, flags, config);
-            },
-            Commands::Tx { txscount, .. } => transaction(txscount), // TODO: Implement params into transaction generator
-            Commands::Block { txscount } => block(txscount),
-            Commands::Clear => clear(),
-            Commands::RegtestStart => handle_result(regtest_manager.start()),
-            Commands::RegtestStop => handle_result(regtest_manager.stop()),
-            Commands::GetBlockbyHeight { height } => {
-                handle_result(regtest_manager.handle_getblockbyheight(height))
+                seed,
+            });
+            break_block(block_header, flags, config, format)?;
+        },
+        Commands::Tx { txscount, seed, .. } => transaction(txscount, seed, format)?, // TODO: Implement params into transaction generator
+        Commands::Block { txscount, seed } => block(txscount, seed, format)?,
+        Commands::Chain { length, seed } => chain(length, seed, format)?,
+        Commands::Headers { length, break_at, breakage, flat, out, seed } => {
+            let break_at = break_at.zip(breakage);
+            let report = Generator::header_chain(length, break_at, flat, seed)?;
+            match out {
+                Some(path) => {
+                    let payload = hex::decode(&report.payload_hex)?;
+                    std::fs::write(&path, &payload)?;
+                    println!("Wrote {} headers ({} bytes) to {}", report.count, payload.len(), path.display());
+                }
+                None => print_report(&report, format, None)?,
+            }
+        }
+        Commands::Multisig { threshold, keys, wrap, seed } => {
+            let report = Generator::multisig(threshold, keys, wrap, seed)?;
+            print_report(&report, format, None)?;
+        }
+        Commands::Tapscript { leaves, leaf, breakage, seed } => {
+            let leaves = (!leaves.is_empty()).then_some(leaves);
+            let report = Generator::tapscript(leaves, leaf, breakage, seed)?;
+            print_report(&report, format, None)?;
+        }
+        Commands::CompactBlock { txscount, prefill, breakage, seed } => {
+            let report = Generator::compact_block(txscount, &prefill, breakage, seed)?;
+            print_report(&report, format, None)?;
+        }
+        Commands::Wrap { raw, command, network, breakage, out } => {
+            let report = Generator::wrap_message(raw, command, network, breakage)?;
+            match out {
+                Some(path) => {
+                    let message = hex::decode(&report.message_hex)?;
+                    std::fs::write(&path, &message)?;
+                    println!("Wrote {} message ({} bytes) to {}", report.command, message.len(), path.display());
+                }
+                None => print_report(&report, format, None)?,
             }
-            Commands::Exit => break
         }
+        Commands::Signal { bit, length, per_period, seed } => {
+            let report = Generator::version_bits_chain(bit, length, per_period, seed);
+            print_report(&report, format, None)?;
+        }
+        Commands::Timestamps { now, seed } => {
+            let report = Generator::timestamp_scenarios(now, seed)?;
+            print_report(&report, format, None)?;
+        }
+        Commands::Retarget { last_bits, first_time, last_time, network } => {
+            let report = Generator::retarget(last_bits, first_time, last_time, network);
+            print_report(&report, format, None)?;
+        }
+        Commands::Clear => clear(),
+        Commands::RegtestStart => regtest_manager.start()?,
+        Commands::RegtestStop => regtest_manager.stop()?,
+        Commands::GetBlockbyHeight { height } => {
+            regtest_manager.handle_getblockbyheight(height)?
+        }
+        Commands::Exit | Commands::Interactive => {}
     }
-    println!("Program finalized 👋");
+    Ok(())
 }
  
 fn build_transaction_flags_vector(flags: TransactionFlags) -> Vec<String> {
     let mut result = Vec::new();
 
+    // Taproot and nested segwit breakers are not part of --all
+    if flags.tr_control_parity { result.push("--tr-control-parity".to_string()); }
+    if flags.tr_control_size { result.push("--tr-control-size".to_string()); }
+    if flags.tr_merkle_path { result.push("--tr-merkle-path".to_string()); }
+    if flags.tr_annex { result.push("--tr-annex".to_string()); }
+    if flags.tr_sighash_default { result.push("--tr-sighash-default".to_string()); }
+    if flags.nested_redeem_script { result.push("--nested-redeem-script".to_string()); }
+    if flags.nested_witness_pubkey { result.push("--nested-witness-pubkey".to_string()); }
+    if flags.nested_witness_script { result.push("--nested-witness-script".to_string()); }
     if flags.all {
         result.push("--all".to_string());
         return result;
@@ -246,8 +648,32 @@ fn build_block_flags_and_config(cfg: BlockFlagsConfig) -> (Vec<String>, Vec<Stri
         if cfg.merkle_root { flags.push("--merkle-root".to_string()); }
         if cfg.timestamp { flags.push("--timestamp".to_string()); }
         if cfg.bits { flags.push("--bits".to_string()); }
+        if cfg.bits_easier { flags.push("--bits-easier".to_string()); }
+        if cfg.bits_harder { flags.push("--bits-harder".to_string()); }
+        if cfg.version_below_bip34 { flags.push("--version-below-bip34".to_string()); }
+        if cfg.version_below_bip66 { flags.push("--version-below-bip66".to_string()); }
+        if cfg.version_below_bip65 { flags.push("--version-below-bip65".to_string()); }
         if cfg.nonce { flags.push("--nonce".to_string()); }
     }
+    if cfg.remove_coinbase { flags.push("--remove-coinbase".to_string()); }
+    if cfg.reorder_txs { flags.push("--reorder-txs".to_string()); }
+    if cfg.duplicate_tx { flags.push("--duplicate-tx".to_string()); }
+    if cfg.tamper_tx { flags.push("--tamper-tx".to_string()); }
+    if cfg.duplicate_tail_txs { flags.push("--duplicate-tail-txs".to_string()); }
+    if cfg.insert_64_byte_tx { flags.push("--insert-64-byte-tx".to_string()); }
+    if cfg.coinbase_overpay { flags.push("--coinbase-overpay".to_string()); }
+    if cfg.coinbase_wrong_height { flags.push("--coinbase-wrong-height".to_string()); }
+    if cfg.coinbase_no_height { flags.push("--coinbase-no-height".to_string()); }
+    if cfg.coinbase_short_script { flags.push("--coinbase-short-script".to_string()); }
+    if cfg.coinbase_long_script { flags.push("--coinbase-long-script".to_string()); }
+    if cfg.coinbase_prevout { flags.push("--coinbase-prevout".to_string()); }
+    if cfg.multiple_coinbases { flags.push("--multiple-coinbases".to_string()); }
+    if cfg.coinbase_no_witness_nonce { flags.push("--coinbase-no-witness-nonce".to_string()); }
+    if cfg.commitment_wrong_hash { flags.push("--commitment-wrong-hash".to_string()); }
+    if cfg.commitment_missing { flags.push("--commitment-missing".to_string()); }
+    if cfg.commitment_not_last { flags.push("--commitment-not-last".to_string()); }
+    if cfg.commitment_wrong_magic { flags.push("--commitment-wrong-magic".to_string()); }
+    if cfg.commitment_nonce_size { flags.push("--commitment-nonce-size".to_string()); }
 
     if let Some(override_val) = cfg.version_override {
         config.push(format!("--version-override={override_val}"));
@@ -258,6 +684,9 @@ fn build_block_flags_and_config(cfg: BlockFlagsConfig) -> (Vec<String>, Vec<Stri
     if cfg.zero_hashes {
         config.push("--zero-hashes".to_string());
     }
+    if let Some(seed) = cfg.seed {
+        config.push(format!("--seed={seed}"));
+    }
 
     (flags, config)
 }
@@ -267,10 +696,13 @@ fn help() {
     println!("\x1b[32m[Utills]\x1b[0m");
     println!("help                                  - Show help message");
     println!("clear                                 - Clear terminal screen");
+    println!("interactive (alias: shell)            - Start the interactive shell");
     println!("exit");
+    println!("--format <text|json>                  - Output format of any command (default: text)");
     println!("\x1b[32m[Decode]\x1b[0m");
     println!("decode-transaction <raw_tx>           - Decode a raw transaction");
     println!("decode-block <block_header>           - Decode a block header");
+    println!("decode-full-block <raw_block>         - Decode a full block and report consensus issues");
     println!(" ");
     println!("\x1b[32m[Break/Invalidate]\x1b[0m");
     println!("  \x1b[34mbreak-transaction <raw_tx> [FLAGS]\x1b[0m   - Break/invalidate specific fields of a transaction");
@@ -285,23 +717,92 @@ fn help() {
     println!("    --witness         - Invalidate witness data");
     println!("    --locktime        - Invalidate transaction locktime");
     println!("    --all             - Invalidate all transaction fields");
-    println!("\x1b[34mbreak-block <block_header> [FLAGS]\x1b[0m - Break/invalidate specific fields of a block");
+    println!("  Taproot flags (not part of --all):");
+    println!("    --tr-control-parity  - Flip the output key parity of control blocks");
+    println!("    --tr-control-size    - Grow control blocks by one byte");
+    println!("    --tr-merkle-path     - Corrupt the merkle path of control blocks");
+    println!("    --tr-annex           - Append an annex to the witnesses");
+    println!("    --tr-sighash-default - Append SIGHASH_DEFAULT to 64-byte Schnorr signatures");
+    println!("  Nested segwit flags (not part of --all):");
+    println!("    --nested-redeem-script  - Push another witness program than the P2SH hash commits to");
+    println!("    --nested-witness-pubkey - Swap the P2SH-P2WPKH witness key for another");
+    println!("    --nested-witness-script - Alter the P2SH-P2WSH witness script");
+    println!("    --seed <n>        - Seed the random generator for reproducible output");
+    println!("\x1b[34mbreak-block <block_header|raw_block> [FLAGS]\x1b[0m - Break/invalidate specific fields of a block");
     println!("  Available flags:");
     println!("    --version         - Invalidate block version");
     println!("    --prev-hash       - Invalidate previous block hash");
     println!("    --merkle-root     - Invalidate merkle root");
     println!("    --timestamp       - Invalidate timestamp");
     println!("    --bits            - Invalidate difficulty bits");
+    println!("    --bits-easier     - Set bits one compact unit easier");
+    println!("    --bits-harder     - Set bits one compact unit harder");
+    println!("    --version-below-bip34 - Set version 1, below the BIP34 minimum");
+    println!("    --version-below-bip66 - Set version 2, below the BIP66 minimum");
+    println!("    --version-below-bip65 - Set version 3, below the BIP65 minimum");
     println!("    --nonce           - Invalidate nonce");
-    println!("    --all             - Invalidate all block fields");
+    println!("    --all             - Invalidate all block header fields");
+    println!("  Transaction flags (need a full block):");
+    println!("    --remove-coinbase - Remove the coinbase transaction");
+    println!("    --reorder-txs     - Move the coinbase away from the first position");
+    println!("    --duplicate-tx    - Duplicate a transaction");
+    println!("    --tamper-tx       - Tamper with a transaction, keeping the header");
+    println!("    --duplicate-tail-txs  - Repeat the trailing transactions, keeping the merkle root");
+    println!("    --insert-64-byte-tx   - Insert a 64-byte transaction");
+    println!("  Coinbase flags (need a full block):");
+    println!("    --coinbase-overpay            - Overpay the block subsidy and fees by 1 sat");
+    println!("    --coinbase-wrong-height       - Push the wrong BIP34 height in the coinbase");
+    println!("    --coinbase-no-height          - Drop the BIP34 height from the coinbase");
+    println!("    --coinbase-short-script       - Shorten the coinbase script_sig below 2 bytes");
+    println!("    --coinbase-long-script        - Grow the coinbase script_sig above 100 bytes");
+    println!("    --coinbase-prevout            - Give the coinbase a non-null prevout");
+    println!("    --multiple-coinbases          - Add a second coinbase");
+    println!("    --coinbase-no-witness-nonce   - Drop the coinbase witness reserved value");
+    println!("  Witness commitment flags (need a full block):");
+    println!("    --commitment-wrong-hash       - Commit to the wrong witness root");
+    println!("    --commitment-missing          - Drop the witness commitment, keeping the witnesses");
+    println!("    --commitment-not-last         - Follow the witness commitment with a wrong one");
+    println!("    --commitment-wrong-magic      - Corrupt the witness commitment magic bytes");
+    println!("    --commitment-nonce-size       - Grow the witness reserved value to 33 bytes");
     println!("  Configuration options:");
     println!("    --version-override <value>  - Override version with specific value");
     println!("    --timestamp-offset <secs>   - Add/subtract seconds to timestamp");
     println!("    --zero-hashes               - Use zero hashes instead of random");
+    println!("    --seed <n>                  - Seed the random generator for reproducible output");
     println!("\x1b[32m[Generate]\x1b[0m");
-    println!("tx <txscount> [params...]             - Generate one or more transactions");
+    println!("tx <txscount> [params...] [--seed <n>] - Generate one or more transactions");
+    println!(
+        "block <txscount> [--seed <n>]         - Generate new block with one or more transactions"
+    );
+    println!(
+        "chain <length> [--seed <n>]           - Generate linked blocks on top of the regtest genesis"
+    );
+    println!(
+        "signal <bit> [length] [--per-period <n>] [--seed <n>] - Chain signalling a BIP9 deployment on regtest"
+    );
+    println!(
+        "headers [length] [--break-at <k> --break <pow|prev-hash|bits>] [--flat] [--out <file>] [--seed <n>] - Header-only chain for light clients"
+    );
+    println!(
+        "timestamps [--now <ts>] [--seed <n>]  - Blocks at the median-time-past and future drift limits"
+    );
+    println!(
+        "retarget <bits> <first_time> <last_time> [--network <name>] - Bits required after a 2016-block window"
+    );
+    println!(
+        "forge-merkle-proof <raw_block>        - Forge an SPV proof from a block's 64-byte transaction"
+    );
+    println!(
+        "multisig [m] [n] [--wrap <type>] [--seed <n>] - Spend an m-of-n multisig output (bare, p2sh, p2wsh, p2sh-p2wsh)"
+    );
+    println!(
+        "tapscript [--leaves <l1,l2>] [--leaf <i>] [--break <kind>] [--seed <n>] - Spend a P2TR output through a leaf of its script tree"
+    );
+    println!(
+        "compact-block [txscount] [--prefill <i,j>] [--break <kind>] [--seed <n>] - BIP152 cmpctblock, getblocktxn and blocktxn"
+    );
     println!(
-        "block <txscount>                      - Generate new block with one or more transactions"
+        "wrap <raw> <command> [--network <name>] [--break <kind>] [--out <file>] - Wrap in a P2P message (tx, block, headers, cmpctblock, inv)"
     );
     println!("\x1b[32m[Regtest]\x1b[0m");
     println!(
@@ -311,76 +812,76 @@ fn help() {
     println!("regtest-stop                          - Stop the regtest node (please remember to stop before closing the program)");
 }
 
-fn transaction_splitter(raw_transaction: String) {
-    match Generator::decode_raw_transaction(raw_transaction) {
-        Ok(decoded) => {
-            println!("Version: {}", decoded.version);
-            println!("Locktime: {}", decoded.lock_time);
-            println!("Input count: {:#?}", decoded.input);
-            println!("Output count: {:#?}", decoded.output);
-        },
-        Err(e) => {
-            eprintln!("Error decoding transaction: {e}");
-        }
-    }
+fn transaction_splitter(raw_transaction: String, format: OutputFormat) -> Result<(), Box<dyn Error>> {
+    let decoded = Generator::decode_raw_transaction(raw_transaction)?;
+    print_report(&DecodedTransactionReport::from(&decoded), format, None)
 }
 
-fn block_splitter(block_header: String) {
-    match Generator::decoder_block_header(block_header) {
-        Ok(header) => {
-            println!("Version: {}", header.version.to_consensus());
-            println!("Previous Block: {}", header.prev_blockhash);
-            println!("Merkle Root: {}", header.merkle_root);
-            println!("Timestamp: {}", header.time);
-            println!("Bits: 0x{:08x}", header.bits.to_consensus());
-            println!("Nonce: {}", header.nonce);
-            println!("Block Hash: {}", header.block_hash());
-        },
-        Err(e) => {
-            eprintln!("Error decoding block header: {e}");
-        }
-    }
+fn block_splitter(block_header: String, format: OutputFormat) -> Result<(), Box<dyn Error>> {
+    let header = Generator::decoder_block_header(block_header)?;
+    print_report(&HeaderReport::from(&header), format, None)
 }
 
-fn break_transaction(raw_transaction: String, flags: Vec<String>) {
-    if flags.is_empty() {
-        println!("No invalidation flags specified. Use 'help' for usage information.");
-        return;
-    }
-    
-    let result = Generator::break_transaction(raw_transaction, flags);
-    println!("🔨 Transaction Breaking Result:");
-    println!("{result}");
+fn full_block_splitter(raw_block: String, format: OutputFormat) -> Result<(), Box<dyn Error>> {
+    let block = Generator::decode_full_block(raw_block)?;
+    print_report(&DecodedBlockReport::from(&block), format, None)
 }
 
-fn break_block(block_header: String, flags: Vec<String>, config: Vec<String>) {
-    if flags.is_empty() {
-        println!("No invalidation flags specified. Use 'help' for usage information.");
-        return;
-    }
-    
-    let result = Generator::break_block(block_header, flags, config);
-    println!("🔨 Block Breaking Result:");
-    println!("{result}");
+fn break_transaction(
+    raw_transaction: String,
+    flags: Vec<String>,
+    seed: Option<u64>,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    let report = Generator::break_transaction(raw_transaction, flags, seed)?;
+    print_report(&report, format, Some("🔨 Transaction Breaking Result:\n"))
 }
 
-fn transaction(txscount: u32) {
-    let transactions = Generator::transaction(txscount);
-    println!("Transactions: {transactions}");
+fn break_block(
+    block_header: String,
+    flags: Vec<String>,
+    config: Vec<String>,
+    format: OutputFormat,
+) -> Result<(), Box<dyn Error>> {
+    let report = Generator::break_block(block_header, flags, config)?;
+    print_report(&report, format, Some("🔨 Block Breaking Result:\n"))
 }
 
-fn block(txscount: u32) {
-    let block = Generator::block(txscount);
-    println!("Block: {block}");
+fn transaction(txscount: u32, seed: Option<u64>, format: OutputFormat) -> Result<(), Box<dyn Error>> {
+    let transactions = Generator::transaction(txscount, seed);
+    print_report(&transactions, format, Some("Transactions: "))
 }
 
-fn clear() {
-    print!("\x1B[2J\x1B[1;1H"); 
-    io::stdout().flush().unwrap();
+fn block(txscount: u32, seed: Option<u64>, format: OutputFormat) -> Result<(), Box<dyn Error>> {
+    let block = Generator::block(txscount, seed);
+    print_report(&block, format, Some("Block: "))
+}
+
+fn chain(length: usize, seed: Option<u64>, format: OutputFormat) -> Result<(), Box<dyn Error>> {
+    let chain = Generator::chain(length, seed);
+    print_report(&chain, format, Some("Chain:\n"))
+}
+
+// Compact bits as 8 hex digits, with or without a 0x prefix
+fn parse_bits(value: &str) -> Result<u32, String> {
+    let digits = value.trim_start_matches("0x");
+    u32::from_str_radix(digits, 16).map_err(|e| format!("invalid bits '{value}': {e}"))
 }
 
-fn handle_result(result: Result<(), Box<dyn std::error::Error>>) {
-    if let Err(e) = result {
-        eprintln!("Error: {e} 🚨");
+/// Print a report as text, preceded by `title`, or as pretty-printed JSON.
+fn print_report<T: Serialize + Display>(
+    report: &T,
+    format: OutputFormat,
+    title: Option<&str>,
+) -> Result<(), Box<dyn Error>> {
+    match format {
+        OutputFormat::Text => println!("{}{report}", title.unwrap_or_default()),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(report)?),
     }
+    Ok(())
+}
+
+fn clear() {
+    print!("\x1B[2J\x1B[1;1H"); 
+    io::stdout().flush().unwrap();
 }
\ No newline at end of file
@@ -0,0 +1,78 @@
+//! Error type returned by the public API.
+
+use std::{fmt, io, string::FromUtf8Error};
+
+use bitcoin::consensus::encode;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Input could not be decoded as hex or as a consensus-encoded structure.
+    Decode(String),
+    /// A breaker flag that is not recognised.
+    InvalidFlag(String),
+    /// A breaker was asked to run without any flag.
+    NoFlags,
+    /// A configuration option that is unknown, has an invalid value or does
+    /// not apply to the input.
+    InvalidConfig(String),
+    /// The regtest node or `bitcoin-cli` failed or answered unexpectedly.
+    Node(String),
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Decode(msg) => write!(f, "Decode error: {msg}"),
+            Self::InvalidFlag(flag) => write!(f, "Unknown flag '{flag}'"),
+            Self::NoFlags => write!(
+                f,
+                "No invalidation flags specified. Use 'help' for usage information."
+            ),
+            Self::InvalidConfig(msg) => write!(f, "Invalid config: {msg}"),
+            Self::Node(msg) => write!(f, "Node error: {msg}"),
+            Self::Io(e) => write!(f, "I/O error: {e}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<hex::FromHexError> for Error {
+    fn from(e: hex::FromHexError) -> Self {
+        Self::Decode(e.to_string())
+    }
+}
+
+impl From<encode::Error> for Error {
+    fn from(e: encode::Error) -> Self {
+        Self::Decode(e.to_string())
+    }
+}
+
+impl From<FromUtf8Error> for Error {
+    fn from(e: FromUtf8Error) -> Self {
+        Self::Node(e.to_string())
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Node(e.to_string())
+    }
+}
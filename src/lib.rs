@@ -1,3 +1,10 @@
 pub mod transaction;
 pub mod regtest_pack;
-pub mod block;
\ No newline at end of file
+pub mod block;
+pub mod p2p;
+pub mod rng;
+pub mod report;
+pub mod api;
+pub mod error;
+
+pub use error::{Error, Result};
@@ -1,6 +1,7 @@
 mod cli;
-mod api;
 mod tests;
-fn main() {
-    cli::handle();
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    cli::handle()
 } 
\ No newline at end of file
@@ -0,0 +1,119 @@
+use std::str::FromStr;
+
+use super::message::{WireMessage, MAX_PROTOCOL_MESSAGE_LENGTH, MESSAGE_HEADER_SIZE};
+
+// Offsets of the envelope fields after the 4-byte magic
+const COMMAND_RANGE: std::ops::Range<usize> = 4..16;
+const LENGTH_RANGE: std::ops::Range<usize> = 16..20;
+const CHECKSUM_RANGE: std::ops::Range<usize> = 20..24;
+
+/// Command no peer implements, within the 12 bytes a command may take.
+pub const UNKNOWN_COMMAND: &[u8] = b"misfit";
+
+/// Ways to break the envelope of a P2P message
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnvelopeBreak {
+    BadChecksum,
+    WrongLength,
+    Oversized,
+    UnknownCommand,
+}
+
+impl EnvelopeBreak {
+    pub const ALL: [EnvelopeBreak; 4] = [
+        Self::BadChecksum,
+        Self::WrongLength,
+        Self::Oversized,
+        Self::UnknownCommand,
+    ];
+
+    /// Name accepted on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::BadChecksum => "checksum",
+            Self::WrongLength => "length",
+            Self::Oversized => "oversized",
+            Self::UnknownCommand => "unknown-command",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::BadChecksum => "Checksum with its first byte flipped",
+            Self::WrongLength => "Length one byte short of the payload",
+            Self::Oversized => "Payload padded past the 4,000,000 byte limit",
+            Self::UnknownCommand => "Command no node implements",
+        }
+    }
+
+    /// What Bitcoin Core logs when it reads the message. A bad checksum only
+    /// drops the message; the byte a short length leaves behind is then read
+    /// as the magic of the next one, which disconnects the peer.
+    pub fn expected_failure(&self) -> &'static str {
+        match self {
+            Self::BadChecksum | Self::WrongLength => "Header error: Wrong checksum",
+            Self::Oversized => "Header error: Size too large",
+            Self::UnknownCommand => "Unknown message type",
+        }
+    }
+}
+
+impl FromStr for EnvelopeBreak {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|breakage| breakage.name() == value)
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::ALL.iter().map(EnvelopeBreak::name).collect();
+                format!("unknown envelope break '{value}', expected one of {}", names.join(", "))
+            })
+    }
+}
+
+/// Processor for modifications of a serialized message's envelope
+///
+/// Each mutation leaves messages shorter than the 24-byte envelope alone.
+pub struct EnvelopeProcessor;
+
+impl EnvelopeProcessor {
+    pub fn process(message: &mut Vec<u8>, breakage: EnvelopeBreak) {
+        if message.len() < MESSAGE_HEADER_SIZE {
+            return;
+        }
+        match breakage {
+            EnvelopeBreak::BadChecksum => message[CHECKSUM_RANGE.start] ^= 0xff,
+            EnvelopeBreak::WrongLength => {
+                let length = Self::length(message).saturating_sub(1);
+                message[LENGTH_RANGE].copy_from_slice(&length.to_le_bytes());
+            }
+            EnvelopeBreak::Oversized => {
+                // Zero padding keeps the length and checksum consistent
+                message.resize(MESSAGE_HEADER_SIZE + MAX_PROTOCOL_MESSAGE_LENGTH + 1, 0x00);
+                Self::refresh(message);
+            }
+            EnvelopeBreak::UnknownCommand => {
+                let mut command = [0u8; 12];
+                command[..UNKNOWN_COMMAND.len()].copy_from_slice(UNKNOWN_COMMAND);
+                message[COMMAND_RANGE].copy_from_slice(&command);
+            }
+        }
+    }
+
+    /// Length the envelope declares for the payload
+    pub fn length(message: &[u8]) -> u32 {
+        let mut length = [0u8; 4];
+        length.copy_from_slice(&message[LENGTH_RANGE]);
+        u32::from_le_bytes(length)
+    }
+
+    // Recompute the length and checksum from the payload
+    fn refresh(message: &mut [u8]) {
+        let payload = &message[MESSAGE_HEADER_SIZE..];
+        let length = (payload.len() as u32).to_le_bytes();
+        let checksum = WireMessage::checksum(payload);
+        message[LENGTH_RANGE].copy_from_slice(&length);
+        message[CHECKSUM_RANGE].copy_from_slice(&checksum);
+    }
+}
@@ -0,0 +1,172 @@
+use std::str::FromStr;
+
+use bitcoin::{
+    bip152::{BlockTransactions, BlockTransactionsRequest, HeaderAndShortIds},
+    Block,
+};
+
+use crate::{Error, Result};
+
+/// BIP152 version whose short IDs and transactions carry witnesses.
+pub const COMPACT_BLOCK_VERSION: u32 = 2;
+
+/// Ways to break a compact block or the transactions sent for it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompactBlockBreak {
+    ShortIdCollision,
+    PrefilledIndexOutOfRange,
+    BlockTxnMissing,
+    BlockTxnExtra,
+}
+
+impl CompactBlockBreak {
+    pub const ALL: [CompactBlockBreak; 4] = [
+        Self::ShortIdCollision,
+        Self::PrefilledIndexOutOfRange,
+        Self::BlockTxnMissing,
+        Self::BlockTxnExtra,
+    ];
+
+    /// Name accepted on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ShortIdCollision => "collision",
+            Self::PrefilledIndexOutOfRange => "prefill-range",
+            Self::BlockTxnMissing => "blocktxn-missing",
+            Self::BlockTxnExtra => "blocktxn-extra",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::ShortIdCollision => "Two transactions sharing a short ID",
+            Self::PrefilledIndexOutOfRange => "Last prefilled transaction indexed past the block",
+            Self::BlockTxnMissing => "blocktxn response without the last requested transaction",
+            Self::BlockTxnExtra => "blocktxn response with an unrequested transaction",
+        }
+    }
+
+    /// What Bitcoin Core makes of it. A collision is not the peer's fault,
+    /// so Core falls back to asking for the full block; the rest get the
+    /// peer marked as misbehaving.
+    pub fn expected_failure(&self) -> &'static str {
+        match self {
+            Self::ShortIdCollision => "READ_STATUS_FAILED",
+            Self::PrefilledIndexOutOfRange => "invalid compact block",
+            Self::BlockTxnMissing | Self::BlockTxnExtra => {
+                "invalid compact block/non-matching block transactions"
+            }
+        }
+    }
+}
+
+impl FromStr for CompactBlockBreak {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|breakage| breakage.name() == value)
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::ALL.iter().map(CompactBlockBreak::name).collect();
+                format!("unknown compact block break '{value}', expected one of {}", names.join(", "))
+            })
+    }
+}
+
+/// A block as BIP152 relays it to a peer with an empty mempool: the
+/// `cmpctblock`, the `getblocktxn` asking for every transaction sent as a
+/// short ID, and the `blocktxn` answering it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompactBlock {
+    pub block: Block,
+    pub compact: HeaderAndShortIds,
+    pub request: BlockTransactionsRequest,
+    pub response: BlockTransactions,
+}
+
+impl CompactBlock {
+    /// Compact `block` with the SipHash keys of `nonce`, prefilling the
+    /// coinbase and the transactions at the sorted indexes of `prefill`.
+    pub fn from_block(block: Block, nonce: u64, prefill: &[usize]) -> Result<Self> {
+        let compact = HeaderAndShortIds::from_block(&block, nonce, COMPACT_BLOCK_VERSION, prefill)
+            .map_err(|e| Error::InvalidConfig(format!("cannot prefill {prefill:?}: {e}")))?;
+        let prefilled = Self::prefilled_indexes(&compact);
+        let request = BlockTransactionsRequest {
+            block_hash: block.block_hash(),
+            indexes: (0..block.txdata.len() as u64)
+                .filter(|index| !prefilled.contains(&(*index as usize)))
+                .collect(),
+        };
+        let response = BlockTransactions::from_request(&request, &block)
+            .map_err(|e| Error::InvalidConfig(e.to_string()))?;
+
+        Ok(Self { block, compact, request, response })
+    }
+
+    /// Absolute block positions of the prefilled transactions, which the
+    /// message encodes as differences
+    pub fn prefilled_indexes(compact: &HeaderAndShortIds) -> Vec<usize> {
+        let mut next = 0;
+        compact
+            .prefilled_txs
+            .iter()
+            .map(|prefilled| {
+                let index = next + prefilled.idx as usize;
+                next = index + 1;
+                index
+            })
+            .collect()
+    }
+}
+
+/// Processor for modifications of compact blocks and their `blocktxn`
+///
+/// Each mutation returns false, leaving the compact block alone, when it
+/// does not apply.
+pub struct CompactBlockProcessor;
+
+impl CompactBlockProcessor {
+    pub fn process(compact_block: &mut CompactBlock, breakage: CompactBlockBreak) -> bool {
+        match breakage {
+            CompactBlockBreak::ShortIdCollision => Self::short_id_collision(compact_block),
+            CompactBlockBreak::PrefilledIndexOutOfRange => Self::prefilled_out_of_range(compact_block),
+            CompactBlockBreak::BlockTxnMissing => {
+                compact_block.response.transactions.pop().is_some()
+            }
+            CompactBlockBreak::BlockTxnExtra => {
+                // The coinbase is always prefilled, so never requested
+                let coinbase = compact_block.block.txdata[0].clone();
+                compact_block.response.transactions.push(coinbase);
+                true
+            }
+        }
+    }
+
+    /// Give the last short ID the value of the first, as two transactions
+    /// whose wtxids collide under the block's SipHash keys would have.
+    /// Needs two short IDs.
+    pub fn short_id_collision(compact_block: &mut CompactBlock) -> bool {
+        let short_ids = &mut compact_block.compact.short_ids;
+        if short_ids.len() < 2 {
+            return false;
+        }
+        let last = short_ids.len() - 1;
+        short_ids[last] = short_ids[0];
+        true
+    }
+
+    /// Move the last prefilled transaction to the position just past the
+    /// end of the block
+    pub fn prefilled_out_of_range(compact_block: &mut CompactBlock) -> bool {
+        let Some(last) = CompactBlock::prefilled_indexes(&compact_block.compact).pop() else {
+            return false;
+        };
+        let shift = compact_block.block.txdata.len() - last;
+        let Some(prefilled) = compact_block.compact.prefilled_txs.last_mut() else {
+            return false;
+        };
+        prefilled.idx = prefilled.idx.saturating_add(shift as u16);
+        true
+    }
+}
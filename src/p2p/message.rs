@@ -0,0 +1,137 @@
+use std::str::FromStr;
+
+use bitcoin::{
+    bip152::HeaderAndShortIds,
+    block::Header,
+    consensus::encode,
+    hashes::{sha256d, Hash},
+    p2p::{
+        message::{NetworkMessage, RawNetworkMessage},
+        message_blockdata::Inventory,
+        message_compact_blocks::CmpctBlock,
+        Magic,
+    },
+    Block, Network, Transaction,
+};
+use rand::Rng;
+
+use crate::rng;
+use super::compact_block::COMPACT_BLOCK_VERSION;
+use crate::{Error, Result};
+
+/// Size of the envelope before the payload: magic, command, length and
+/// checksum.
+pub const MESSAGE_HEADER_SIZE: usize = 24;
+/// Largest payload Bitcoin Core reads before disconnecting the peer.
+pub const MAX_PROTOCOL_MESSAGE_LENGTH: usize = 4_000_000;
+
+/// Messages a generated object can be wrapped in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MessageCommand {
+    Tx,
+    Block,
+    Headers,
+    CmpctBlock,
+    Inv,
+}
+
+impl MessageCommand {
+    pub const ALL: [MessageCommand; 5] =
+        [Self::Tx, Self::Block, Self::Headers, Self::CmpctBlock, Self::Inv];
+
+    /// Command as it appears in the envelope
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Tx => "tx",
+            Self::Block => "block",
+            Self::Headers => "headers",
+            Self::CmpctBlock => "cmpctblock",
+            Self::Inv => "inv",
+        }
+    }
+}
+
+impl FromStr for MessageCommand {
+    type Err = String;
+
+    fn from_str(value: &str) -> std::result::Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|command| command.name() == value)
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::ALL.iter().map(MessageCommand::name).collect();
+                format!("unknown command '{value}', expected one of {}", names.join(", "))
+            })
+    }
+}
+
+/// Object carried by a message
+#[derive(Debug, Clone, PartialEq)]
+pub enum WireObject {
+    Transaction(Transaction),
+    Block(Block),
+    Header(Header),
+}
+
+impl WireObject {
+    /// Build the payload of `command` for this object. Blocks can go in
+    /// every message but `tx`, headers only in `headers` and `inv`, and
+    /// transactions only in `tx` and `inv`.
+    pub fn payload(&self, command: MessageCommand) -> Result<NetworkMessage> {
+        let message = match (command, self) {
+            (MessageCommand::Tx, Self::Transaction(tx)) => NetworkMessage::Tx(tx.clone()),
+            (MessageCommand::Block, Self::Block(block)) => NetworkMessage::Block(block.clone()),
+            (MessageCommand::Headers, Self::Block(block)) => NetworkMessage::Headers(vec![block.header]),
+            (MessageCommand::Headers, Self::Header(header)) => NetworkMessage::Headers(vec![*header]),
+            (MessageCommand::CmpctBlock, Self::Block(block)) => {
+                let nonce = rng::rng().random();
+                let compact_block =
+                    HeaderAndShortIds::from_block(block, nonce, COMPACT_BLOCK_VERSION, &[])
+                        .map_err(|e| Error::InvalidConfig(e.to_string()))?;
+                NetworkMessage::CmpctBlock(CmpctBlock { compact_block })
+            }
+            (MessageCommand::Inv, Self::Transaction(tx)) => {
+                NetworkMessage::Inv(vec![Inventory::Transaction(tx.compute_txid())])
+            }
+            (MessageCommand::Inv, Self::Block(block)) => {
+                NetworkMessage::Inv(vec![Inventory::Block(block.block_hash())])
+            }
+            (MessageCommand::Inv, Self::Header(header)) => {
+                NetworkMessage::Inv(vec![Inventory::Block(header.block_hash())])
+            }
+            (command, object) => {
+                return Err(Error::InvalidConfig(format!(
+                    "a {} message cannot carry a {}",
+                    command.name(),
+                    object.kind()
+                )))
+            }
+        };
+        Ok(message)
+    }
+
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Transaction(_) => "transaction",
+            Self::Block(_) => "block",
+            Self::Header(_) => "header",
+        }
+    }
+}
+
+/// Builds P2P messages in the v1 transport encoding
+pub struct WireMessage;
+
+impl WireMessage {
+    /// Serialized message carrying `object` in `command` for `network`
+    pub fn encode(object: &WireObject, command: MessageCommand, network: Network) -> Result<Vec<u8>> {
+        let payload = object.payload(command)?;
+        Ok(encode::serialize(&RawNetworkMessage::new(Magic::from(network), payload)))
+    }
+
+    /// First four bytes of the double SHA256 of `payload`
+    pub fn checksum(payload: &[u8]) -> [u8; 4] {
+        let hash = sha256d::Hash::hash(payload).to_byte_array();
+        [hash[0], hash[1], hash[2], hash[3]]
+    }
+}
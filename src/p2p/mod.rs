@@ -0,0 +1,3 @@
+pub mod message;
+pub mod breakers;
+pub mod compact_block;
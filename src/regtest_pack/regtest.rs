@@ -1,5 +1,4 @@
 use std::{
-    error::Error,
     process::Command,
     thread,
     time::Duration,
@@ -8,6 +7,8 @@ use std::{
 use indicatif::{ProgressBar, ProgressStyle};
 use serde_json;
 
+use crate::{Error, Result};
+
 const SPINNER_TICKS: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
 
 pub struct RegtestManager {
@@ -25,14 +26,16 @@ impl RegtestManager {
         }
     }
 
-    pub fn start(&self) -> Result<(), Box<dyn Error>> {
+    pub fn start(&self) -> Result<()> {
         let output = Command::new("bitcoind")
             .arg(&self.regtest_arg)
             .arg("-daemon")
             .output()?;
 
         if !output.status.success() {
-            return Err("Failed to start bitcoind regtest node. Is Bitcoin Core installed?".into());
+            return Err(Error::Node(
+                "Failed to start bitcoind regtest node. Is Bitcoin Core installed?".to_string(),
+            ));
         }
 
         let pb = self.create_spinner_progress_bar("Starting regtest node");
@@ -63,25 +66,27 @@ impl RegtestManager {
             println!("Regtest node started successfully ✅");
             Ok(())
         } else {
-            Err("Failed to start node within timeout period".into())
+            Err(Error::Node(
+                "Failed to start node within timeout period".to_string(),
+            ))
         }
     }
 
-    pub fn stop(&self) -> Result<(), Box<dyn Error>> {
+    pub fn stop(&self) -> Result<()> {
         let output = Command::new("bitcoin-cli")
             .arg(&self.regtest_arg)
             .arg("stop")
             .output()?;
 
         if !output.status.success() {
-            return Err("Failed to stop regtest node. Is it running?".into());
+            return Err(Error::Node("Failed to stop regtest node. Is it running?".to_string()));
         }
 
         println!("Regtest node stopped successfully");
         Ok(())
     }
 
-    pub fn handle_getblockbyheight(&self, target_height: u64) -> Result<(), Box<dyn Error>> {
+    pub fn handle_getblockbyheight(&self, target_height: u64) -> Result<()> {
         let current_height = self.get_current_height()?;
         println!("Current blockchain height: {current_height}");
 
@@ -98,7 +103,7 @@ impl RegtestManager {
         self.display_block(target_height)
     }
 
-    fn ensure_wallet(&self) -> Result<(), Box<dyn Error>> {
+    fn ensure_wallet(&self) -> Result<()> {
         let load_output = Command::new("bitcoin-cli")
             .arg(&self.regtest_arg)
             .args(["loadwallet", &self.wallet_name])
@@ -114,74 +119,76 @@ impl RegtestManager {
             .output()?;
 
         if !create_output.status.success() {
-            return Err(format!(
+            return Err(Error::Node(format!(
                 "Failed to create wallet: {}",
                 String::from_utf8_lossy(&create_output.stderr)
-            ).into());
+            )));
         }
 
         Ok(())
     }
 
-    fn generate_address(&self) -> Result<String, Box<dyn Error>> {
+    fn generate_address(&self) -> Result<String> {
         let output = Command::new("bitcoin-cli")
             .arg(&self.regtest_arg)
             .arg("getnewaddress")
             .output()?;
 
         if !output.status.success() {
-            return Err(format!(
+            return Err(Error::Node(format!(
                 "Address generation failed: {}",
                 String::from_utf8_lossy(&output.stderr)
-            ).into());
+            )));
         }
 
         Ok(String::from_utf8(output.stdout)?.trim().to_string())
     }
 
-    fn mine_blocks(&self, address: &str, count: u64) -> Result<(), Box<dyn Error>> {
+    fn mine_blocks(&self, address: &str, count: u64) -> Result<()> {
         let output = Command::new("bitcoin-cli")
             .arg(&self.regtest_arg)
             .args(["generatetoaddress", &count.to_string(), address])
             .output()?;
 
         if !output.status.success() {
-            return Err(format!(
+            return Err(Error::Node(format!(
                 "Mining failed: {}",
                 String::from_utf8_lossy(&output.stderr)
-            ).into());
+            )));
         }
 
         Ok(())
     }
 
-    fn get_current_height(&self) -> Result<u64, Box<dyn Error>> {
+    fn get_current_height(&self) -> Result<u64> {
         let output = Command::new("bitcoin-cli")
             .arg(&self.regtest_arg)
             .arg("getblockchaininfo")
             .output()?;
 
         if !output.status.success() {
-            return Err("Failed to get blockchain info".into());
+            return Err(Error::Node("Failed to get blockchain info".to_string()));
         }
 
         let json_str = String::from_utf8(output.stdout)?;
         let json: serde_json::Value = serde_json::from_str(&json_str)?;
         let height = json["blocks"]
             .as_u64()
-            .ok_or("Missing or invalid 'blocks' field in blockchain info")?;
+            .ok_or_else(|| {
+                Error::Node("Missing or invalid 'blocks' field in blockchain info".to_string())
+            })?;
 
         Ok(height)
     }
 
-    fn display_block(&self, height: u64) -> Result<(), Box<dyn Error>> {
+    fn display_block(&self, height: u64) -> Result<()> {
         let output = Command::new("bitcoin-cli")
             .arg(&self.regtest_arg)
             .args(["getblockhash", &height.to_string()])
             .output()?;
 
         if !output.status.success() {
-            return Err("Failed to get block hash for the target height".into());
+            return Err(Error::Node("Failed to get block hash for the target height".to_string()));
         }
 
         let block_hash = String::from_utf8(output.stdout)?.trim().to_string();
@@ -192,7 +199,7 @@ impl RegtestManager {
             .output()?;
 
         if !block_output.status.success() {
-            return Err("Failed to retrieve block data".into());
+            return Err(Error::Node("Failed to retrieve block data".to_string()));
         }
 
         let block_data = String::from_utf8(block_output.stdout)?;
@@ -0,0 +1,773 @@
+//! Serializable results of the generator commands.
+//!
+//! Reports render as the human-readable text printed by the CLI through
+//! `Display` and as JSON through `serde`, so test runners can consume the
+//! same data without scraping.
+
+use std::fmt;
+
+use std::collections::HashSet;
+
+use bitcoin::{block::Header, consensus::encode, Block, Transaction, Weight};
+use serde::Serialize;
+
+use crate::block::decoder::{BlockUtils, WITNESS_COMMITMENT_HEADER};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TransactionReport {
+    pub raw_hex: String,
+    pub txid: String,
+    pub wtxid: String,
+}
+
+impl From<&Transaction> for TransactionReport {
+    fn from(tx: &Transaction) -> Self {
+        Self {
+            raw_hex: encode::serialize_hex(tx),
+            txid: tx.compute_txid().to_string(),
+            wtxid: tx.compute_wtxid().to_string(),
+        }
+    }
+}
+
+/// Result of the `tx` command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TransactionsReport {
+    pub transactions: Vec<TransactionReport>,
+}
+
+impl fmt::Display for TransactionsReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let raw_tx: Vec<&str> = self.transactions.iter().map(|tx| tx.raw_hex.as_str()).collect();
+        let txid: Vec<&str> = self.transactions.iter().map(|tx| tx.txid.as_str()).collect();
+        write!(f, "Raw Transactions: {raw_tx:#?}\n---\nTXIDs: {txid:#?}")
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HeaderReport {
+    pub version: i32,
+    pub prev_blockhash: String,
+    pub merkle_root: String,
+    pub time: u32,
+    pub bits: u32,
+    pub nonce: u32,
+    pub block_hash: String,
+    pub raw_hex: String,
+}
+
+impl From<&Header> for HeaderReport {
+    fn from(header: &Header) -> Self {
+        Self {
+            version: header.version.to_consensus(),
+            prev_blockhash: header.prev_blockhash.to_string(),
+            merkle_root: header.merkle_root.to_string(),
+            time: header.time,
+            bits: header.bits.to_consensus(),
+            nonce: header.nonce,
+            block_hash: header.block_hash().to_string(),
+            raw_hex: encode::serialize_hex(header),
+        }
+    }
+}
+
+impl fmt::Display for HeaderReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Version: {}", self.version)?;
+        writeln!(f, "Previous Block: {}", self.prev_blockhash)?;
+        writeln!(f, "Merkle Root: {}", self.merkle_root)?;
+        writeln!(f, "Timestamp: {}", self.time)?;
+        writeln!(f, "Bits: 0x{:08x}", self.bits)?;
+        writeln!(f, "Nonce: {}", self.nonce)?;
+        write!(f, "Block Hash: {}", self.block_hash)
+    }
+}
+
+/// Result of the `block` command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BlockReport {
+    pub height: u32,
+    pub header: HeaderReport,
+    pub raw_hex: String,
+    pub transactions: Vec<TransactionReport>,
+}
+
+impl BlockReport {
+    pub fn new(block: &Block, height: u32) -> Self {
+        Self {
+            height,
+            header: HeaderReport::from(&block.header),
+            raw_hex: encode::serialize_hex(block),
+            transactions: block.txdata.iter().map(TransactionReport::from).collect(),
+        }
+    }
+}
+
+impl fmt::Display for BlockReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let raw_tx: Vec<&str> = self.transactions.iter().map(|tx| tx.raw_hex.as_str()).collect();
+        let tx_ids: Vec<&str> = self.transactions.iter().map(|tx| tx.txid.as_str()).collect();
+        write!(
+            f,
+            "Block Header:\n{}\n---\nBlock Height: {}\n---\nBlock Header encoded: {:#?}\n---\nRaw txs: {raw_tx:#?}\n---\nTxID: {tx_ids:#?}",
+            self.header, self.height, self.header.raw_hex
+        )
+    }
+}
+
+/// Result of the `chain` command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ChainReport {
+    pub blocks: Vec<BlockReport>,
+}
+
+impl fmt::Display for ChainReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, block) in self.blocks.iter().enumerate() {
+            if index > 0 {
+                writeln!(f, "---")?;
+            }
+            writeln!(f, "Block Height: {}", block.height)?;
+            writeln!(f, "Block Hash: {}", block.header.block_hash)?;
+            writeln!(f, "Previous Block: {}", block.header.prev_blockhash)?;
+            writeln!(f, "Raw block: {}", block.raw_hex)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct InputReport {
+    pub txid: String,
+    pub vout: u32,
+    pub script_sig: String,
+    pub sequence: u32,
+    pub witness: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct OutputReport {
+    pub value: u64,
+    pub script_pubkey: String,
+}
+
+/// Result of the `decode-transaction` command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DecodedTransactionReport {
+    pub txid: String,
+    pub wtxid: String,
+    pub version: i32,
+    pub lock_time: u32,
+    pub inputs: Vec<InputReport>,
+    pub outputs: Vec<OutputReport>,
+}
+
+impl From<&Transaction> for DecodedTransactionReport {
+    fn from(tx: &Transaction) -> Self {
+        Self {
+            txid: tx.compute_txid().to_string(),
+            wtxid: tx.compute_wtxid().to_string(),
+            version: tx.version.0,
+            lock_time: tx.lock_time.to_consensus_u32(),
+            inputs: tx
+                .input
+                .iter()
+                .map(|input| InputReport {
+                    txid: input.previous_output.txid.to_string(),
+                    vout: input.previous_output.vout,
+                    script_sig: input.script_sig.to_hex_string(),
+                    sequence: input.sequence.0,
+                    witness: input.witness.iter().map(hex::encode).collect(),
+                })
+                .collect(),
+            outputs: tx
+                .output
+                .iter()
+                .map(|output| OutputReport {
+                    value: output.value.to_sat(),
+                    script_pubkey: output.script_pubkey.to_hex_string(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl fmt::Display for DecodedTransactionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "TXID: {}", self.txid)?;
+        writeln!(f, "WTXID: {}", self.wtxid)?;
+        writeln!(f, "Version: {}", self.version)?;
+        writeln!(f, "Locktime: {}", self.lock_time)?;
+        writeln!(f, "Input count: {}", self.inputs.len())?;
+        for (index, input) in self.inputs.iter().enumerate() {
+            writeln!(f, "  [{index}] {}:{}", input.txid, input.vout)?;
+            writeln!(f, "      Script Sig: {}", input.script_sig)?;
+            writeln!(f, "      Sequence: 0x{:08x}", input.sequence)?;
+            writeln!(f, "      Witness: {:?}", input.witness)?;
+        }
+        write!(f, "Output count: {}", self.outputs.len())?;
+        for (index, output) in self.outputs.iter().enumerate() {
+            write!(f, "\n  [{index}] {} sat", output.value)?;
+            write!(f, "\n      Script PubKey: {}", output.script_pubkey)?;
+        }
+        Ok(())
+    }
+}
+
+/// Result of the `decode-full-block` command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct DecodedBlockReport {
+    pub header: HeaderReport,
+    /// Height pushed by the coinbase as required by BIP34, if any.
+    pub coinbase_height: Option<u64>,
+    /// Commitment found in the last matching coinbase output, if any.
+    pub witness_commitment: Option<String>,
+    pub computed_merkle_root: Option<String>,
+    pub weight: u64,
+    pub size: usize,
+    pub stripped_size: usize,
+    pub vsize: u64,
+    /// Legacy sigops of every script_sig and script_pubkey, the count Bitcoin
+    /// Core checks without looking up the spent outputs.
+    pub sigop_count: usize,
+    pub transactions: Vec<DecodedTransactionReport>,
+    /// Consensus rules the block breaks, as Bitcoin Core reject reasons.
+    pub issues: Vec<String>,
+}
+
+const MAX_BLOCK_SIGOPS_COST: usize = 80_000;
+const WITNESS_SCALE_FACTOR: usize = 4;
+
+impl From<&Block> for DecodedBlockReport {
+    fn from(block: &Block) -> Self {
+        let witness_commitment = block.txdata.first().and_then(|coinbase| {
+            coinbase
+                .output
+                .iter()
+                .rev()
+                .map(|output| output.script_pubkey.as_bytes())
+                .find(|script| script.len() >= 38 && script.starts_with(&WITNESS_COMMITMENT_HEADER))
+                .map(|script| hex::encode(&script[6..38]))
+        });
+        let sigop_count = block
+            .txdata
+            .iter()
+            .map(|tx| {
+                let inputs: usize = tx
+                    .input
+                    .iter()
+                    .map(|input| input.script_sig.count_sigops_legacy())
+                    .sum();
+                let outputs: usize = tx
+                    .output
+                    .iter()
+                    .map(|output| output.script_pubkey.count_sigops_legacy())
+                    .sum();
+                inputs + outputs
+            })
+            .sum();
+        let weight = block.weight();
+
+        Self {
+            header: HeaderReport::from(&block.header),
+            coinbase_height: BlockUtils::bip34_height(block),
+            computed_merkle_root: block.compute_merkle_root().map(|root| root.to_string()),
+            weight: weight.to_wu(),
+            size: block.total_size(),
+            stripped_size: encode::serialize(&block.header).len()
+                + encode::VarInt(block.txdata.len() as u64).size()
+                + block.txdata.iter().map(Transaction::base_size).sum::<usize>(),
+            vsize: weight.to_vbytes_ceil(),
+            sigop_count,
+            transactions: block.txdata.iter().map(DecodedTransactionReport::from).collect(),
+            issues: block_issues(block, witness_commitment.is_some(), sigop_count, weight),
+            witness_commitment,
+        }
+    }
+}
+
+fn block_issues(
+    block: &Block,
+    has_witness_commitment: bool,
+    sigop_count: usize,
+    weight: Weight,
+) -> Vec<String> {
+    let mut issues = vec![];
+    let mut issue = |reason: &str, description: &str| issues.push(format!("{reason}: {description}"));
+
+    if block.header.validate_pow(block.header.target()).is_err() {
+        issue("high-hash", "block hash does not meet the target in bits");
+    }
+    if !block.txdata.first().is_some_and(Transaction::is_coinbase) {
+        issue("bad-cb-missing", "first transaction is not a coinbase");
+    }
+    if let Some(coinbase) = block.txdata.first().filter(|tx| tx.is_coinbase()) {
+        if !(2..=100).contains(&coinbase.input[0].script_sig.len()) {
+            issue("bad-cb-length", "coinbase script_sig is not 2 to 100 bytes long");
+        }
+    }
+    if block.txdata.iter().skip(1).any(Transaction::is_coinbase) {
+        issue("bad-cb-multiple", "more than one coinbase transaction");
+    }
+    if !block.check_merkle_root() {
+        issue("bad-txnmrklroot", "claimed merkle root does not match the transactions");
+    }
+    let mut txids = HashSet::new();
+    if !block.txdata.iter().all(|tx| txids.insert(tx.compute_txid())) {
+        issue("bad-txns-duplicate", "the same transaction appears more than once");
+    }
+    if block.header.version.to_consensus() >= 2 && BlockUtils::bip34_height(block).is_none() {
+        issue("bad-cb-height", "coinbase does not start with a BIP34 height push");
+    }
+    let has_witness = block
+        .txdata
+        .iter()
+        .any(|tx| tx.input.iter().any(|input| !input.witness.is_empty()));
+    if has_witness_commitment {
+        let witness_nonce = block.txdata.first().map(|coinbase| &coinbase.input[0].witness);
+        if !witness_nonce.is_some_and(|witness| witness.len() == 1 && witness[0].len() == 32) {
+            issue("bad-witness-nonce-size", "coinbase witness is not a single 32-byte value");
+        } else if !block.check_witness_commitment() {
+            issue("bad-witness-merkle-match", "witness commitment does not match the witnesses");
+        }
+    } else if has_witness {
+        issue("unexpected-witness", "witness data without a witness commitment");
+    }
+    if weight > Weight::MAX_BLOCK {
+        issue("bad-blk-weight", "block weight exceeds 4,000,000 WU");
+    }
+    if sigop_count * WITNESS_SCALE_FACTOR > MAX_BLOCK_SIGOPS_COST {
+        issue("bad-blk-sigops", "too many signature operations");
+    }
+
+    issues
+}
+
+impl fmt::Display for DecodedBlockReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let or_none = |value: &Option<String>| value.clone().unwrap_or_else(|| "none".to_string());
+
+        writeln!(f, "Block Header:\n{}", indent(&self.header))?;
+        match self.coinbase_height {
+            Some(height) => writeln!(f, "Coinbase Height (BIP34): {height}")?,
+            None => writeln!(f, "Coinbase Height (BIP34): none")?,
+        }
+        writeln!(f, "Claimed Merkle Root: {}", self.header.merkle_root)?;
+        writeln!(f, "Computed Merkle Root: {}", or_none(&self.computed_merkle_root))?;
+        writeln!(f, "Witness Commitment: {}", or_none(&self.witness_commitment))?;
+        writeln!(f, "Weight: {} WU", self.weight)?;
+        writeln!(f, "Size: {} bytes (stripped: {})", self.size, self.stripped_size)?;
+        writeln!(f, "Virtual Size: {} vB", self.vsize)?;
+        writeln!(f, "Sigops (legacy): {}", self.sigop_count)?;
+        if self.issues.is_empty() {
+            writeln!(f, "Issues: none")?;
+        } else {
+            writeln!(f, "Issues:")?;
+            for issue in &self.issues {
+                writeln!(f, "  - {issue}")?;
+            }
+        }
+        write!(f, "Transactions: {}", self.transactions.len())?;
+        for (index, tx) in self.transactions.iter().enumerate() {
+            write!(f, "\n[{index}]\n{}", indent(tx))?;
+        }
+        Ok(())
+    }
+}
+
+/// A single breakage applied by a breaker.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MutationReport {
+    pub name: String,
+    pub description: String,
+    /// Bitcoin Core reject reason the mutation should trigger, if any.
+    pub expected_failure: Option<String>,
+}
+
+impl MutationReport {
+    pub fn new(name: impl fmt::Debug, description: &str, expected_failure: Option<&str>) -> Self {
+        Self {
+            name: format!("{name:?}"),
+            description: description.to_string(),
+            expected_failure: expected_failure.map(str::to_string),
+        }
+    }
+}
+
+impl fmt::Display for MutationReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "  - {}", self.description)?;
+        if let Some(reason) = &self.expected_failure {
+            write!(f, " (expected: {reason})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Result of the `break-transaction` command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BreakTransactionReport {
+    pub mutations: Vec<MutationReport>,
+    pub original_hex: String,
+    pub broken: TransactionReport,
+}
+
+impl fmt::Display for BreakTransactionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Invalidating the following fields:")?;
+        for mutation in &self.mutations {
+            writeln!(f, "{mutation}")?;
+        }
+        writeln!(f, "\nInputed Transaction:\n{}\n", self.original_hex)?;
+        writeln!(f, "Invalidated Transaction:")?;
+        writeln!(f, "  TXID: {}", self.broken.txid)?;
+        writeln!(f, "  WTXID: {}", self.broken.wtxid)?;
+        write!(f, "Invalidated Raw Transaction:\n{}\n", self.broken.raw_hex)
+    }
+}
+
+/// Result of the `break-block` command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BreakBlockReport {
+    pub mutations: Vec<MutationReport>,
+    /// Processing options that shaped the mutations, e.g. a version override.
+    pub settings: Vec<String>,
+    pub original_header: HeaderReport,
+    pub broken_header: HeaderReport,
+    /// Whole broken block, when a full block rather than a header was given.
+    pub broken_block_hex: Option<String>,
+}
+
+impl fmt::Display for BreakBlockReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Breaking the following block fields:")?;
+        for mutation in &self.mutations {
+            writeln!(f, "{mutation}")?;
+        }
+        for setting in &self.settings {
+            writeln!(f, "  - {setting}")?;
+        }
+        writeln!(f, "\nOriginal Block Header:\n{}", indent(&self.original_header))?;
+        writeln!(f, "\nBroken Block Header:\n{}", indent(&self.broken_header))?;
+        writeln!(
+            f,
+            "\nBroken Block Header (Hex):\n{}",
+            self.broken_header.raw_hex
+        )?;
+        if let Some(block_hex) = &self.broken_block_hex {
+            writeln!(f, "\nBroken Block (Hex):\n{block_hex}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Result of the `forge-merkle-proof` command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MerkleProofReport {
+    /// Txid the proof claims is in the block, though no transaction has it.
+    pub forged_txid: String,
+    /// Position of the 64-byte transaction the forged txid is half of.
+    pub inner_tx_index: usize,
+    pub inner_txid: String,
+    pub merkle_root: String,
+    /// Serialized `merkleblock` message payload.
+    pub merkle_block_hex: String,
+}
+
+impl fmt::Display for MerkleProofReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Forged TXID: {}", self.forged_txid)?;
+        writeln!(
+            f,
+            "Hidden Behind: transaction {} ({})",
+            self.inner_tx_index, self.inner_txid
+        )?;
+        writeln!(f, "Merkle Root: {}", self.merkle_root)?;
+        write!(f, "Merkle Block (Hex):\n{}", self.merkle_block_hex)
+    }
+}
+
+/// Result of the `retarget` command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RetargetReport {
+    pub network: String,
+    pub last_bits: u32,
+    /// Seconds between the first and last block of the window.
+    pub timespan: i64,
+    /// Timespan after clamping to between a quarter and four times two weeks.
+    pub clamped_timespan: u64,
+    pub required_bits: u32,
+    /// Bits one compact unit off the required ones, rejected as bad-diffbits.
+    pub one_unit_easier: u32,
+    pub one_unit_harder: u32,
+}
+
+impl fmt::Display for RetargetReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Network: {}", self.network)?;
+        writeln!(f, "Last Bits: 0x{:08x}", self.last_bits)?;
+        writeln!(f, "Timespan: {}s (clamped to {}s)", self.timespan, self.clamped_timespan)?;
+        writeln!(f, "Required Bits: 0x{:08x}", self.required_bits)?;
+        writeln!(f, "One Unit Easier: 0x{:08x}", self.one_unit_easier)?;
+        write!(f, "One Unit Harder: 0x{:08x}", self.one_unit_harder)
+    }
+}
+
+/// Block of the `timestamps` command set up for one scenario.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TimestampScenarioReport {
+    pub scenario: MutationReport,
+    pub time: u32,
+    /// Lock time of the extra transaction of the lock time scenarios.
+    pub lock_time: Option<u32>,
+    pub block: BlockReport,
+}
+
+/// Result of the `timestamps` command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TimestampScenariosReport {
+    /// Adjusted time of the node the future drift scenarios are set for.
+    pub now: u32,
+    pub median_time_past: u32,
+    /// Blocks every scenario block builds on, from the regtest genesis.
+    pub chain: Vec<BlockReport>,
+    pub scenarios: Vec<TimestampScenarioReport>,
+}
+
+impl fmt::Display for TimestampScenariosReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Adjusted Time: {}", self.now)?;
+        writeln!(f, "Median-Time-Past: {}", self.median_time_past)?;
+        writeln!(f, "Chain:")?;
+        for block in &self.chain {
+            writeln!(f, "  {} {}", block.height, block.raw_hex)?;
+        }
+        for scenario in &self.scenarios {
+            writeln!(f, "---\n{}", scenario.scenario)?;
+            writeln!(f, "  Timestamp: {}", scenario.time)?;
+            if let Some(lock_time) = scenario.lock_time {
+                writeln!(f, "  Lock Time: {lock_time}")?;
+            }
+            writeln!(f, "  Raw block: {}", scenario.block.raw_hex)?;
+        }
+        Ok(())
+    }
+}
+
+/// Height from which a deployment is in a new state.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct StateTransitionReport {
+    pub height: u32,
+    pub state: String,
+}
+
+/// Result of the `signal` command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VersionBitsReport {
+    pub bit: u8,
+    pub period: u32,
+    pub threshold: u32,
+    /// Signalling blocks per period; the rest of the period does not signal.
+    pub signalling_per_period: u32,
+    pub transitions: Vec<StateTransitionReport>,
+    pub chain: ChainReport,
+}
+
+impl fmt::Display for VersionBitsReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Deployment Bit: {}", self.bit)?;
+        writeln!(
+            f,
+            "Signalling: {} per period of {} (threshold {})",
+            self.signalling_per_period, self.period, self.threshold
+        )?;
+        writeln!(f, "State Transitions:")?;
+        for transition in &self.transitions {
+            writeln!(f, "  {}: {}", transition.height, transition.state)?;
+        }
+        write!(f, "---\n{}", self.chain)
+    }
+}
+
+/// Header broken inside a `headers` chain.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BrokenHeaderReport {
+    /// Position of the header in the chain, from 0.
+    pub index: usize,
+    pub hash: String,
+    pub mutation: MutationReport,
+}
+
+/// Result of the `headers` command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HeaderChainReport {
+    pub count: usize,
+    /// `headers` for a P2P message payload, `flat` for bare 80-byte headers.
+    pub encoding: String,
+    pub tip_hash: String,
+    pub broken: Option<BrokenHeaderReport>,
+    pub payload_hex: String,
+}
+
+impl fmt::Display for HeaderChainReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Headers: {} ({} encoding)", self.count, self.encoding)?;
+        writeln!(f, "Tip Hash: {}", self.tip_hash)?;
+        if let Some(broken) = &self.broken {
+            writeln!(f, "Broken Header: {} ({})", broken.index, broken.hash)?;
+            writeln!(f, "{}", broken.mutation)?;
+        }
+        write!(f, "Payload (Hex):\n{}", self.payload_hex)
+    }
+}
+
+/// Result of the `wrap` command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct WireMessageReport {
+    pub network: String,
+    pub magic: String,
+    /// Command in the envelope, after any breaker ran.
+    pub command: String,
+    /// Payload length the envelope declares.
+    pub length: u32,
+    pub checksum: String,
+    pub size: usize,
+    pub mutation: Option<MutationReport>,
+    pub message_hex: String,
+}
+
+impl fmt::Display for WireMessageReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Network: {} (magic {})", self.network, self.magic)?;
+        writeln!(f, "Command: {}", self.command)?;
+        writeln!(f, "Length: {} (message is {} bytes)", self.length, self.size)?;
+        writeln!(f, "Checksum: {}", self.checksum)?;
+        if let Some(mutation) = &self.mutation {
+            writeln!(f, "Envelope Breaker:\n{mutation}")?;
+        }
+        write!(f, "Message (Hex):\n{}", self.message_hex)
+    }
+}
+
+/// Result of the `compact-block` command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CompactBlockReport {
+    pub block_hash: String,
+    pub nonce: u64,
+    pub short_ids: Vec<String>,
+    /// Block positions of the prefilled transactions, the coinbase first.
+    pub prefilled: Vec<usize>,
+    /// Block positions the `getblocktxn` asks for.
+    pub requested: Vec<u64>,
+    pub mutation: Option<MutationReport>,
+    pub block_hex: String,
+    /// `cmpctblock`, `getblocktxn` and `blocktxn` message payloads.
+    pub cmpctblock_hex: String,
+    pub getblocktxn_hex: String,
+    pub blocktxn_hex: String,
+}
+
+impl fmt::Display for CompactBlockReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Block Hash: {}", self.block_hash)?;
+        writeln!(f, "Nonce: {}", self.nonce)?;
+        writeln!(f, "Short IDs: {}", self.short_ids.join(", "))?;
+        writeln!(f, "Prefilled: {:?}", self.prefilled)?;
+        writeln!(f, "Requested: {:?}", self.requested)?;
+        if let Some(mutation) = &self.mutation {
+            writeln!(f, "Compact Block Breaker:\n{mutation}")?;
+        }
+        writeln!(f, "Block (Hex):\n{}", self.block_hex)?;
+        writeln!(f, "cmpctblock (Hex):\n{}", self.cmpctblock_hex)?;
+        writeln!(f, "getblocktxn (Hex):\n{}", self.getblocktxn_hex)?;
+        write!(f, "blocktxn (Hex):\n{}", self.blocktxn_hex)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TapLeafReport {
+    pub script: String,
+    pub leaf_hash: String,
+    /// Signatures a spend through the leaf carries.
+    pub threshold: usize,
+}
+
+/// Result of the `tapscript` command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct TapscriptReport {
+    pub internal_key: String,
+    pub output_key: String,
+    pub merkle_root: String,
+    pub leaves: Vec<TapLeafReport>,
+    pub spent_leaf: usize,
+    pub control_block: String,
+    /// Output the transaction spends.
+    pub prevout_script: String,
+    pub prevout_value: u64,
+    pub mutation: Option<MutationReport>,
+    pub transaction: TransactionReport,
+}
+
+impl fmt::Display for TapscriptReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Internal Key: {}", self.internal_key)?;
+        writeln!(f, "Output Key: {}", self.output_key)?;
+        writeln!(f, "Merkle Root: {}", self.merkle_root)?;
+        writeln!(f, "Leaves:")?;
+        for (index, leaf) in self.leaves.iter().enumerate() {
+            let spent = if index == self.spent_leaf { " (spent)" } else { "" };
+            writeln!(f, "  {index}: {}{spent}", leaf.script)?;
+            writeln!(f, "     leaf hash {}", leaf.leaf_hash)?;
+        }
+        writeln!(f, "Control Block: {}", self.control_block)?;
+        writeln!(f, "Prevout: {} sat to {}", self.prevout_value, self.prevout_script)?;
+        if let Some(mutation) = &self.mutation {
+            writeln!(f, "Taproot Breaker:\n{mutation}")?;
+        }
+        writeln!(f, "TXID: {}", self.transaction.txid)?;
+        write!(f, "Raw Transaction:\n{}", self.transaction.raw_hex)
+    }
+}
+
+/// Result of the `multisig` command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct MultisigReport {
+    pub wrapping: String,
+    pub threshold: usize,
+    pub pubkeys: Vec<String>,
+    /// The OP_CHECKMULTISIG script, in ASM.
+    pub script: String,
+    /// Script the P2SH wrappings reveal in the script_sig.
+    pub redeem_script: Option<String>,
+    /// Output the transaction spends.
+    pub prevout_script: String,
+    pub prevout_value: u64,
+    pub transaction: TransactionReport,
+}
+
+impl fmt::Display for MultisigReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Wrapping: {}", self.wrapping)?;
+        writeln!(f, "Threshold: {} of {}", self.threshold, self.pubkeys.len())?;
+        writeln!(f, "Public Keys:")?;
+        for pubkey in &self.pubkeys {
+            writeln!(f, "  {pubkey}")?;
+        }
+        writeln!(f, "Script: {}", self.script)?;
+        if let Some(redeem_script) = &self.redeem_script {
+            writeln!(f, "Redeem Script: {redeem_script}")?;
+        }
+        writeln!(f, "Prevout: {} sat to {}", self.prevout_value, self.prevout_script)?;
+        writeln!(f, "TXID: {}", self.transaction.txid)?;
+        write!(f, "Raw Transaction:\n{}", self.transaction.raw_hex)
+    }
+}
+
+fn indent(value: &impl fmt::Display) -> String {
+    value
+        .to_string()
+        .lines()
+        .map(|line| format!("  {line}"))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
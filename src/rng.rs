@@ -0,0 +1,54 @@
+//! Shared random number generator for every generator and breaker.
+//!
+//! All randomness is drawn from a per-thread `StdRng`, so seeding it once
+//! (directly with `set_seed` or through the `seed` field of `TxParams`,
+//! `BlockParams`, `HeaderParams` or `ProcessingConfig`) makes a whole run
+//! reproducible.
+
+use std::cell::RefCell;
+
+use bitcoin::{secp256k1::SecretKey, NetworkKind, PrivateKey};
+use rand::{rngs::StdRng, Rng, RngCore, SeedableRng};
+
+thread_local! {
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_os_rng());
+}
+
+/// Reseed the generator of the current thread.
+pub fn set_seed(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+/// Handle to the current thread's generator.
+///
+/// Handles are free to create; they all draw from the same seeded stream.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MisfitRng;
+
+pub fn rng() -> MisfitRng {
+    MisfitRng
+}
+
+impl RngCore for MisfitRng {
+    fn next_u32(&mut self) -> u32 {
+        RNG.with(|rng| rng.borrow_mut().next_u32())
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        RNG.with(|rng| rng.borrow_mut().next_u64())
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        RNG.with(|rng| rng.borrow_mut().fill_bytes(dest))
+    }
+}
+
+/// Private key drawn from the shared generator, unlike
+/// `PrivateKey::generate` which always uses the OS entropy source.
+pub fn private_key() -> PrivateKey {
+    loop {
+        if let Ok(secret_key) = SecretKey::from_slice(&rng().random::<[u8; 32]>()) {
+            return PrivateKey::new(secret_key, NetworkKind::Main);
+        }
+    }
+}
@@ -1,11 +1,12 @@
 
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
-    pub use crate::api::{Generator};
+    pub use misfit_core::api::Generator;
 
     #[test]
     fn test_generate_single_transaction() {
-        let result = Generator::transaction(1);
+        let result = Generator::transaction(1, None).to_string();
         assert!(result.contains("Raw Transactions:"));
         assert!(result.contains("TXIDs:"));
         assert!(result.contains("---"));
@@ -20,7 +21,7 @@ mod tests {
     #[test]
     fn test_generate_multiple_transactions() {
         let tx_count = 3;
-        let result = Generator::transaction(tx_count);
+        let result = Generator::transaction(tx_count, None).to_string();
         
         assert!(result.contains("Raw Transactions:"));
         assert!(result.contains("TXIDs:"));
@@ -35,7 +36,7 @@ mod tests {
 
     #[test]
     fn test_generate_zero_transactions() {
-        let result = Generator::transaction(0);
+        let result = Generator::transaction(0, None).to_string();
         
         assert!(result.contains("Raw Transactions:"));
         assert!(result.contains("TXIDs:"));
@@ -43,7 +44,7 @@ mod tests {
 
 #[test]
 fn test_generate_one_block_with_one_transaction() {
-    let result = Generator::block(1);
+    let result = Generator::block(1, None).to_string();
     let sections: Vec<&str> = result.split("\n---\n").collect();
     assert_eq!(sections.len(), 5); 
     assert!(result.contains("Header"));
@@ -53,7 +54,7 @@ fn test_generate_one_block_with_one_transaction() {
 
 #[test]
 fn generate_zero_tx_block() {
-    let result = Generator::block(0);
+    let result = Generator::block(0, None).to_string();
     let sections: Vec<&str> = result.split("\n---\n").collect();
     assert_eq!(sections.len(), 5); 
     assert!(result.contains("Header"));
@@ -64,7 +65,7 @@ fn generate_zero_tx_block() {
 #[test]
 fn test_generate_block_with_multiple_transactions() {
     let tx_count = 10;
-    let result = Generator::block(tx_count);
+    let result = Generator::block(tx_count, None).to_string();
     assert!(result.contains("Raw txs:"));
     assert!(result.contains("TxID:"));
     let sections: Vec<&str> = result.split("\n---\n").collect();
@@ -78,7 +79,7 @@ fn test_generate_block_with_multiple_transactions() {
             "--amount".to_string(),
         ];
         
-        let result = Generator::parse_cli_flags_to_invalidation_flags(flags);
+        let result = Generator::parse_cli_flags_to_invalidation_flags(flags).unwrap();
         
         assert_eq!(result.len(), 3);
         assert!(result.contains(&misfit_core::transaction::breakers::flags::InvalidationFlag::Version));
@@ -90,7 +91,7 @@ fn test_generate_block_with_multiple_transactions() {
     fn test_parse_cli_flags_with_all_flag() {
         let flags = vec!["--all".to_string()];
         
-        let result = Generator::parse_cli_flags_to_invalidation_flags(flags);
+        let result = Generator::parse_cli_flags_to_invalidation_flags(flags).unwrap();
         
         assert_eq!(result.len(), 1);
         assert!(result.contains(&misfit_core::transaction::breakers::flags::InvalidationFlag::All));
@@ -106,9 +107,10 @@ fn test_generate_block_with_multiple_transactions() {
         
         let result = Generator::parse_cli_flags_to_invalidation_flags(flags);
         
-        assert_eq!(result.len(), 2);
-        assert!(result.contains(&misfit_core::transaction::breakers::flags::InvalidationFlag::Version));
-        assert!(result.contains(&misfit_core::transaction::breakers::flags::InvalidationFlag::InputTxid));
+        assert!(matches!(
+            result,
+            Err(misfit_core::Error::InvalidFlag(flag)) if flag == "--unknown-flag"
+        ));
     }
 
     #[test]
@@ -119,7 +121,7 @@ fn test_generate_block_with_multiple_transactions() {
             "--nonce".to_string(),
         ];
         
-        let result = Generator::parse_cli_flags_to_block_fields(flags);
+        let result = Generator::parse_cli_flags_to_block_fields(flags).unwrap();
         
         assert_eq!(result.len(), 3);
         assert!(result.contains(&misfit_core::block::breakers::block::BlockField::Version));
@@ -136,11 +138,11 @@ fn test_generate_block_with_multiple_transactions() {
         ];
         let fields = vec![misfit_core::block::breakers::block::BlockField::Version];
         
-        let result = Generator::parse_cli_config_to_processing_config(cli_config, fields);
+        let result = Generator::parse_cli_config_to_processing_config(cli_config, fields).unwrap();
         
         assert_eq!(result.version_override, Some(2));
         assert_eq!(result.timestamp_offset, Some(3600));
-        assert_eq!(result.randomize_hashes, false);
+        assert!(!result.randomize_hashes);
         assert_eq!(result.fields_to_modify.len(), 1);
     }
 
@@ -154,9 +156,10 @@ fn test_generate_block_with_multiple_transactions() {
         
         let result = Generator::parse_cli_config_to_processing_config(cli_config, fields);
         
-        assert_eq!(result.version_override, None);
-        assert_eq!(result.timestamp_offset, None);
-        assert_eq!(result.randomize_hashes, true); 
+        assert!(matches!(
+            result,
+            Err(misfit_core::Error::InvalidConfig(option)) if option == "--version-override=invalid"
+        ));
     }
 
     #[test]
@@ -188,12 +191,13 @@ fn test_generate_block_with_multiple_transactions() {
     }
     #[test]
     fn test_break_transaction_with_valid_data() {
-        let tx_result = Generator::transaction(1);
+        let raw_tx = Generator::transaction(1, None).transactions[0].raw_hex.clone();
 
         let cli_flags = vec!["--version".to_string()];
-        let result = Generator::break_transaction(tx_result.to_string(), cli_flags);
-        
-        assert!(result != tx_result);
+        let result = Generator::break_transaction(raw_tx.clone(), cli_flags, None).unwrap();
+
+        assert_eq!(result.original_hex, raw_tx);
+        assert_ne!(result.broken.raw_hex, raw_tx);
     }
 
     #[test]
@@ -201,7 +205,9 @@ fn test_generate_block_with_multiple_transactions() {
         let raw_tx = "4f6e3b7201e8370e51a135fb8e468e8188ea580b5a6c74a92b5cab5af2785bd307297be9a808e47956006b6b5dbe0118a478e14edc0b651976a9148840c86761418aa78e7667e8e7e427c4e955989588ac59500852".to_string();
         let cli_flags = vec![];
         
-        let result = Generator::break_transaction(raw_tx, cli_flags);
+        let result = Generator::break_transaction(raw_tx, cli_flags, None)
+            .unwrap_err()
+            .to_string();
         
         assert!(result.contains("No invalidation flags specified"));
         assert!(result.contains("Use 'help' for usage information"));
@@ -213,7 +219,9 @@ fn test_generate_block_with_multiple_transactions() {
         let cli_flags = vec![];
         let cli_config = vec![];
         
-        let result = Generator::break_block(block_header, cli_flags, cli_config);
+        let result = Generator::break_block(block_header, cli_flags, cli_config)
+            .unwrap_err()
+            .to_string();
         
         assert!(result.contains("No invalidation flags specified"));
         assert!(result.contains("Use 'help' for usage information"));
@@ -223,14 +231,14 @@ fn test_generate_block_with_multiple_transactions() {
     #[test]
     fn test_empty_cli_flags() {
         let flags: Vec<String> = vec![];
-        let result = Generator::parse_cli_flags_to_invalidation_flags(flags);
+        let result = Generator::parse_cli_flags_to_invalidation_flags(flags).unwrap();
         assert!(result.is_empty());
     }
 
     #[test]
     fn test_empty_block_flags() {
         let flags: Vec<String> = vec![];
-        let result = Generator::parse_cli_flags_to_block_fields(flags);
+        let result = Generator::parse_cli_flags_to_block_fields(flags).unwrap();
         assert!(result.is_empty());
     }
 
@@ -239,11 +247,11 @@ fn test_generate_block_with_multiple_transactions() {
         let cli_config: Vec<String> = vec![];
         let fields = vec![];
         
-        let result = Generator::parse_cli_config_to_processing_config(cli_config, fields);
+        let result = Generator::parse_cli_config_to_processing_config(cli_config, fields).unwrap();
         
         assert_eq!(result.version_override, None);
         assert_eq!(result.timestamp_offset, None);
-        assert_eq!(result.randomize_hashes, true);
+        assert!(result.randomize_hashes);
         assert!(result.fields_to_modify.is_empty());
     }
 
@@ -261,7 +269,7 @@ fn test_generate_block_with_multiple_transactions() {
             "--locktime".to_string(),
         ];
         
-        let result = Generator::parse_cli_flags_to_invalidation_flags(flags);
+        let result = Generator::parse_cli_flags_to_invalidation_flags(flags).unwrap();
         
         assert_eq!(result.len(), 9);
         assert!(result.contains(&misfit_core::transaction::breakers::flags::InvalidationFlag::Version));
@@ -286,7 +294,7 @@ fn test_generate_block_with_multiple_transactions() {
             "--nonce".to_string(),
         ];
         
-        let result = Generator::parse_cli_flags_to_block_fields(flags);
+        let result = Generator::parse_cli_flags_to_block_fields(flags).unwrap();
         
         assert_eq!(result.len(), 6);
         assert!(result.contains(&misfit_core::block::breakers::block::BlockField::Version));
@@ -326,7 +334,7 @@ fn test_generate_block_with_multiple_transactions() {
             input: Some(input_params),
             ..Default::default()
         };
-        let tx = <bitcoin::Transaction as RandomTransacion>::random(tx_params);
+        let tx = <bitcoin::Transaction as RandomTransacion>::random(tx_params).unwrap();
 
         let witness = &tx.input[0].witness;
 
@@ -357,12 +365,16 @@ fn test_block_segwit_bip141_commitment() {
             }),
             ..Default::default()
         }
-    );
+    ).unwrap();
 
     let (block, _height) = GenerateBlock::valid_random(BlockParams {
         header: None,
         txs: Some(vec![tx]),
         height: None,
+        target: None,
+        private_key: None,
+        seed: None,
+        ..Default::default()
     });
 
     let coinbase = &block.txdata[0];
@@ -379,4 +391,1518 @@ fn test_block_segwit_bip141_commitment() {
     assert!(found_commitment, "Output de compromisso wTXID não encontrado na coinbase");
     }
 
+    fn assert_input_verifies(tx: &bitcoin::Transaction, index: usize, prevouts: &[bitcoin::TxOut]) {
+        use bitcoin::hashes::Hash;
+        use bitcoin::secp256k1::{schnorr, Message, Secp256k1, XOnlyPublicKey};
+        use bitcoin::sighash::{EcdsaSighashType, Prevouts, SighashCache, TapSighashType};
+
+        let secp = Secp256k1::new();
+        let prevout = &prevouts[index];
+        let script_pubkey = &prevout.script_pubkey;
+        let mut cache = SighashCache::new(tx);
+
+        if script_pubkey.is_p2tr() {
+            let sig = schnorr::Signature::from_slice(&tx.input[index].witness[0]).unwrap();
+            let key = XOnlyPublicKey::from_slice(&script_pubkey.as_bytes()[2..]).unwrap();
+            let sighash = cache
+                .taproot_key_spend_signature_hash(index, &Prevouts::All(prevouts), TapSighashType::Default)
+                .unwrap();
+            let msg = Message::from_digest_slice(&sighash[..]).unwrap();
+            assert!(secp.verify_schnorr(&sig, &msg, &key).is_ok(), "input {index} taproot signature");
+            return;
+        }
+
+        let (sig, pubkey, sighash) = if script_pubkey.is_p2wpkh() {
+            let witness = &tx.input[index].witness;
+            let sighash = cache
+                .p2wpkh_signature_hash(index, script_pubkey, prevout.value, EcdsaSighashType::All)
+                .unwrap();
+            (witness[0].to_vec(), witness[1].to_vec(), sighash.to_byte_array())
+        } else {
+            assert!(script_pubkey.is_p2pkh(), "unexpected prevout type for input {index}");
+            let pushes: Vec<Vec<u8>> = tx.input[index]
+                .script_sig
+                .instructions()
+                .map(|ins| ins.unwrap().push_bytes().unwrap().as_bytes().to_vec())
+                .collect();
+            let sighash = cache
+                .legacy_signature_hash(index, script_pubkey, EcdsaSighashType::All.to_u32())
+                .unwrap();
+            (pushes[0].clone(), pushes[1].clone(), sighash.to_byte_array())
+        };
+
+        let sig = bitcoin::ecdsa::Signature::from_slice(&sig).unwrap();
+        let pubkey = bitcoin::PublicKey::from_slice(&pubkey).unwrap();
+        let msg = Message::from_digest(sighash);
+        assert!(
+            secp.verify_ecdsa(&msg, &sig.signature, &pubkey.inner).is_ok(),
+            "input {index} ecdsa signature"
+        );
+    }
+
+    #[test]
+    fn test_multi_input_multi_output_transaction_verifies() {
+        use misfit_core::transaction::random::{
+            input::InputParams,
+            script::{ScriptParams, ScriptTypes},
+            transaction::{Count, RandomTransacion, TxParams},
+        };
+
+        let inputs = [
+            ScriptTypes::P2WPKH,
+            ScriptTypes::P2TR,
+            ScriptTypes::P2PKH,
+            ScriptTypes::P2TWEAKEDTR,
+        ]
+        .into_iter()
+        .map(|script_type| InputParams {
+            script_params: Some(ScriptParams {
+                script_type: Some(script_type),
+                private_key: None,
+            }),
+            ..Default::default()
+        })
+        .collect();
+
+        let info = <bitcoin::Transaction as RandomTransacion>::random_with_info(TxParams {
+            inputs: Some(inputs),
+            output_count: Some(Count::Range(2..=4)),
+            ..Default::default()
+        }).unwrap();
+        let tx = &info.transaction;
+
+        assert_eq!(tx.input.len(), 4);
+        assert!((2..=4).contains(&tx.output.len()));
+        assert_eq!(info.prevouts.len(), 4);
+        for index in 0..tx.input.len() {
+            assert_input_verifies(tx, index, &info.prevouts);
+        }
+    }
+
+    #[test]
+    fn test_input_count_uses_template_for_uncovered_slots() {
+        use misfit_core::transaction::random::{
+            input::InputParams,
+            script::{ScriptParams, ScriptTypes},
+            transaction::{Count, RandomTransacion, TxParams},
+        };
+
+        let info = <bitcoin::Transaction as RandomTransacion>::random_with_info(TxParams {
+            input: Some(InputParams {
+                script_params: Some(ScriptParams {
+                    script_type: Some(ScriptTypes::P2WPKH),
+                    private_key: None,
+                }),
+                ..Default::default()
+            }),
+            inputs: Some(vec![InputParams {
+                script_params: Some(ScriptParams {
+                    script_type: Some(ScriptTypes::P2PKH),
+                    private_key: None,
+                }),
+                ..Default::default()
+            }]),
+            input_count: Some(Count::Fixed(3)),
+            output_count: Some(Count::Fixed(5)),
+            ..Default::default()
+        }).unwrap();
+
+        assert_eq!(info.transaction.output.len(), 5);
+        assert!(info.prevouts[0].script_pubkey.is_p2pkh());
+        assert!(info.prevouts[1..].iter().all(|prevout| prevout.script_pubkey.is_p2wpkh()));
+        for index in 0..info.transaction.input.len() {
+            assert_input_verifies(&info.transaction, index, &info.prevouts);
+        }
+    }
+
+    #[test]
+    fn test_transaction_conserves_value_with_absolute_fee() {
+        use bitcoin::Amount;
+        use misfit_core::transaction::generator::GenerateTx;
+        use misfit_core::transaction::random::{
+            input::InputParams,
+            output::OutputParams,
+            script::{ScriptParams, ScriptTypes},
+            transaction::{Count, Fee, TxParams},
+        };
+
+        let info = GenerateTx::valid_random_with_info(TxParams {
+            input: Some(InputParams {
+                value: Some(Amount::from_sat(50_000)),
+                script_params: Some(ScriptParams {
+                    script_type: Some(ScriptTypes::P2WPKH),
+                    private_key: None,
+                }),
+                ..Default::default()
+            }),
+            input_count: Some(Count::Fixed(2)),
+            outputs: Some(vec![OutputParams {
+                value: Some(Amount::from_sat(30_000)),
+                ..Default::default()
+            }]),
+            output_count: Some(Count::Fixed(3)),
+            fee: Some(Fee::Absolute(Amount::from_sat(1_234))),
+            ..Default::default()
+        }).unwrap();
+
+        assert_eq!(info.transaction.output[0].value, Amount::from_sat(30_000));
+        assert_eq!(info.fee(), Some(Amount::from_sat(1_234)));
+        for index in 0..info.transaction.input.len() {
+            assert_input_verifies(&info.transaction, index, &info.prevouts);
+        }
+    }
+
+    #[test]
+    fn test_transaction_pays_requested_fee_rate() {
+        use bitcoin::{Amount, FeeRate};
+        use misfit_core::transaction::generator::GenerateTx;
+        use misfit_core::transaction::random::transaction::{Count, Fee, TxParams};
+
+        let rate = FeeRate::from_sat_per_vb_unchecked(7);
+        let info = GenerateTx::valid_random_with_info(TxParams {
+            input_count: Some(Count::Range(1..=3)),
+            output_count: Some(Count::Range(1..=3)),
+            fee: Some(Fee::Rate(rate)),
+            ..Default::default()
+        }).unwrap();
+
+        let fee = info.fee().expect("transaction creates money");
+        let input_value: Amount = info.prevouts.iter().map(|prevout| prevout.value).sum();
+        assert!(input_value <= Amount::MAX_MONEY);
+        assert!(fee >= rate.fee_vb(info.transaction.vsize() as u64).unwrap());
+    }
+
+    #[test]
+    fn test_transaction_raises_random_inputs_to_cover_fixed_outputs() {
+        use bitcoin::{Amount, FeeRate};
+        use misfit_core::transaction::generator::GenerateTx;
+        use misfit_core::transaction::random::{
+            input::InputParams,
+            output::OutputParams,
+            script::{ScriptParams, ScriptTypes},
+            transaction::{Count, Fee, TxParams},
+        };
+
+        // Random input values stay below 1 BTC
+        let rate = FeeRate::from_sat_per_vb_unchecked(20);
+        let info = GenerateTx::valid_random_with_info(TxParams {
+            input: Some(InputParams {
+                script_params: Some(ScriptParams {
+                    script_type: Some(ScriptTypes::P2WPKH),
+                    private_key: None,
+                }),
+                ..Default::default()
+            }),
+            input_count: Some(Count::Fixed(2)),
+            outputs: Some(vec![OutputParams {
+                value: Some(Amount::from_int_btc(5)),
+                ..Default::default()
+            }]),
+            output_count: Some(Count::Fixed(2)),
+            fee: Some(Fee::Rate(rate)),
+            seed: Some(7),
+            ..Default::default()
+        }).unwrap();
+
+        assert_eq!(info.transaction.output[0].value, Amount::from_int_btc(5));
+        let fee = info.fee().expect("transaction creates money");
+        assert!(fee >= rate.fee_vb(info.transaction.vsize() as u64).unwrap());
+        for index in 0..info.transaction.input.len() {
+            assert_input_verifies(&info.transaction, index, &info.prevouts);
+        }
+    }
+
+    #[test]
+    fn test_transaction_rejects_fixed_outputs_above_fixed_inputs() {
+        use bitcoin::Amount;
+        use misfit_core::transaction::generator::GenerateTx;
+        use misfit_core::transaction::random::{
+            input::InputParams,
+            output::OutputParams,
+            transaction::{Fee, TxParams},
+        };
+
+        let result = GenerateTx::valid_random_with_info(TxParams {
+            input: Some(InputParams {
+                value: Some(Amount::from_sat(50_000)),
+                ..Default::default()
+            }),
+            outputs: Some(vec![OutputParams {
+                value: Some(Amount::from_sat(50_000)),
+                ..Default::default()
+            }]),
+            fee: Some(Fee::Absolute(Amount::from_sat(1_000))),
+            ..Default::default()
+        });
+        assert!(matches!(result, Err(misfit_core::Error::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn test_seeded_generation_is_reproducible() {
+        assert_eq!(Generator::transaction(3, Some(42)), Generator::transaction(3, Some(42)));
+        assert_eq!(Generator::block(2, Some(7)), Generator::block(2, Some(7)));
+        assert_ne!(Generator::transaction(1, Some(1)), Generator::transaction(1, Some(2)));
+    }
+
+    #[test]
+    fn test_seeded_params_reproduce_header_and_breakage() {
+        use bitcoin::block::Header;
+        use misfit_core::block::breakers::block::{BlockField, BlockProcessor, ProcessingConfig};
+        use misfit_core::block::random::header::{HeaderParams, RandomHeader};
+
+        let header = |seed| {
+            Header::random(HeaderParams {
+                seed: Some(seed),
+                ..Default::default()
+            })
+        };
+        assert_eq!(header(9), header(9));
+
+        let processor = BlockProcessor::new(ProcessingConfig {
+            fields_to_modify: vec![BlockField::PrevBlockHash, BlockField::MerkleRoot],
+            randomize_hashes: true,
+            seed: Some(3),
+            ..Default::default()
+        });
+        assert_eq!(
+            processor.process_block_header(&header(9)),
+            processor.process_block_header(&header(9))
+        );
+    }
+
+    #[test]
+    fn test_cli_one_shot_parsing_and_exit_status() {
+        use crate::cli::{run, Cli, Commands, OutputFormat};
+        use clap::Parser;
+
+        let cli = Cli::try_parse_from(["misfit_core", "tx", "5", "--seed", "1"]).unwrap();
+        assert!(matches!(
+            cli.command,
+            Some(Commands::Tx { txscount: 5, seed: Some(1), .. })
+        ));
+
+        let cli = Cli::try_parse_from(["misfit_core", "shell"]).unwrap();
+        assert!(matches!(cli.command, Some(Commands::Interactive)));
+        assert!(Cli::try_parse_from(["misfit_core"]).unwrap().command.is_none());
+
+        let regtest_manager = Generator::regtest_invocation("blablabla", "-regtest");
+        let bad_tx = Commands::DecodeTransaction {
+            raw_transaction: "zz".to_string(),
+        };
+        assert!(run(bad_tx, OutputFormat::Text, &regtest_manager).is_err());
+    }
+
+    #[test]
+    fn test_reports_serialize_to_json() {
+        use crate::cli::{Cli, OutputFormat};
+        use clap::Parser;
+
+        let cli = Cli::try_parse_from(["misfit_core", "tx", "--format", "json"]).unwrap();
+        assert_eq!(cli.format, OutputFormat::Json);
+
+        let transactions = serde_json::to_value(Generator::transaction(1, Some(5))).unwrap();
+        let tx = &transactions["transactions"][0];
+        assert!(tx["raw_hex"].is_string());
+        assert!(tx["txid"].is_string());
+        assert!(tx["wtxid"].is_string());
+
+        let block = serde_json::to_value(Generator::block(2, Some(5))).unwrap();
+        assert_eq!(block["transactions"].as_array().unwrap().len(), 3);
+        assert!(block["header"]["bits"].is_u64());
+
+        let raw_tx = tx["raw_hex"].as_str().unwrap().to_string();
+        let cli_flags = vec!["--locktime".to_string(), "--amount".to_string()];
+        let report = Generator::break_transaction(raw_tx, cli_flags, Some(5)).unwrap();
+        let broken = serde_json::to_value(report).unwrap();
+        let mutations = broken["mutations"].as_array().unwrap();
+        assert_eq!(mutations.len(), 2);
+        assert_eq!(mutations[0]["name"], "OutputAmount");
+        assert_eq!(mutations[0]["expected_failure"], "bad-txns-vout-toolarge");
+        assert_eq!(mutations[1]["name"], "Locktime");
+    }
+
+    #[test]
+    fn test_errors_are_typed() {
+        use misfit_core::transaction::breakers::transaction::parse_flags;
+        use misfit_core::Error;
+
+        assert!(matches!(
+            Generator::decode_raw_transaction("zz".to_string()),
+            Err(Error::Decode(_))
+        ));
+        assert!(matches!(
+            Generator::decoder_block_header("00".repeat(79)),
+            Err(Error::Decode(_))
+        ));
+        assert!(matches!(
+            Generator::parse_cli_flags_to_block_fields(vec!["--nonse".to_string()]),
+            Err(Error::InvalidFlag(flag)) if flag == "--nonse"
+        ));
+
+        let args = |flags: &[&str]| {
+            std::iter::once("btc-invalidator")
+                .chain(flags.iter().copied())
+                .map(String::from)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(parse_flags(args(&["--txid", "--amount"])).unwrap().len(), 2);
+        assert!(matches!(parse_flags(args(&[])), Err(Error::NoFlags)));
+        assert!(matches!(
+            parse_flags(args(&["--txid", "amount"])),
+            Err(Error::InvalidFlag(flag)) if flag == "amount"
+        ));
+    }
+
+    #[test]
+    fn test_library_facade_returns_typed_values() {
+        use misfit_core::block::breakers::block::{BlockField, ProcessingConfig};
+        use misfit_core::transaction::breakers::flags::InvalidationFlag;
+        use std::collections::HashSet;
+
+        let (block, height) = Generator::random_block(2, Some(11));
+        assert_eq!(block.txdata.len(), 3);
+        assert!(block.check_merkle_root());
+        assert_eq!(Generator::random_block(0, Some(11)), Generator::random_block(0, Some(11)));
+        assert_eq!(Generator::block(2, Some(11)), misfit_core::report::BlockReport::new(&block, height));
+
+        let tx = Generator::transactions(1, Some(11)).remove(0);
+        let flags = HashSet::from([InvalidationFlag::Version]);
+        let broken = Generator::invalidate_transaction(tx.clone(), &flags, None).unwrap();
+        assert_eq!(broken.version.0, tx.version.0 + 15);
+        assert_eq!(broken.input, tx.input);
+        assert!(Generator::invalidate_transaction(tx, &HashSet::new(), None).is_err());
+
+        let config = ProcessingConfig {
+            fields_to_modify: vec![BlockField::Nonce],
+            ..Default::default()
+        };
+        let header = Generator::break_header(&block.header, config).unwrap();
+        assert_ne!(header.nonce, block.header.nonce);
+        assert_eq!(header.merkle_root, block.header.merkle_root);
+    }
+
+    #[test]
+    fn test_decode_full_block_reports_inconsistencies() {
+        use bitcoin::hashes::Hash;
+        use misfit_core::report::DecodedBlockReport;
+
+        let (block, _) = Generator::random_block(2, Some(8));
+        let raw_block = bitcoin::consensus::encode::serialize_hex(&block);
+        let decoded = Generator::decode_full_block(raw_block).unwrap();
+        assert_eq!(decoded, block);
+
+        let report = DecodedBlockReport::from(&decoded);
+        assert_eq!(report.transactions.len(), 3);
+        assert_eq!(report.computed_merkle_root, Some(report.header.merkle_root.clone()));
+        assert!(report.witness_commitment.is_some());
+        assert_eq!(report.vsize, block.weight().to_vbytes_ceil());
+        assert!(!report.issues.iter().any(|issue| issue.starts_with("bad-txnmrklroot")));
+
+        let mut broken = block.clone();
+        broken.header.merkle_root = bitcoin::TxMerkleNode::all_zeros();
+        broken.txdata[0].output.retain(|output| !output.script_pubkey.is_op_return());
+        let issues = DecodedBlockReport::from(&broken).issues;
+        assert!(issues.iter().any(|issue| issue.starts_with("bad-txnmrklroot")));
+        assert!(issues.iter().any(|issue| issue.starts_with("unexpected-witness")));
+
+        broken.txdata.push(broken.txdata[1].clone());
+        broken.txdata.swap(0, 1);
+        let issues = DecodedBlockReport::from(&broken).issues;
+        assert!(issues.iter().any(|issue| issue.starts_with("bad-txns-duplicate")));
+        assert!(issues.iter().any(|issue| issue.starts_with("bad-cb-missing")));
+
+        assert!(Generator::decode_full_block("00".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_break_full_block_body() {
+        use misfit_core::report::DecodedBlockReport;
+
+        let (block, _) = Generator::random_block(3, Some(21));
+        let raw_block = bitcoin::consensus::encode::serialize_hex(&block);
+
+        for (flag, reason) in [
+            ("--remove-coinbase", "bad-cb-missing"),
+            ("--reorder-txs", "bad-cb-missing"),
+            ("--duplicate-tx", "bad-txns-duplicate"),
+            ("--tamper-tx", "bad-txnmrklroot"),
+        ] {
+            let report =
+                Generator::break_block(raw_block.clone(), vec![flag.to_string()], vec![]).unwrap();
+            assert_eq!(report.mutations[0].expected_failure.as_deref(), Some(reason));
+
+            let broken = Generator::decode_full_block(report.broken_block_hex.unwrap()).unwrap();
+            let issues = DecodedBlockReport::from(&broken).issues;
+            assert!(issues.iter().any(|issue| issue.starts_with(reason)), "{flag}: {issues:?}");
+            if reason != "bad-txnmrklroot" {
+                assert!(broken.check_merkle_root(), "{flag}");
+            } else {
+                assert_eq!(broken.header, block.header);
+            }
+        }
+
+        let header = bitcoin::consensus::encode::serialize_hex(&block.header);
+        let result = Generator::break_block(header, vec!["--tamper-tx".to_string()], vec![]);
+        assert!(matches!(result, Err(misfit_core::Error::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn test_generated_blocks_meet_their_target() {
+        use bitcoin::CompactTarget;
+        use misfit_core::block::generator::GenerateBlock;
+        use misfit_core::block::random::block::BlockParams;
+
+        let (block, _) = Generator::random_block(2, Some(4));
+        assert_eq!(block.header.bits.to_consensus(), 0x207fffff);
+        assert!(block.header.validate_pow(block.header.target()).is_ok());
+        assert!(block.check_merkle_root());
+
+        let bits = CompactTarget::from_consensus(0x1f00ffff);
+        let (block, _) = GenerateBlock::valid_random(BlockParams {
+            target: Some(bits),
+            seed: Some(4),
+            ..Default::default()
+        });
+        assert_eq!(block.header.bits, bits);
+        assert!(block.header.validate_pow(block.header.target()).is_ok());
+        assert!(block.check_merkle_root());
+    }
+
+    #[test]
+    fn test_generated_chain_is_linked() {
+        use bitcoin::{blockdata::constants::genesis_block, CompressedPublicKey, Network, ScriptBuf};
+        use misfit_core::block::decoder::BlockUtils;
+        use misfit_core::block::generator::{ChainParams, GenerateChain};
+
+        let private_key = misfit_core::rng::private_key();
+        let chain = GenerateChain::valid_random(ChainParams {
+            length: Some(5),
+            private_key: Some(private_key),
+            seed: Some(2),
+            ..Default::default()
+        });
+        assert_eq!(chain.len(), 5);
+
+        let secp = bitcoin::secp256k1::Secp256k1::new();
+        let public_key = CompressedPublicKey::from_private_key(&secp, &private_key).unwrap();
+        let spendable = ScriptBuf::new_p2wpkh(&public_key.wpubkey_hash());
+        let mut parent = genesis_block(Network::Regtest).header;
+        for (index, (block, height)) in chain.iter().enumerate() {
+            assert_eq!(*height as usize, index + 1);
+            assert_eq!(block.header.prev_blockhash, parent.block_hash());
+            assert!(block.header.time > parent.time);
+            assert_eq!(BlockUtils::bip34_height(block), Some(*height as u64));
+            assert!(block.header.validate_pow(block.header.target()).is_ok());
+            assert!(block.check_merkle_root());
+            assert!(block.txdata[0].output.iter().all(|output| output.script_pubkey == spendable));
+            parent = block.header;
+        }
+
+        assert_eq!(Generator::random_chain(3, Some(2)), Generator::random_chain(3, Some(2)));
+    }
+
+    #[test]
+    fn test_coinbase_claims_subsidy_and_fees() {
+        use bitcoin::{Amount, Network, ScriptBuf};
+        use misfit_core::block::decoder::BlockUtils;
+        use misfit_core::transaction::random::coinbase::block_subsidy;
+
+        assert_eq!(block_subsidy(149, Network::Regtest), Amount::from_btc(50.0).unwrap());
+        assert_eq!(block_subsidy(150, Network::Regtest), Amount::from_btc(25.0).unwrap());
+        assert_eq!(block_subsidy(420_000, Network::Bitcoin), Amount::from_btc(12.5).unwrap());
+        assert_eq!(block_subsidy(64 * 210_000, Network::Bitcoin), Amount::ZERO);
+
+        misfit_core::rng::set_seed(6);
+        let infos = Generator::transaction_infos(3, None);
+        let fees: Amount = infos.iter().map(|info| info.fee().unwrap()).sum();
+        misfit_core::rng::set_seed(6);
+        let (block, height) = Generator::random_block(3, None);
+
+        let coinbase = &block.txdata[0];
+        let claimed: Amount = coinbase.output.iter().map(|output| output.value).sum();
+        assert_eq!(claimed, block_subsidy(height, Network::Regtest) + fees);
+        assert_eq!(BlockUtils::bip34_height(&block), Some(height as u64));
+        let height_push = ScriptBuf::builder().push_int(height as i64).into_bytes();
+        assert!(coinbase.input[0].script_sig.as_bytes().starts_with(&height_push));
+        assert_eq!(coinbase.input[0].witness.len(), 1);
+        assert_eq!(coinbase.input[0].witness[0], [0u8; 32]);
+        assert!(block.check_witness_commitment());
+    }
+
+    #[test]
+    fn test_coinbase_breakers_trigger_their_reject_reason() {
+        use bitcoin::Amount;
+        use misfit_core::block::decoder::BlockUtils;
+        use misfit_core::report::DecodedBlockReport;
+
+        let (block, _) = Generator::random_block(2, Some(13));
+        let raw_block = bitcoin::consensus::encode::serialize_hex(&block);
+        let break_with = |flag: &str| {
+            let report =
+                Generator::break_block(raw_block.clone(), vec![flag.to_string()], vec![]).unwrap();
+            let broken = Generator::decode_full_block(report.broken_block_hex.unwrap()).unwrap();
+            (report.mutations[0].expected_failure.clone().unwrap(), broken)
+        };
+
+        for flag in [
+            "--coinbase-short-script",
+            "--coinbase-long-script",
+            "--coinbase-prevout",
+            "--multiple-coinbases",
+            "--coinbase-no-witness-nonce",
+        ] {
+            let (reason, broken) = break_with(flag);
+            let issues = DecodedBlockReport::from(&broken).issues;
+            assert!(issues.iter().any(|issue| issue.starts_with(&reason)), "{flag}: {issues:?}");
+            assert!(!issues.iter().any(|issue| issue.starts_with("high-hash")), "{flag}");
+            assert!(broken.check_merkle_root(), "{flag}");
+        }
+
+        // Height and amount issues depend on the version and chain context
+        let (reason, broken) = break_with("--coinbase-no-height");
+        assert_eq!(reason, "bad-cb-height");
+        assert_eq!(BlockUtils::bip34_height(&broken), None);
+
+        let (reason, broken) = break_with("--coinbase-wrong-height");
+        assert_eq!(reason, "bad-cb-height");
+        assert_eq!(
+            BlockUtils::bip34_height(&broken),
+            BlockUtils::bip34_height(&block).map(|height| height + 1)
+        );
+
+        let (reason, broken) = break_with("--coinbase-overpay");
+        assert_eq!(reason, "bad-cb-amount");
+        assert_eq!(
+            broken.txdata[0].output[0].value,
+            block.txdata[0].output[0].value + Amount::ONE_SAT
+        );
+        assert!(DecodedBlockReport::from(&broken).issues.is_empty());
+
+        // Without a witness commitment there is no reserved value to drop
+        let (block, _) = Generator::random_block(0, Some(13));
+        let result = Generator::break_block(
+            bitcoin::consensus::encode::serialize_hex(&block),
+            vec!["--coinbase-no-witness-nonce".to_string()],
+            vec![],
+        );
+        assert!(matches!(result, Err(misfit_core::Error::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn test_witness_commitment_breakers() {
+        use misfit_core::report::DecodedBlockReport;
+
+        let (block, _) = Generator::random_block(2, Some(14));
+        assert!(block.check_witness_commitment());
+        let raw_block = bitcoin::consensus::encode::serialize_hex(&block);
+
+        for (flag, reason) in [
+            ("--commitment-wrong-hash", "bad-witness-merkle-match"),
+            ("--commitment-missing", "unexpected-witness"),
+            ("--commitment-not-last", "bad-witness-merkle-match"),
+            ("--commitment-wrong-magic", "unexpected-witness"),
+            ("--commitment-nonce-size", "bad-witness-nonce-size"),
+        ] {
+            let report =
+                Generator::break_block(raw_block.clone(), vec![flag.to_string()], vec![]).unwrap();
+            assert_eq!(report.mutations[0].expected_failure.as_deref(), Some(reason));
+
+            let broken = Generator::decode_full_block(report.broken_block_hex.unwrap()).unwrap();
+            assert!(broken.check_merkle_root(), "{flag}");
+            assert!(!broken.check_witness_commitment(), "{flag}");
+            let issues = DecodedBlockReport::from(&broken).issues;
+            assert!(issues.iter().any(|issue| issue.starts_with(reason)), "{flag}: {issues:?}");
+            assert!(!issues.iter().any(|issue| issue.starts_with("high-hash")), "{flag}");
+        }
+
+        // A lone coinbase has no witness to commit to
+        let (block, _) = Generator::random_block(0, Some(14));
+        let raw_block = bitcoin::consensus::encode::serialize_hex(&block);
+        for flag in [
+            "--commitment-wrong-hash",
+            "--commitment-missing",
+            "--commitment-not-last",
+            "--commitment-wrong-magic",
+            "--commitment-nonce-size",
+        ] {
+            let result = Generator::break_block(raw_block.clone(), vec![flag.to_string()], vec![]);
+            assert!(matches!(result, Err(misfit_core::Error::InvalidConfig(_))), "{flag}");
+        }
+    }
+
+    #[test]
+    fn test_merkle_tree_attacks() {
+        use bitcoin::consensus::encode::{deserialize, serialize_hex};
+        use bitcoin::MerkleBlock;
+        use misfit_core::report::DecodedBlockReport;
+
+        // CVE-2012-2459: 3 transactions pair the last with itself, 6 pair the
+        // last two with themselves one level up
+        for tx_count in [2, 5] {
+            let (block, _) = Generator::random_block(tx_count, Some(15));
+            let report = Generator::break_block(
+                serialize_hex(&block),
+                vec!["--duplicate-tail-txs".to_string()],
+                vec![],
+            )
+            .unwrap();
+            let broken = Generator::decode_full_block(report.broken_block_hex.unwrap()).unwrap();
+            assert_eq!(broken.header, block.header);
+            assert!(broken.txdata.len() > block.txdata.len());
+            assert!(broken.check_merkle_root());
+            assert!(broken.check_witness_commitment());
+            let issues = DecodedBlockReport::from(&broken).issues;
+            assert_eq!(issues.len(), 1, "{issues:?}");
+            assert!(issues[0].starts_with("bad-txns-duplicate"));
+        }
+
+        // 2 and 4 transactions never pair a node with itself
+        for tx_count in [1, 3] {
+            let (block, _) = Generator::random_block(tx_count, Some(15));
+            let result = Generator::break_block(
+                serialize_hex(&block),
+                vec!["--duplicate-tail-txs".to_string()],
+                vec![],
+            );
+            assert!(matches!(result, Err(misfit_core::Error::InvalidConfig(_))));
+        }
+
+        // A 64-byte transaction hides a txid one level below the real leaves
+        let (block, _) = Generator::random_block(3, Some(15));
+        let report = Generator::break_block(
+            serialize_hex(&block),
+            vec!["--insert-64-byte-tx".to_string()],
+            vec![],
+        )
+        .unwrap();
+        let raw_block = report.broken_block_hex.unwrap();
+        let broken = Generator::decode_full_block(raw_block.clone()).unwrap();
+        assert!(DecodedBlockReport::from(&broken).issues.is_empty());
+
+        let proof = Generator::forge_merkle_proof(raw_block).unwrap();
+        let inner_tx = &broken.txdata[proof.inner_tx_index];
+        assert_eq!(proof.inner_txid, inner_tx.compute_txid().to_string());
+        assert!(broken.txdata.iter().all(|tx| tx.compute_txid().to_string() != proof.forged_txid));
+
+        let merkle_block: MerkleBlock =
+            deserialize(&hex::decode(&proof.merkle_block_hex).unwrap()).unwrap();
+        let (mut matches, mut indexes) = (vec![], vec![]);
+        let root = merkle_block.txn.extract_matches(&mut matches, &mut indexes).unwrap();
+        assert_eq!(root, broken.header.merkle_root);
+        assert!(merkle_block.extract_matches(&mut vec![], &mut vec![]).is_ok());
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].to_string(), proof.forged_txid);
+        assert_eq!(indexes, vec![2 * proof.inner_tx_index as u32 + 1]);
+
+        assert!(matches!(
+            Generator::forge_merkle_proof(serialize_hex(&block)),
+            Err(misfit_core::Error::InvalidConfig(_))
+        ));
+    }
+
+    #[test]
+    fn test_retarget_matches_bitcoin_core() {
+        use bitcoin::{pow::Target, Network};
+        use misfit_core::block::breakers::bits::BitsProcessor;
+
+        // Vectors of Bitcoin Core's pow_tests
+        let cases = [
+            (0x1d00ffff, 1261130161, 1262152739, 0x1d00d86a),
+            (0x1d00ffff, 1231006505, 1233061996, 0x1d00ffff),
+            (0x1c05a3f4, 1279008237, 1279297671, 0x1c0168fd),
+            (0x1c387f6f, 1263163443, 1269211443, 0x1d00e1fd),
+        ];
+        for (last_bits, first_time, last_time, required) in cases {
+            let retarget = BitsProcessor::retarget(last_bits, first_time, last_time, Network::Bitcoin);
+            assert_eq!(retarget, required, "0x{last_bits:08x}");
+        }
+        // Regtest never retargets
+        assert_eq!(BitsProcessor::retarget(0x207fffff, 0, 1, Network::Regtest), 0x207fffff);
+
+        let report = Generator::retarget(0x1d00ffff, 1261130161, 1262152739, Network::Bitcoin);
+        assert_eq!(report.required_bits, 0x1d00d86a);
+        assert_eq!(report.one_unit_easier, 0x1d00d86b);
+        assert_eq!(report.one_unit_harder, 0x1d00d869);
+        // The minimum clamps at a quarter of two weeks
+        let report = Generator::retarget(0x1c05a3f4, 1279008237, 1279297671, Network::Bitcoin);
+        assert_eq!(report.clamped_timespan, 302_400);
+
+        assert_eq!(BitsProcessor::one_unit_easier(0x207fffff), 0x21008000);
+        assert_eq!(BitsProcessor::one_unit_harder(0x1d008000), 0x1c7fffff);
+        assert_eq!(BitsProcessor::bits_to_target(0x1d00ffff), Target::MAX);
+        assert_eq!(BitsProcessor::increase_difficulty(0x1d00ffff, 4.0), 0x1c3fffc0);
+        assert_eq!(BitsProcessor::decrease_difficulty(0x1c3fffc0, 4.0), 0x1d00ffff);
+        assert!(BitsProcessor::bits_to_work(0x1c3fffc0) > BitsProcessor::bits_to_work(0x1d00ffff));
+        assert!(!BitsProcessor::is_valid_bits(0x1d80ffff));
+        assert!(!BitsProcessor::is_valid_bits(0x2301ffff));
+
+        // Re-mined to the harder bits, so only the difficulty check fails
+        let (block, _) = Generator::random_block(1, Some(16));
+        let report = Generator::break_block(
+            bitcoin::consensus::encode::serialize_hex(&block),
+            vec!["--bits-harder".to_string()],
+            vec![],
+        )
+        .unwrap();
+        assert_eq!(report.mutations[0].expected_failure.as_deref(), Some("bad-diffbits"));
+        let broken = Generator::decode_full_block(report.broken_block_hex.unwrap()).unwrap();
+        assert_eq!(broken.header.bits.to_consensus(), 0x207ffffe);
+        assert!(broken.header.validate_pow(broken.header.target()).is_ok());
+    }
+
+    #[test]
+    fn test_timestamp_scenarios_sit_on_the_time_rule_edges() {
+        use misfit_core::block::breakers::timestamp::TimestampScenario;
+        use misfit_core::report::DecodedBlockReport;
+
+        let now = 1_700_000_000;
+        let report = Generator::timestamp_scenarios(Some(now), Some(17)).unwrap();
+        assert_eq!(report.chain.len(), 100);
+        assert_eq!(report.scenarios.len(), TimestampScenario::ALL.len());
+
+        let mut times: Vec<u32> =
+            report.chain[89..].iter().map(|block| block.header.time).collect();
+        times.sort_unstable();
+        let median_time_past = times[5];
+        assert_eq!(report.median_time_past, median_time_past);
+
+        let tip_hash = &report.chain[99].header.block_hash;
+        let times: Vec<u32> = report.scenarios.iter().map(|scenario| scenario.time).collect();
+        assert_eq!(times[..4], [median_time_past, median_time_past + 1, now + 7_200, now + 7_201]);
+        for scenario in &report.scenarios {
+            assert_eq!(&scenario.block.header.prev_blockhash, tip_hash);
+            assert_eq!(scenario.block.height, 101);
+            let block = Generator::decode_full_block(scenario.block.raw_hex.clone()).unwrap();
+            assert!(DecodedBlockReport::from(&block).issues.is_empty(), "{}", scenario.scenario);
+        }
+        assert_eq!(report.scenarios[0].scenario.expected_failure.as_deref(), Some("time-too-old"));
+        assert_eq!(report.scenarios[1].scenario.expected_failure, None);
+        assert_eq!(report.scenarios[3].scenario.expected_failure.as_deref(), Some("time-too-new"));
+
+        // BIP113: final by the block's time, but not by median-time-past.
+        // The locked transaction spends the first coinbase, now mature.
+        let first = Generator::decode_full_block(report.chain[0].raw_hex.clone()).unwrap();
+        for (scenario, lock_time) in [(4, median_time_past), (5, median_time_past - 1)] {
+            let scenario = &report.scenarios[scenario];
+            assert_eq!(scenario.lock_time, Some(lock_time));
+            let block = Generator::decode_full_block(scenario.block.raw_hex.clone()).unwrap();
+            let locked = &block.txdata[1];
+            assert_eq!(locked.lock_time.to_consensus_u32(), lock_time);
+            assert!(locked.is_lock_time_enabled());
+            assert!(lock_time < block.header.time);
+            assert_eq!(locked.input[0].previous_output.txid, first.txdata[0].compute_txid());
+            let prevout = &first.txdata[0].output[locked.input[0].previous_output.vout as usize];
+            assert!(prevout.script_pubkey.is_p2wpkh());
+            let spent: bitcoin::Amount = locked.output.iter().map(|output| output.value).sum();
+            assert!(spent < prevout.value);
+        }
+        assert_eq!(report.scenarios[5].scenario.expected_failure, None);
+        assert_eq!(
+            report.scenarios[4].scenario.expected_failure.as_deref(),
+            Some("bad-txns-nonfinal")
+        );
+    }
+
+    #[test]
+    fn test_version_bits_drive_a_deployment_to_active() {
+        use bitcoin::block::Version;
+        use misfit_core::block::breakers::version::VersionProcessor;
+        use misfit_core::block::version_bits::{signalling_version, signals};
+
+        let version = signalling_version(&[1, 28]);
+        assert_eq!(version.to_consensus(), 0x3000_0002);
+        assert!(signals(version, 28) && signals(version, 1) && !signals(version, 0));
+        // Bit 28 under the wrong top bits does not signal
+        assert!(!signals(Version::from_consensus(0x5000_0000), 28));
+
+        let report = Generator::version_bits_chain(28, 432, None, Some(18));
+        let transitions: Vec<(u32, &str)> = report
+            .transitions
+            .iter()
+            .map(|transition| (transition.height, transition.state.as_str()))
+            .collect();
+        assert_eq!(transitions, [(144, "started"), (288, "locked_in"), (432, "active")]);
+
+        // One signalling block short of the threshold never locks in
+        let report = Generator::version_bits_chain(28, 432, Some(107), Some(18));
+        assert_eq!(report.transitions.len(), 1);
+        assert_eq!(report.transitions[0].state, "started");
+        let blocks = &report.chain.blocks;
+        assert!(signals(Version::from_consensus(blocks[143].header.version), 28));
+        assert!(!signals(Version::from_consensus(blocks[250].header.version), 28));
+
+        assert_eq!(VersionProcessor::process_version(0x2000_0000, None), 3);
+        assert_eq!(VersionProcessor::process_version(2, None), 1);
+        assert_eq!(VersionProcessor::process_version(2, Some(7)), 7);
+
+        let (block, _) = Generator::random_block(1, Some(18));
+        for (flag, version) in [
+            ("--version-below-bip34", 1),
+            ("--version-below-bip66", 2),
+            ("--version-below-bip65", 3),
+        ] {
+            let report = Generator::break_block(
+                bitcoin::consensus::encode::serialize_hex(&block),
+                vec![flag.to_string()],
+                vec![],
+            )
+            .unwrap();
+            assert_eq!(report.mutations[0].expected_failure.as_deref(), Some("bad-version"));
+            let broken = Generator::decode_full_block(report.broken_block_hex.unwrap()).unwrap();
+            assert_eq!(broken.header.version.to_consensus(), version);
+            assert!(broken.header.validate_pow(broken.header.target()).is_ok(), "{flag}");
+        }
+    }
+
+    #[test]
+    fn test_header_chains_break_one_header_and_encode_as_headers_message() {
+        use bitcoin::block::Header;
+        use bitcoin::consensus::encode::{deserialize_partial, VarInt};
+        use misfit_core::block::breakers::header_chain::HeaderChainBreak;
+
+        let headers = Generator::random_header_chain(30, None, Some(19)).unwrap();
+        assert_eq!(headers.len(), 30);
+        for (index, header) in headers.iter().enumerate().skip(1) {
+            assert_eq!(header.prev_blockhash, headers[index - 1].block_hash());
+            assert!(header.validate_pow(header.target()).is_ok());
+        }
+
+        for breakage in HeaderChainBreak::ALL {
+            let headers = Generator::random_header_chain(30, Some((12, breakage)), Some(19)).unwrap();
+            let broken = headers[12];
+            match breakage {
+                HeaderChainBreak::BadPow => {
+                    assert!(broken.validate_pow(broken.target()).is_err());
+                }
+                HeaderChainBreak::BadPrevHash => {
+                    assert_ne!(broken.prev_blockhash, headers[11].block_hash());
+                    assert!(broken.validate_pow(broken.target()).is_ok());
+                }
+                HeaderChainBreak::BadBits => {
+                    assert_eq!(broken.bits.to_consensus(), 0x207ffffe);
+                    assert!(broken.validate_pow(broken.target()).is_ok());
+                }
+            }
+            // Everything else still links up and meets its target
+            for (index, header) in headers.iter().enumerate().skip(1).filter(|(i, _)| *i != 12) {
+                assert_eq!(header.prev_blockhash, headers[index - 1].block_hash());
+                assert!(header.validate_pow(header.target()).is_ok());
+                assert_eq!(header.bits.to_consensus(), 0x207fffff);
+            }
+        }
+        assert!(Generator::random_header_chain(3, Some((3, HeaderChainBreak::BadPow)), None).is_err());
+        assert_eq!("prev-hash".parse::<HeaderChainBreak>(), Ok(HeaderChainBreak::BadPrevHash));
+
+        let report = Generator::header_chain(300, Some((5, HeaderChainBreak::BadPow)), false, Some(19))
+            .unwrap();
+        assert_eq!(report.broken.as_ref().unwrap().mutation.expected_failure.as_deref(), Some("high-hash"));
+        let payload = hex::decode(&report.payload_hex).unwrap();
+        assert_eq!(payload.len(), 3 + 300 * 81);
+        let (count, mut offset) = deserialize_partial::<VarInt>(&payload).unwrap();
+        assert_eq!(count.0, 300);
+        let mut decoded = vec![];
+        while offset < payload.len() {
+            let (header, read) = deserialize_partial::<Header>(&payload[offset..]).unwrap();
+            assert_eq!(payload[offset + read], 0x00);
+            decoded.push(header);
+            offset += read + 1;
+        }
+        assert_eq!(decoded.len(), 300);
+        assert_eq!(decoded.last().unwrap().block_hash().to_string(), report.tip_hash);
+
+        let flat = Generator::header_chain(300, Some((5, HeaderChainBreak::BadPow)), true, Some(19))
+            .unwrap();
+        let flat = hex::decode(&flat.payload_hex).unwrap();
+        assert_eq!(flat.len(), 300 * 80);
+        assert_eq!(&flat[..80], &payload[3..83]);
+
+        // Only a first header is looked up by its previous hash, the others
+        // break the message's continuity
+        let label = |index, flat| {
+            let breakage = Some((index, HeaderChainBreak::BadPrevHash));
+            let report = Generator::header_chain(3, breakage, flat, Some(19)).unwrap();
+            report.broken.unwrap().mutation.expected_failure.unwrap()
+        };
+        assert_eq!(label(0, false), "prev-blk-not-found");
+        assert_eq!(label(1, false), "non-continuous headers sequence");
+        assert_eq!(label(1, true), "prev-blk-not-found");
+    }
+
+    #[test]
+    fn test_wire_messages_and_envelope_breakers() {
+        use bitcoin::consensus::encode::{deserialize, serialize_hex};
+        use bitcoin::p2p::message::{NetworkMessage, RawNetworkMessage};
+        use bitcoin::Network;
+        use misfit_core::p2p::breakers::EnvelopeBreak;
+        use misfit_core::p2p::message::{MessageCommand, MAX_PROTOCOL_MESSAGE_LENGTH};
+
+        let (block, _) = Generator::random_block(2, Some(20));
+        let block_hex = serialize_hex(&block);
+        let tx_hex = serialize_hex(&block.txdata[1]);
+        let header_hex = serialize_hex(&block.header);
+
+        for (network, magic) in [
+            (Network::Bitcoin, "f9beb4d9"),
+            (Network::Testnet, "0b110907"),
+            (Network::Signet, "0a03cf40"),
+            (Network::Regtest, "fabfb5da"),
+        ] {
+            let report =
+                Generator::wrap_message(tx_hex.clone(), MessageCommand::Tx, network, None).unwrap();
+            assert_eq!(report.magic, magic);
+            assert!(report.message_hex.starts_with(magic));
+        }
+
+        for (raw, command) in [
+            (&tx_hex, MessageCommand::Tx),
+            (&tx_hex, MessageCommand::Inv),
+            (&block_hex, MessageCommand::Block),
+            (&block_hex, MessageCommand::Headers),
+            (&block_hex, MessageCommand::CmpctBlock),
+            (&header_hex, MessageCommand::Headers),
+            (&header_hex, MessageCommand::Inv),
+        ] {
+            let report =
+                Generator::wrap_message(raw.clone(), command, Network::Regtest, None).unwrap();
+            assert_eq!(report.command, command.name());
+            let bytes = hex::decode(&report.message_hex).unwrap();
+            assert_eq!(report.length as usize, bytes.len() - 24);
+            let message: RawNetworkMessage = deserialize(&bytes).unwrap();
+            assert_eq!(message.cmd(), command.name());
+            match message.payload() {
+                NetworkMessage::Block(decoded) => assert_eq!(decoded, &block),
+                NetworkMessage::Headers(headers) => assert_eq!(headers, &vec![block.header]),
+                NetworkMessage::CmpctBlock(compact) => {
+                    assert_eq!(compact.compact_block.header, block.header);
+                    assert_eq!(compact.compact_block.short_ids.len(), block.txdata.len() - 1);
+                }
+                _ => {}
+            }
+        }
+        assert!(Generator::wrap_message(tx_hex.clone(), MessageCommand::Block, Network::Regtest, None)
+            .is_err());
+
+        let valid = Generator::wrap_message(block_hex.clone(), MessageCommand::Block, Network::Regtest, None)
+            .unwrap();
+        for breakage in EnvelopeBreak::ALL {
+            let report = Generator::wrap_message(
+                block_hex.clone(),
+                MessageCommand::Block,
+                Network::Regtest,
+                Some(breakage),
+            )
+            .unwrap();
+            let bytes = hex::decode(&report.message_hex).unwrap();
+            match breakage {
+                EnvelopeBreak::BadChecksum => {
+                    assert_ne!(report.checksum, valid.checksum);
+                    assert!(deserialize::<RawNetworkMessage>(&bytes).is_err());
+                }
+                EnvelopeBreak::WrongLength => {
+                    assert_eq!(report.length + 1, valid.length);
+                    assert_eq!(report.size, valid.size);
+                }
+                EnvelopeBreak::Oversized => {
+                    assert_eq!(report.length as usize, MAX_PROTOCOL_MESSAGE_LENGTH + 1);
+                    assert_eq!(bytes.len(), 24 + MAX_PROTOCOL_MESSAGE_LENGTH + 1);
+                }
+                EnvelopeBreak::UnknownCommand => {
+                    assert_eq!(report.command, "misfit");
+                    assert_eq!(report.checksum, valid.checksum);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_compact_blocks_and_their_breakers() {
+        use std::collections::HashSet;
+
+        use bitcoin::bip152::{BlockTransactions, HeaderAndShortIds, ShortId};
+        use bitcoin::consensus::encode::deserialize;
+        use misfit_core::p2p::compact_block::{CompactBlock, CompactBlockBreak};
+
+        let compact_block = Generator::random_compact_block(4, &[2], Some(21)).unwrap();
+        let compact = &compact_block.compact;
+        let keys = ShortId::calculate_siphash_keys(&compact.header, compact.nonce);
+        let expected: Vec<ShortId> = [1, 3, 4]
+            .iter()
+            .map(|index: &usize| {
+                let wtxid = compact_block.block.txdata[*index].compute_wtxid();
+                ShortId::with_siphash_keys(&wtxid, keys)
+            })
+            .collect();
+        assert_eq!(compact.short_ids, expected);
+        assert_eq!(CompactBlock::prefilled_indexes(compact), vec![0, 2]);
+        assert_eq!(compact_block.request.indexes, vec![1, 3, 4]);
+        assert_eq!(compact_block.response.transactions.len(), 3);
+        assert!(Generator::random_compact_block(2, &[5], Some(21)).is_err());
+
+        for breakage in CompactBlockBreak::ALL {
+            let report = Generator::compact_block(4, &[2], Some(breakage), Some(21)).unwrap();
+            let compact: HeaderAndShortIds =
+                deserialize(&hex::decode(&report.cmpctblock_hex).unwrap()).unwrap();
+            let response: BlockTransactions =
+                deserialize(&hex::decode(&report.blocktxn_hex).unwrap()).unwrap();
+            let prefilled = CompactBlock::prefilled_indexes(&compact);
+            let unique: HashSet<_> = compact.short_ids.iter().collect();
+            let tx_count = compact.short_ids.len() + prefilled.len();
+
+            match breakage {
+                CompactBlockBreak::ShortIdCollision => {
+                    assert_eq!(unique.len() + 1, compact.short_ids.len());
+                }
+                CompactBlockBreak::PrefilledIndexOutOfRange => {
+                    assert_eq!(prefilled, vec![0, tx_count]);
+                    // Core's check in PartiallyDownloadedBlock::InitData
+                    assert!(prefilled[1] > compact.short_ids.len() + 1);
+                }
+                CompactBlockBreak::BlockTxnMissing => {
+                    assert_eq!(response.transactions.len(), report.requested.len() - 1);
+                }
+                CompactBlockBreak::BlockTxnExtra => {
+                    assert_eq!(response.transactions.len(), report.requested.len() + 1);
+                    assert!(response.transactions.last().unwrap().is_coinbase());
+                }
+            }
+            if breakage != CompactBlockBreak::ShortIdCollision {
+                assert_eq!(unique.len(), compact.short_ids.len());
+            }
+        }
+        // A single short ID cannot collide
+        assert!(Generator::compact_block(1, &[], Some(CompactBlockBreak::ShortIdCollision), None).is_err());
+    }
+
+    #[test]
+    fn test_tapscript_spends_through_each_leaf() {
+        use bitcoin::key::Keypair;
+        use bitcoin::secp256k1::{schnorr, Message, Secp256k1};
+        use bitcoin::sighash::{Prevouts, SighashCache, TapSighashType};
+        use bitcoin::taproot::ControlBlock;
+        use bitcoin::{ScriptBuf, XOnlyPublicKey};
+        use misfit_core::transaction::random::taproot::TapLeafParams;
+
+        let secp = Secp256k1::new();
+        let leaves = vec![
+            TapLeafParams::CheckSig,
+            TapLeafParams::Multi { threshold: 2, keys: 3 },
+            TapLeafParams::Script(ScriptBuf::from_bytes(vec![0x51])),
+        ];
+
+        for leaf_index in 0..leaves.len() {
+            let (info, spend) =
+                Generator::tapscript_spend(Some(leaves.clone()), Some(leaf_index), None, Some(22))
+                    .unwrap();
+            let tx = &info.transaction;
+            let witness = &tx.input[0].witness;
+            let leaf = spend.leaf();
+
+            // The output key commits to the spent leaf through the control block
+            let script_pubkey = &info.prevouts[0].script_pubkey;
+            assert!(script_pubkey.is_p2tr());
+            assert_eq!(script_pubkey, &spend.script_pubkey());
+            let output_key = XOnlyPublicKey::from_slice(&script_pubkey.as_bytes()[2..]).unwrap();
+            let control_block = ControlBlock::decode(witness.last().unwrap()).unwrap();
+            assert_eq!(control_block, spend.control_block());
+            assert!(control_block.verify_taproot_commitment(&secp, output_key, &leaf.script));
+            assert_eq!(witness.nth(witness.len() - 2).unwrap(), leaf.script.as_bytes());
+
+            let sighash = SighashCache::new(tx)
+                .taproot_script_spend_signature_hash(
+                    0,
+                    &Prevouts::All(&info.prevouts),
+                    leaf.leaf_hash(),
+                    TapSighashType::Default,
+                )
+                .unwrap();
+            let message = Message::from_digest_slice(&sighash[..]).unwrap();
+            let stack: Vec<&[u8]> = witness.iter().take(witness.len() - 2).collect();
+            assert_eq!(stack.len(), leaf.keys.len());
+            // The first key the script checks pops the top of the stack
+            for (key, item) in leaf.keys.iter().zip(stack.iter().rev()) {
+                if item.is_empty() {
+                    continue;
+                }
+                let key = Keypair::from_secret_key(&secp, &key.inner).x_only_public_key().0;
+                let sig = schnorr::Signature::from_slice(item).unwrap();
+                assert!(secp.verify_schnorr(&sig, &message, &key).is_ok(), "leaf {leaf_index}");
+            }
+            let signatures = stack.iter().filter(|item| !item.is_empty()).count();
+            assert_eq!(signatures, leaf.threshold);
+        }
+
+        let (info, spend) = Generator::tapscript_spend(None, None, None, Some(22)).unwrap();
+        assert!((1..=4).contains(&spend.leaves.len()));
+        assert!(!info.transaction.input[0].witness.is_empty());
+        assert!(Generator::tapscript_spend(Some(vec![]), None, None, None).is_err());
+        assert!(Generator::tapscript_spend(Some(leaves), Some(3), None, None).is_err());
+        assert_eq!(
+            "multi:2-of-3".parse::<TapLeafParams>(),
+            Ok(TapLeafParams::Multi { threshold: 2, keys: 3 })
+        );
+        assert!("multi:0-of-3".parse::<TapLeafParams>().is_err());
+    }
+
+    #[test]
+    fn test_taproot_breakers_break_the_spend_they_label() {
+        use bitcoin::consensus::encode;
+        use bitcoin::secp256k1::Secp256k1;
+        use bitcoin::taproot::{ControlBlock, LeafVersion};
+        use bitcoin::XOnlyPublicKey;
+        use misfit_core::transaction::breakers::taproot::{TaprootBreak, ANNEX_TAG, OP_SUCCESS80};
+        use misfit_core::transaction::generator::GenerateTx;
+        use misfit_core::transaction::random::{
+            input::InputParams,
+            script::{ScriptParams, ScriptTypes},
+            taproot::TapLeafParams,
+            transaction::TxParams,
+        };
+
+        let secp = Secp256k1::new();
+        let leaves = vec![
+            TapLeafParams::CheckSig,
+            TapLeafParams::Multi { threshold: 2, keys: 2 },
+        ];
+        let spend_with = |breakage| {
+            Generator::tapscript_spend(Some(leaves.clone()), Some(1), Some(breakage), Some(23))
+                .unwrap()
+        };
+        let (valid, _) =
+            Generator::tapscript_spend(Some(leaves.clone()), Some(1), None, Some(23)).unwrap();
+        let valid_witness = valid.transaction.input[0].witness.to_vec();
+
+        for breakage in [TaprootBreak::ControlBlockParity, TaprootBreak::MerklePathMismatch] {
+            let (info, spend) = spend_with(breakage);
+            let witness = &info.transaction.input[0].witness;
+            let script_pubkey = &info.prevouts[0].script_pubkey;
+            let output_key = XOnlyPublicKey::from_slice(&script_pubkey.as_bytes()[2..]).unwrap();
+            let control_block = ControlBlock::decode(witness.last().unwrap()).unwrap();
+            assert!(!control_block.verify_taproot_commitment(&secp, output_key, &spend.leaf().script));
+        }
+
+        let (info, _) = spend_with(TaprootBreak::ControlBlockSize);
+        let control_block = info.transaction.input[0].witness.last().unwrap().to_vec();
+        assert_eq!(control_block.len(), valid_witness.last().unwrap().len() + 1);
+        assert!(ControlBlock::decode(&control_block).is_err());
+
+        let (info, _) = spend_with(TaprootBreak::Annex);
+        let witness = info.transaction.input[0].witness.to_vec();
+        assert_eq!(witness.last(), Some(&vec![ANNEX_TAG]));
+        assert_eq!(&witness[..witness.len() - 1], &valid_witness[..]);
+
+        let (info, _) = spend_with(TaprootBreak::ExplicitSighashDefault);
+        let witness = info.transaction.input[0].witness.to_vec();
+        assert!(witness[..2].iter().all(|sig| sig.len() == 65 && sig[64] == 0x00));
+        assert_eq!(&witness[2..], &valid_witness[2..]);
+
+        // Tree breakers leave a spend the output still commits to
+        for breakage in [TaprootBreak::OpSuccess, TaprootBreak::LeafVersion] {
+            let (info, spend) = spend_with(breakage);
+            let witness = &info.transaction.input[0].witness;
+            let output_key =
+                XOnlyPublicKey::from_slice(&info.prevouts[0].script_pubkey.as_bytes()[2..]).unwrap();
+            let control_block = ControlBlock::decode(witness.last().unwrap()).unwrap();
+            assert!(control_block.verify_taproot_commitment(&secp, output_key, &spend.leaf().script));
+            match breakage {
+                TaprootBreak::OpSuccess => {
+                    assert_eq!(spend.leaf().script.as_bytes().last(), Some(&OP_SUCCESS80))
+                }
+                _ => assert_eq!(control_block.leaf_version, LeafVersion::from_consensus(0xc2).unwrap()),
+            }
+        }
+
+        // A bare script leaf has no signature to extend
+        let script_leaf = vec![TapLeafParams::Script(bitcoin::ScriptBuf::from_bytes(vec![0x51]))];
+        assert!(Generator::tapscript_spend(
+            Some(script_leaf),
+            None,
+            Some(TaprootBreak::ExplicitSighashDefault),
+            None
+        )
+        .is_err());
+
+        // The witness breakers also run through break-transaction
+        let raw = encode::serialize_hex(&valid.transaction);
+        let report =
+            Generator::break_transaction(raw, vec!["--tr-annex".to_string()], None).unwrap();
+        assert_eq!(report.mutations.len(), 1);
+        assert_eq!(report.mutations[0].expected_failure.as_deref(), Some("bad-witness-nonstandard"));
+        let broken: bitcoin::Transaction = encode::deserialize_hex(&report.broken.raw_hex).unwrap();
+        assert_eq!(broken.input[0].witness.last(), Some(&[ANNEX_TAG][..]));
+        assert!(Generator::break_transaction(
+            encode::serialize_hex(&valid.transaction),
+            vec!["--tr-op-success".to_string()],
+            None
+        )
+        .is_err());
+
+        // A P2WPKH spend gives none of them a hold, not even the annex
+        let p2wpkh = GenerateTx::valid_random_with_info(TxParams {
+            input: Some(InputParams {
+                script_params: Some(ScriptParams {
+                    script_type: Some(ScriptTypes::P2WPKH),
+                    private_key: None,
+                }),
+                ..Default::default()
+            }),
+            seed: Some(23),
+            ..Default::default()
+        }).unwrap();
+        for breakage in TaprootBreak::WITNESS {
+            let flag = format!("--tr-{}", breakage.name());
+            let raw = encode::serialize_hex(&p2wpkh.transaction);
+            let result = Generator::break_transaction(raw, vec![flag.clone()], None);
+            assert!(matches!(result, Err(misfit_core::Error::InvalidConfig(_))), "{flag}");
+        }
+    }
+
+    #[test]
+    fn test_multisig_spends_for_each_wrapping() {
+        use bitcoin::ecdsa::Signature;
+        use bitcoin::hashes::Hash;
+        use bitcoin::secp256k1::{Message, Secp256k1};
+        use bitcoin::sighash::{EcdsaSighashType, SighashCache};
+        use bitcoin::script::Instruction;
+        use misfit_core::transaction::random::multisig::MultisigWrapping;
+
+        let secp = Secp256k1::new();
+        for wrapping in MultisigWrapping::ALL {
+            let (info, multisig) = Generator::multisig_spend(2, 3, wrapping, Some(24)).unwrap();
+            let input = &info.transaction.input[0];
+            let prevout = &info.prevouts[0];
+            let script = multisig.script();
+            assert_eq!(prevout.script_pubkey, multisig.script_pubkey());
+
+            // Signatures of the first two keys, in script order, after the extra pop
+            let (stack, sighash): (Vec<Vec<u8>>, _) = if wrapping.is_segwit() {
+                let witness = input.witness.to_vec();
+                assert_eq!(witness.last().unwrap(), script.as_bytes());
+                let sighash = SighashCache::new(&info.transaction)
+                    .p2wsh_signature_hash(0, &script, prevout.value, EcdsaSighashType::All)
+                    .unwrap();
+                (witness[..witness.len() - 1].to_vec(), sighash.to_byte_array())
+            } else {
+                let pushes: Vec<Vec<u8>> = input
+                    .script_sig
+                    .instructions()
+                    .map(|instruction| match instruction.unwrap() {
+                        Instruction::PushBytes(bytes) => bytes.as_bytes().to_vec(),
+                        Instruction::Op(op) => panic!("unexpected {op}"),
+                    })
+                    .collect();
+                assert!(input.witness.is_empty());
+                let sighash = SighashCache::new(&info.transaction)
+                    .legacy_signature_hash(0, &script, EcdsaSighashType::All.to_u32())
+                    .unwrap();
+                (pushes, sighash.to_byte_array())
+            };
+            let stack = match wrapping {
+                MultisigWrapping::P2SH => {
+                    assert_eq!(stack.last().unwrap(), script.as_bytes());
+                    stack[..stack.len() - 1].to_vec()
+                }
+                _ => stack,
+            };
+            assert_eq!(stack.len(), 3, "{wrapping:?}");
+            assert!(stack[0].is_empty());
+            let message = Message::from_digest(sighash);
+            for (key, item) in multisig.keys.iter().zip(&stack[1..]) {
+                let sig = Signature::from_slice(item).unwrap();
+                let pubkey = key.public_key(&secp).inner;
+                assert!(secp.verify_ecdsa(&message, &sig.signature, &pubkey).is_ok(), "{wrapping:?}");
+            }
+
+            // P2SH-P2WSH pushes the witness program and nothing else
+            match wrapping {
+                MultisigWrapping::P2SHP2WSH => {
+                    let redeem_script = multisig.redeem_script().unwrap();
+                    assert!(redeem_script.is_p2wsh());
+                    assert_eq!(input.script_sig.len(), redeem_script.len() + 1);
+                }
+                MultisigWrapping::P2WSH => assert!(input.script_sig.is_empty()),
+                _ => {}
+            }
+        }
+
+        assert!(Generator::multisig_spend(0, 3, MultisigWrapping::P2WSH, None).is_err());
+        assert!(Generator::multisig_spend(4, 3, MultisigWrapping::P2WSH, None).is_err());
+        assert!(Generator::multisig_spend(1, 16, MultisigWrapping::P2SH, None).is_err());
+        assert!(Generator::multisig_spend(1, 16, MultisigWrapping::P2WSH, None).is_ok());
+    }
+
+    #[test]
+    fn test_nested_segwit_spends_and_their_breakers() {
+        use bitcoin::consensus::encode;
+        use bitcoin::ecdsa::Signature;
+        use bitcoin::hashes::Hash;
+        use bitcoin::secp256k1::{Message, PublicKey, Secp256k1};
+        use bitcoin::sighash::{EcdsaSighashType, SighashCache};
+        use bitcoin::{ScriptBuf, Transaction, WScriptHash};
+        use misfit_core::transaction::breakers::nested::NestedSegwitProcessor;
+        use misfit_core::transaction::random::{
+            input::InputParams,
+            script::{ScriptParams, ScriptTypes},
+            transaction::TxParams,
+        };
+
+        let secp = Secp256k1::new();
+        let spend = |script_type: ScriptTypes| {
+            misfit_core::transaction::generator::GenerateTx::valid_random_with_info(TxParams {
+                input: Some(InputParams {
+                    script_params: Some(ScriptParams {
+                        script_type: Some(script_type),
+                        private_key: None,
+                    }),
+                    ..Default::default()
+                }),
+                seed: Some(25),
+                ..Default::default()
+            }).unwrap()
+        };
+
+        // The redeem script push hashes to the P2SH output and holds the
+        // program the witness spends
+        let p2wpkh = spend(ScriptTypes::P2SHP2WPKH);
+        let input = &p2wpkh.transaction.input[0];
+        let prevout = &p2wpkh.prevouts[0];
+        let program = NestedSegwitProcessor::redeem_script(&input.script_sig).unwrap();
+        assert!(program.is_p2wpkh());
+        assert_eq!(prevout.script_pubkey, ScriptBuf::new_p2sh(&program.script_hash()));
+        let pubkey = bitcoin::PublicKey::from_slice(input.witness.nth(1).unwrap()).unwrap();
+        assert_eq!(program, ScriptBuf::new_p2wpkh(&pubkey.wpubkey_hash().unwrap()));
+        let sighash = SighashCache::new(&p2wpkh.transaction)
+            .p2wpkh_signature_hash(0, &program, prevout.value, EcdsaSighashType::All)
+            .unwrap();
+        let sig = Signature::from_slice(input.witness.nth(0).unwrap()).unwrap();
+        let message = Message::from_digest(sighash.to_byte_array());
+        assert!(secp.verify_ecdsa(&message, &sig.signature, &pubkey.inner).is_ok());
+
+        let p2wsh = spend(ScriptTypes::P2SHP2WSH);
+        let input = &p2wsh.transaction.input[0];
+        let program = NestedSegwitProcessor::redeem_script(&input.script_sig).unwrap();
+        assert_eq!(p2wsh.prevouts[0].script_pubkey, ScriptBuf::new_p2sh(&program.script_hash()));
+        let witness_script = input.witness.last().unwrap();
+        assert_eq!(program, ScriptBuf::new_p2wsh(&WScriptHash::hash(witness_script)));
+        assert!(PublicKey::from_slice(&witness_script[2..35]).is_ok());
+
+        // Each breaker applies to the kind of program it targets
+        let break_raw = |info: &misfit_core::transaction::random::transaction::TxInfo, flag: &str| {
+            let raw = encode::serialize_hex(&info.transaction);
+            Generator::break_transaction(raw, vec![flag.to_string()], None)
+        };
+        let break_with = |info: &misfit_core::transaction::random::transaction::TxInfo, flag: &str| {
+            let report = break_raw(info, flag).unwrap();
+            assert_eq!(report.mutations.len(), 1);
+            let broken: Transaction = encode::deserialize_hex(&report.broken.raw_hex).unwrap();
+            broken.input[0].clone()
+        };
+        let original = &p2wpkh.transaction.input[0];
+        let broken = break_with(&p2wpkh, "--nested-redeem-script");
+        let program = NestedSegwitProcessor::redeem_script(&broken.script_sig).unwrap();
+        assert!(program.is_p2wpkh());
+        assert_ne!(p2wpkh.prevouts[0].script_pubkey, ScriptBuf::new_p2sh(&program.script_hash()));
+        assert_eq!(broken.witness, original.witness);
+
+        let broken = break_with(&p2wpkh, "--nested-witness-pubkey");
+        assert_eq!(broken.script_sig, original.script_sig);
+        assert_ne!(broken.witness.nth(1), original.witness.nth(1));
+        assert_eq!(broken.witness.nth(1).unwrap().len(), 33);
+        assert!(break_raw(&p2wpkh, "--nested-witness-script").is_err());
+
+        let broken = break_with(&p2wsh, "--nested-witness-script");
+        let program = NestedSegwitProcessor::redeem_script(&broken.script_sig).unwrap();
+        assert_ne!(program, ScriptBuf::new_p2wsh(&WScriptHash::hash(broken.witness.last().unwrap())));
+        assert!(break_raw(&p2wsh, "--nested-witness-pubkey").is_err());
+
+        // A native P2WPKH spend has no redeem script to break
+        let native = spend(ScriptTypes::P2WPKH);
+        for flag in ["--nested-redeem-script", "--nested-witness-pubkey"] {
+            let result = break_raw(&native, flag);
+            assert!(matches!(result, Err(misfit_core::Error::InvalidConfig(_))), "{flag}");
+        }
+        assert!(Generator::parse_cli_flags_to_invalidation_flags(vec!["--nested-bogus".to_string()])
+            .is_err());
+    }
+
+    #[test]
+    fn test_header_fields_past_the_regtest_limit_or_target_are_high_hash() {
+        use bitcoin::{CompactTarget, Network};
+        use misfit_core::block::breakers::block::BlockField;
+        use misfit_core::block::miner::Miner;
+
+        let (block, _) = Generator::random_block(1, Some(16));
+        assert_eq!(block.header.bits.to_consensus(), 0x207fffff);
+        let raw_header = bitcoin::consensus::encode::serialize_hex(&block.header);
+        let report =
+            Generator::break_block(raw_header, vec!["--bits-easier".to_string()], vec![]).unwrap();
+        assert_eq!(report.mutations.len(), 1);
+        assert_eq!(report.mutations[0].expected_failure.as_deref(), Some("high-hash"));
+
+        // Below the limit the easier bits are in range, just not the required ones
+        let mut header = block.header;
+        Miner::mine_header(&mut header, CompactTarget::from_consensus(0x207ffffe));
+        let field = BlockField::BitsOneUnitEasier;
+        assert_eq!(field.expected_failure_for(&header, Network::Regtest), Some("bad-diffbits"));
+        assert_eq!(field.expected_failure_for(&header, Network::Bitcoin), Some("high-hash"));
+        // A flipped nonce that happens to meet the target breaks nothing
+        assert_eq!(BlockField::Nonce.expected_failure_for(&header, Network::Regtest), None);
+
+        // Every header field fails proof of work first when the header misses its target
+        while header.validate_pow(header.target()).is_ok() {
+            header.nonce = header.nonce.wrapping_add(1);
+        }
+        for field in BlockField::FIELDS.iter().chain(&BlockField::EXPLICIT_HEADER_FIELDS) {
+            assert_eq!(field.expected_failure_for(&header, Network::Regtest), Some("high-hash"));
+        }
+        assert_eq!(
+            BlockField::TamperTx.expected_failure_for(&header, Network::Regtest),
+            Some("bad-txnmrklroot")
+        );
+    }
+
+    #[test]
+    fn test_header_breakages_are_labelled_with_what_fires() {
+        use misfit_core::block::version_bits::BIP65_MIN_VERSION;
+        use misfit_core::report::DecodedBlockReport;
+
+        let (block, _) = Generator::random_block(1, Some(5));
+        let raw_block = bitcoin::consensus::encode::serialize_hex(&block);
+        for (flag, reason) in [("--version", "bad-version"), ("--timestamp", "time-too-new")] {
+            let report =
+                Generator::break_block(raw_block.clone(), vec![flag.to_string()], vec![]).unwrap();
+            assert_eq!(report.mutations[0].expected_failure.as_deref(), Some(reason));
+            let broken = Generator::decode_full_block(report.broken_block_hex.unwrap()).unwrap();
+            assert!(broken.header.validate_pow(broken.header.target()).is_ok(), "{flag}");
+            assert!(DecodedBlockReport::from(&broken).issues.is_empty(), "{flag}");
+            match flag {
+                "--version" => assert!(broken.header.version.to_consensus() < BIP65_MIN_VERSION),
+                _ => assert!(broken.header.time > block.header.time + 2 * 60 * 60),
+            }
+        }
+
+        // Bits cannot be re-mined to, so proof of work fails first
+        let report =
+            Generator::break_block(raw_block, vec!["--bits".to_string()], vec![]).unwrap();
+        assert_eq!(report.mutations[0].expected_failure.as_deref(), Some("high-hash"));
+        let broken = Generator::decode_full_block(report.broken_block_hex.unwrap()).unwrap();
+        let issues = DecodedBlockReport::from(&broken).issues;
+        assert!(issues[0].starts_with("high-hash"), "{issues:?}");
+    }
+
+    #[test]
+    fn test_duplicate_tx_forms_an_aligned_pair() {
+        use misfit_core::block::breakers::body::BodyProcessor;
+
+        // Core's mutated merkle check only compares positions 2k and 2k + 1
+        for tx_count in 1..=4 {
+            for seed in 0..8 {
+                let (mut block, _) = Generator::random_block(tx_count, Some(seed));
+                BodyProcessor::duplicate_tx(&mut block);
+                let txids: Vec<_> = block.txdata.iter().map(|tx| tx.compute_txid()).collect();
+                let aligned = txids.chunks_exact(2).any(|pair| pair[0] == pair[1]);
+                assert!(aligned, "{tx_count} transactions, seed {seed}");
+                assert!(block.check_merkle_root());
+            }
+        }
+    }
 }
@@ -1,4 +1,10 @@
 use std::str::FromStr;
+
+use crate::Error;
+
+use super::nested::NestedSegwitBreak;
+use super::taproot::TaprootBreak;
+
 #[derive(Debug, PartialEq, Eq, Hash)]
 pub enum InvalidationFlag {
     Version,    
@@ -10,11 +16,67 @@ pub enum InvalidationFlag {
     OutputScriptPubKey,
     WitnessData,
     Locktime,
+    /// Taproot witness breakers, which `All` leaves out
+    Taproot(TaprootBreak),
+    /// Nested segwit breakers, which `All` leaves out
+    NestedSegwit(NestedSegwitBreak),
     All,
 }
 
+impl InvalidationFlag {
+    /// Every flag `All` stands for.
+    pub const FIELDS: [InvalidationFlag; 9] = [
+        Self::Version,
+        Self::InputTxid,
+        Self::InputVout,
+        Self::InputScriptSig,
+        Self::InputSequence,
+        Self::OutputAmount,
+        Self::OutputScriptPubKey,
+        Self::WitnessData,
+        Self::Locktime,
+    ];
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Version => "Transaction Version",
+            Self::InputTxid => "Input TXIDs",
+            Self::InputVout => "Input Vouts",
+            Self::InputScriptSig => "Input Script Signatures",
+            Self::InputSequence => "Input Sequences",
+            Self::OutputAmount => "Output Amounts",
+            Self::OutputScriptPubKey => "Output Script PubKeys",
+            Self::WitnessData => "Witness Data",
+            Self::Locktime => "Locktime",
+            Self::Taproot(breakage) => breakage.description(),
+            Self::NestedSegwit(breakage) => breakage.description(),
+            Self::All => "All Fields",
+        }
+    }
+
+    /// Bitcoin Core reject reason the mutation is expected to trigger.
+    ///
+    /// Sequence, output script and locktime are all committed to by the
+    /// signatures, so changing them shows up as a script failure.
+    pub fn expected_failure(&self) -> Option<&'static str> {
+        match self {
+            Self::Version => Some("version"),
+            Self::InputTxid | Self::InputVout => Some("bad-txns-inputs-missingorspent"),
+            Self::OutputAmount => Some("bad-txns-vout-toolarge"),
+            Self::InputScriptSig
+            | Self::InputSequence
+            | Self::OutputScriptPubKey
+            | Self::WitnessData
+            | Self::Locktime => Some("mandatory-script-verify-flag-failed"),
+            Self::Taproot(breakage) => Some(breakage.expected_failure()),
+            Self::NestedSegwit(breakage) => Some(breakage.expected_failure()),
+            Self::All => None,
+        }
+    }
+}
+
 impl FromStr for InvalidationFlag {
-    type Err = ();
+    type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s.to_lowercase().as_str() {
@@ -28,7 +90,17 @@ impl FromStr for InvalidationFlag {
             "witness" | "witness-data" => Ok(Self::WitnessData),
             "locktime" => Ok(Self::Locktime),
             "all" => Ok(Self::All),
-            _ => Err(()),
+            flag if flag.starts_with("nested-") => flag
+                .strip_prefix("nested-")
+                .and_then(|name| name.parse().ok())
+                .map(Self::NestedSegwit)
+                .ok_or_else(|| Error::InvalidFlag(s.to_string())),
+            flag => flag
+                .strip_prefix("tr-")
+                .and_then(|name| name.parse().ok())
+                .filter(|breakage| TaprootBreak::WITNESS.contains(breakage))
+                .map(Self::Taproot)
+                .ok_or_else(|| Error::InvalidFlag(s.to_string())),
         }
     }
 }
\ No newline at end of file
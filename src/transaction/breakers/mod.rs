@@ -1,7 +1,9 @@
 pub mod flags;
 pub mod input;
 pub mod locktime;
+pub mod nested;
 pub mod output;
 pub mod script;
+pub mod taproot;
 pub mod transaction;
 pub mod version;
\ No newline at end of file
@@ -0,0 +1,130 @@
+use std::str::FromStr;
+
+use bitcoin::{
+    opcodes::all::OP_NOP,
+    script::{Instruction, PushBytesBuf},
+    secp256k1::Secp256k1,
+    PublicKey, ScriptBuf, TxIn, Witness,
+};
+
+use crate::rng;
+
+/// Breakers for segwit nested in P2SH, making the redeem script in the
+/// script_sig and the witness disagree
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NestedSegwitBreak {
+    RedeemScript,
+    WitnessPubkey,
+    WitnessScript,
+}
+
+impl NestedSegwitBreak {
+    pub const ALL: [NestedSegwitBreak; 3] =
+        [Self::RedeemScript, Self::WitnessPubkey, Self::WitnessScript];
+
+    /// Name accepted on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::RedeemScript => "redeem-script",
+            Self::WitnessPubkey => "witness-pubkey",
+            Self::WitnessScript => "witness-script",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::RedeemScript => "Redeem script holding another witness program than the P2SH hash commits to",
+            Self::WitnessPubkey => "P2SH-P2WPKH witness key not hashing to the program",
+            Self::WitnessScript => "P2SH-P2WSH witness script not hashing to the program",
+        }
+    }
+
+    /// Reject reason of the mempool. The P2SH hash is checked before the
+    /// program runs, and a P2WPKH program compares key hashes with
+    /// OP_EQUALVERIFY.
+    pub fn expected_failure(&self) -> &'static str {
+        match self {
+            Self::RedeemScript => {
+                "mandatory-script-verify-flag-failed (Script evaluated without error but finished with a false/empty top stack element)"
+            }
+            Self::WitnessPubkey => {
+                "mandatory-script-verify-flag-failed (Script failed an OP_EQUALVERIFY operation)"
+            }
+            Self::WitnessScript => "mandatory-script-verify-flag-failed (Witness program hash mismatch)",
+        }
+    }
+}
+
+impl FromStr for NestedSegwitBreak {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|breakage| breakage.name() == value)
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::ALL.iter().map(NestedSegwitBreak::name).collect();
+                format!("unknown nested segwit break '{value}', expected one of {}", names.join(", "))
+            })
+    }
+}
+
+/// Processor for modifications of nested segwit inputs
+///
+/// Each mutation returns false, leaving the input alone, when it does not
+/// spend a nested program of the kind it breaks.
+pub struct NestedSegwitProcessor;
+
+impl NestedSegwitProcessor {
+    pub fn process(input: &mut TxIn, breakage: NestedSegwitBreak) -> bool {
+        let Some(program) = Self::redeem_script(&input.script_sig) else {
+            return false;
+        };
+        match breakage {
+            NestedSegwitBreak::RedeemScript => {
+                // Keep the program well formed, just for another hash
+                let mut bytes = program.into_bytes();
+                let last = bytes.len() - 1;
+                bytes[last] ^= 0x01;
+                let push = PushBytesBuf::try_from(bytes).expect("a program fits in a push");
+                input.script_sig = ScriptBuf::builder().push_slice(push).into_script();
+                true
+            }
+            NestedSegwitBreak::WitnessPubkey => {
+                if !program.is_p2wpkh() || input.witness.len() != 2 {
+                    return false;
+                }
+                let pubkey = PublicKey::from_private_key(&Secp256k1::new(), &rng::private_key());
+                Self::replace_last(&mut input.witness, pubkey.to_bytes());
+                true
+            }
+            NestedSegwitBreak::WitnessScript => {
+                let Some(script) = input.witness.last().filter(|_| program.is_p2wsh()) else {
+                    return false;
+                };
+                // A trailing OP_NOP changes the hash, not what the script does
+                let mut script = script.to_vec();
+                script.push(OP_NOP.to_u8());
+                Self::replace_last(&mut input.witness, script);
+                true
+            }
+        }
+    }
+
+    /// Witness program a nested segwit script_sig pushes as its only item
+    pub fn redeem_script(script_sig: &ScriptBuf) -> Option<ScriptBuf> {
+        let mut instructions = script_sig.instructions();
+        let Some(Ok(Instruction::PushBytes(push))) = instructions.next() else {
+            return None;
+        };
+        let program = ScriptBuf::from_bytes(push.as_bytes().to_vec());
+        (instructions.next().is_none() && program.is_witness_program()).then_some(program)
+    }
+
+    fn replace_last(witness: &mut Witness, item: Vec<u8>) {
+        let mut stack = witness.to_vec();
+        stack.pop();
+        stack.push(item);
+        *witness = Witness::from_slice(&stack);
+    }
+}
@@ -0,0 +1,245 @@
+use std::str::FromStr;
+
+use bitcoin::{
+    taproot::{
+        LeafVersion, TAPROOT_CONTROL_BASE_SIZE, TAPROOT_CONTROL_NODE_SIZE,
+        TAPROOT_LEAF_MASK,
+    },
+    Witness,
+};
+use rand::Rng;
+
+use crate::rng;
+use crate::transaction::random::taproot::TapScriptSpend;
+
+/// First byte that makes the last witness item an annex (BIP341).
+pub const ANNEX_TAG: u8 = 0x50;
+
+/// OP_SUCCESS80, the first of the opcodes tapscript reserves as OP_SUCCESSx.
+pub const OP_SUCCESS80: u8 = 0x50;
+
+/// Leaf version no soft fork has given a meaning to yet.
+pub const UNKNOWN_LEAF_VERSION: u8 = 0xc2;
+
+/// Breakers for taproot spends
+///
+/// The control block, annex and signature ones rewrite the witness of a spend
+/// and can run on any transaction. `OpSuccess` and `LeafVersion` change the
+/// leaf the output commits to, so they need the script tree and apply before
+/// the output is created.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TaprootBreak {
+    ControlBlockParity,
+    ControlBlockSize,
+    MerklePathMismatch,
+    Annex,
+    ExplicitSighashDefault,
+    OpSuccess,
+    LeafVersion,
+}
+
+impl TaprootBreak {
+    pub const ALL: [TaprootBreak; 7] = [
+        Self::ControlBlockParity,
+        Self::ControlBlockSize,
+        Self::MerklePathMismatch,
+        Self::Annex,
+        Self::ExplicitSighashDefault,
+        Self::OpSuccess,
+        Self::LeafVersion,
+    ];
+
+    /// Breakers that only rewrite the witness
+    pub const WITNESS: [TaprootBreak; 5] = [
+        Self::ControlBlockParity,
+        Self::ControlBlockSize,
+        Self::MerklePathMismatch,
+        Self::Annex,
+        Self::ExplicitSighashDefault,
+    ];
+
+    /// Name accepted on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::ControlBlockParity => "control-parity",
+            Self::ControlBlockSize => "control-size",
+            Self::MerklePathMismatch => "merkle-path",
+            Self::Annex => "annex",
+            Self::ExplicitSighashDefault => "sighash-default",
+            Self::OpSuccess => "op-success",
+            Self::LeafVersion => "leaf-version",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::ControlBlockParity => "Control block with the output key parity flipped",
+            Self::ControlBlockSize => "Control block one byte longer than a whole merkle path",
+            Self::MerklePathMismatch => "Merkle path not leading to the output key",
+            Self::Annex => "Annex (0x50 prefix) appended after signing",
+            Self::ExplicitSighashDefault => "65-byte Schnorr signatures ending in SIGHASH_DEFAULT",
+            Self::OpSuccess => "Spent leaf ending in OP_SUCCESS80",
+            Self::LeafVersion => "Spent leaf committed with leaf version 0xc2",
+        }
+    }
+
+    /// Script error Bitcoin Core's interpreter stops with. An annex is
+    /// committed to by the signature hash, so the signatures no longer match.
+    pub fn script_error(&self) -> &'static str {
+        match self {
+            Self::ControlBlockParity | Self::MerklePathMismatch => "Witness program hash mismatch",
+            Self::ControlBlockSize => "Invalid Taproot control block size",
+            Self::Annex => "Invalid Schnorr signature",
+            Self::ExplicitSighashDefault => "Invalid Schnorr signature hash type",
+            Self::OpSuccess => "OP_SUCCESSx reserved for soft-fork upgrades",
+            Self::LeafVersion => "Taproot version reserved for soft-fork upgrades",
+        }
+    }
+
+    /// Reject reason of the mempool. Policy refuses an annex before any
+    /// script runs, and OP_SUCCESSx and unknown leaf versions are valid by
+    /// consensus, so only policy rejects them.
+    pub fn expected_failure(&self) -> &'static str {
+        match self {
+            Self::ControlBlockParity | Self::MerklePathMismatch => {
+                "mandatory-script-verify-flag-failed (Witness program hash mismatch)"
+            }
+            Self::ControlBlockSize => {
+                "mandatory-script-verify-flag-failed (Invalid Taproot control block size)"
+            }
+            Self::Annex => "bad-witness-nonstandard",
+            Self::ExplicitSighashDefault => {
+                "mandatory-script-verify-flag-failed (Invalid Schnorr signature hash type)"
+            }
+            Self::OpSuccess => {
+                "non-mandatory-script-verify-flag (OP_SUCCESSx reserved for soft-fork upgrades)"
+            }
+            Self::LeafVersion => {
+                "non-mandatory-script-verify-flag (Taproot version reserved for soft-fork upgrades)"
+            }
+        }
+    }
+}
+
+impl FromStr for TaprootBreak {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|breakage| breakage.name() == value)
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::ALL.iter().map(TaprootBreak::name).collect();
+                format!("unknown taproot break '{value}', expected one of {}", names.join(", "))
+            })
+    }
+}
+
+/// Processor for taproot-aware modifications
+///
+/// Each mutation returns false, leaving its input alone, when it does not
+/// apply, e.g. a control block breaker on a key path spend.
+pub struct TaprootProcessor;
+
+impl TaprootProcessor {
+    /// Apply one of the witness breakers; the others never apply here.
+    pub fn break_witness(witness: &mut Witness, breakage: TaprootBreak) -> bool {
+        let mut stack: Vec<Vec<u8>> = witness.iter().map(<[u8]>::to_vec).collect();
+        let applied = match breakage {
+            TaprootBreak::ControlBlockParity => Self::control_block(&mut stack)
+                .map(|control_block| control_block[0] ^= 0x01)
+                .is_some(),
+            TaprootBreak::ControlBlockSize => Self::control_block(&mut stack)
+                .map(|control_block| control_block.push(rng::rng().random()))
+                .is_some(),
+            TaprootBreak::MerklePathMismatch => Self::control_block(&mut stack)
+                .map(|control_block| {
+                    // A single leaf has no path to corrupt, so give it one
+                    if control_block.len() == TAPROOT_CONTROL_BASE_SIZE {
+                        control_block.extend(rng::rng().random::<[u8; TAPROOT_CONTROL_NODE_SIZE]>());
+                    } else {
+                        let position = control_block.len() - 1;
+                        control_block[position] ^= 0x01;
+                    }
+                })
+                .is_some(),
+            // Only taproot spends read an annex; any other witness program
+            // would just see an extra item
+            TaprootBreak::Annex => {
+                if !Self::is_taproot_spend(&mut stack) || Self::has_annex(&stack) {
+                    false
+                } else {
+                    stack.push(vec![ANNEX_TAG]);
+                    true
+                }
+            }
+            TaprootBreak::ExplicitSighashDefault => {
+                // Signatures sit below the script and control block
+                let mut end = stack.len() - Self::has_annex(&stack) as usize;
+                if Self::control_block(&mut stack).is_some() {
+                    end -= 2;
+                }
+                let mut applied = false;
+                for item in stack[..end].iter_mut().filter(|item| item.len() == 64) {
+                    item.push(0x00);
+                    applied = true;
+                }
+                applied
+            }
+            TaprootBreak::OpSuccess | TaprootBreak::LeafVersion => false,
+        };
+
+        if applied {
+            *witness = Witness::from_slice(&stack);
+        }
+        applied
+    }
+
+    /// Apply one of the tree breakers to the leaf `spend` goes through; the
+    /// others never apply here.
+    pub fn break_tree(spend: &mut TapScriptSpend, breakage: TaprootBreak) -> bool {
+        let leaf = &mut spend.leaves[spend.leaf_index];
+        match breakage {
+            TaprootBreak::OpSuccess => {
+                let mut script = leaf.script.to_bytes();
+                script.push(OP_SUCCESS80);
+                leaf.script = script.into();
+                true
+            }
+            TaprootBreak::LeafVersion => {
+                leaf.version = LeafVersion::from_consensus(UNKNOWN_LEAF_VERSION)
+                    .expect("0xc2 is an even leaf version other than 0x50");
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // BIP341 reads the last item as an annex when it starts with 0x50 and
+    // is not the only one
+    fn has_annex(stack: &[Vec<u8>]) -> bool {
+        stack.len() >= 2 && stack.last().is_some_and(|item| item.first() == Some(&ANNEX_TAG))
+    }
+
+    // A key path spend is a lone 64 or 65-byte Schnorr signature, a script
+    // path spend ends in a control block
+    fn is_taproot_spend(stack: &mut [Vec<u8>]) -> bool {
+        let key_path = stack.len() == 1 && matches!(stack[0].len(), 64 | 65);
+        key_path || Self::control_block(stack).is_some()
+    }
+
+    // Control block of a script path spend: the last item before the annex,
+    // when a script precedes it and its size fits a merkle path. The public
+    // key ending a P2WPKH witness has that size too, but its first byte
+    // reads as leaf version 0x02.
+    fn control_block(stack: &mut [Vec<u8>]) -> Option<&mut Vec<u8>> {
+        let end = stack.len() - Self::has_annex(stack) as usize;
+        if end < 2 {
+            return None;
+        }
+        let control_block = &mut stack[end - 1];
+        let path = control_block.len().checked_sub(TAPROOT_CONTROL_BASE_SIZE)?;
+        let leaf_version = control_block[0] & TAPROOT_LEAF_MASK;
+        (path % TAPROOT_CONTROL_NODE_SIZE == 0 && leaf_version != 0x02).then_some(control_block)
+    }
+}
@@ -1,7 +1,10 @@
 use std::collections::HashSet;
 use std::str::FromStr;
 use bitcoin::{Transaction, consensus::deserialize};
+use crate::{Error, Result};
 use super::{flags::InvalidationFlag, input::*, output::*, version::*, locktime::*};
+use super::nested::{NestedSegwitBreak, NestedSegwitProcessor};
+use super::taproot::{TaprootBreak, TaprootProcessor};
 
 #[derive(Default)]
 pub struct TransactionInvalidator;
@@ -11,7 +14,9 @@ pub struct BitcoinTransactionDecoder;
 
 
 impl TransactionInvalidator {
-    pub fn invalidate(mut tx: Transaction, flags: &HashSet<InvalidationFlag>) -> Transaction {
+    /// Fails when a taproot or nested segwit breaker finds no input to
+    /// apply to, rather than reporting a mutation it did not make.
+    pub fn invalidate(mut tx: Transaction, flags: &HashSet<InvalidationFlag>) -> Result<Transaction> {
         let should_invalidate_all = flags.contains(&InvalidationFlag::All);
         
         // Invalidate transaction structure (affects txid) - do this first
@@ -38,8 +43,37 @@ impl TransactionInvalidator {
         for output in tx.output.iter_mut() {
             invalidate_output_in_place(output, flags, should_invalidate_all);
         }
+
+        // Taproot breakers, in a fixed order as an annex hides the control block
+        for breakage in TaprootBreak::WITNESS {
+            if flags.contains(&InvalidationFlag::Taproot(breakage)) {
+                let mut applied = false;
+                for input in tx.input.iter_mut() {
+                    applied |= TaprootProcessor::break_witness(&mut input.witness, breakage);
+                }
+                if !applied {
+                    return Err(Self::not_applicable(breakage.name()));
+                }
+            }
+        }
+
+        for breakage in NestedSegwitBreak::ALL {
+            if flags.contains(&InvalidationFlag::NestedSegwit(breakage)) {
+                let mut applied = false;
+                for input in tx.input.iter_mut() {
+                    applied |= NestedSegwitProcessor::process(input, breakage);
+                }
+                if !applied {
+                    return Err(Self::not_applicable(breakage.name()));
+                }
+            }
+        }
         
-        tx
+        Ok(tx)
+    }
+
+    fn not_applicable(name: &str) -> Error {
+        Error::InvalidConfig(format!("cannot apply {name} to any input of the transaction"))
     }
 
     // Helper methods
@@ -64,14 +98,14 @@ impl BitcoinTransactionDecoder {
     }
 
     /// Decode a hex string directly to bitcoin::Transaction
-    pub fn decode_hex(&self, hex_string: &str) -> Result<Transaction, Box<dyn std::error::Error>> {
+    pub fn decode_hex(&self, hex_string: &str) -> Result<Transaction> {
         let clean_hex = hex_string.trim().replace(" ", "").to_lowercase();
         let bytes = hex::decode(&clean_hex)?;
         self.decode_bytes(&bytes)
     }
 
     /// Decode bytes directly to bitcoin::Transaction
-    pub fn decode_bytes(&self, bytes: &[u8]) -> Result<Transaction, Box<dyn std::error::Error>> {
+    pub fn decode_bytes(&self, bytes: &[u8]) -> Result<Transaction> {
         let tx: Transaction = deserialize(bytes)?;
         Ok(tx)
     }
@@ -94,52 +128,52 @@ impl BitcoinTransactionDecoder {
 
 
 
-pub fn parse_flags(args: Vec<String>) -> HashSet<InvalidationFlag> {
+/// Parse program arguments (the first one is the program name) into flags.
+///
+/// Unknown flags are rejected; see `usage` for the accepted ones.
+pub fn parse_flags(args: Vec<String>) -> Result<HashSet<InvalidationFlag>> {
     let mut flags = HashSet::new();
 
-    // Display help and exit if requested
-    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
-        print_usage();
-        std::process::exit(0);
-    }
-
-    // No flags provided - show help
-    if args.len() <= 1 {
-        print_usage();
-        std::process::exit(1);
-    }
-
     // Parse all provided flags
     for arg in args.iter().skip(1) {
-    if !arg.starts_with("--") {
-        continue;
+        let flag = arg
+            .strip_prefix("--")
+            .and_then(|flag_str| InvalidationFlag::from_str(flag_str).ok())
+            .ok_or_else(|| Error::InvalidFlag(arg.clone()))?;
+        flags.insert(flag);
     }
 
-    let flag_str = &arg[2..]; 
-    if let Ok(flag) = InvalidationFlag::from_str(flag_str) {
-        flags.insert(flag);
-    } else {
-        println!("Warning: Unknown flag '{arg}' ignored");
+    if flags.is_empty() {
+        return Err(Error::NoFlags);
     }
-}
 
-    flags
+    Ok(flags)
 }
 
-fn print_usage() {
-    println!("Bitcoin Transaction Invalidator");
-    println!("Usage: btc-invalidator [FLAGS]");
-    println!("\nAvailable flags:");
-    println!("  --all           Invalidate all transaction fields");
-    println!("  --version       Invalidate transaction version");
-    println!("  --txid          Invalidate input transaction ID");
-    println!("  --vout          Invalidate input vout");
-    println!("  --script-sig    Invalidate input script signature");
-    println!("  --sequence      Invalidate input sequence number");
-    println!("  --amount        Invalidate output amount");
-    println!("  --script-pubkey Invalidate output script pubkey");
-    println!("  --witness       Invalidate witness data");
-    println!("  --locktime      Invalidate transaction locktime");
-    println!("  --help          Print this help message");
-    println!("\nExample: btc-invalidator --txid --amount --locktime");
-}
\ No newline at end of file
+pub fn usage() -> String {
+    [
+        "Bitcoin Transaction Invalidator",
+        "Usage: btc-invalidator [FLAGS]",
+        "\nAvailable flags:",
+        "  --all           Invalidate all transaction fields",
+        "  --version       Invalidate transaction version",
+        "  --txid          Invalidate input transaction ID",
+        "  --vout          Invalidate input vout",
+        "  --script-sig    Invalidate input script signature",
+        "  --sequence      Invalidate input sequence number",
+        "  --amount        Invalidate output amount",
+        "  --script-pubkey Invalidate output script pubkey",
+        "  --witness       Invalidate witness data",
+        "  --locktime      Invalidate transaction locktime",
+        "  --tr-control-parity  Flip the output key parity of taproot control blocks",
+        "  --tr-control-size    Grow taproot control blocks by one byte",
+        "  --tr-merkle-path     Corrupt the merkle path of taproot control blocks",
+        "  --tr-annex           Append an annex to taproot witnesses",
+        "  --tr-sighash-default Append SIGHASH_DEFAULT to 64-byte Schnorr signatures",
+        "  --nested-redeem-script  Push another witness program than the P2SH hash commits to",
+        "  --nested-witness-pubkey Swap the P2SH-P2WPKH witness key for another",
+        "  --nested-witness-script Alter the P2SH-P2WSH witness script",
+        "\nExample: btc-invalidator --txid --amount --locktime",
+    ]
+    .join("\n")
+}
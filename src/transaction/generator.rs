@@ -1,10 +1,17 @@
-use super::random::transaction::{RandomTransacion, TxParams};
+use super::random::transaction::{RandomTransacion, TxInfo, TxParams};
 use bitcoin::Transaction;
 
+use crate::Result;
+
 pub struct GenerateTx {}
 
 impl GenerateTx {
-    pub fn valid_random(params: TxParams) -> Transaction {
+    pub fn valid_random(params: TxParams) -> Result<Transaction> {
         Transaction::random(params)
     }
+
+    /// Like `valid_random`, also returning the outputs spent by each input.
+    pub fn valid_random_with_info(params: TxParams) -> Result<TxInfo> {
+        Transaction::random_with_info(params)
+    }
 }
\ No newline at end of file
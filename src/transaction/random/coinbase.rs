@@ -0,0 +1,103 @@
+use bitcoin::{
+    absolute::LockTime, transaction::Version, Amount, Network, OutPoint, PrivateKey, ScriptBuf,
+    Sequence, Transaction, TxIn, TxOut, Witness,
+};
+use rand::Rng;
+
+use crate::rng;
+
+use super::{
+    output::{OutputParams, RandomOutput},
+    script::{ScriptParams, ScriptTypes},
+};
+
+/// Subsidy of the genesis block, halved every halving interval.
+const INITIAL_SUBSIDY: Amount = Amount::from_sat(50 * 100_000_000);
+
+#[derive(Default, Clone)]
+pub struct CoinbaseParams {
+    /// Height pushed first by the script_sig (BIP34); defaults to a random one.
+    pub height: Option<u32>,
+    /// Network whose halving schedule sets the subsidy; defaults to regtest.
+    pub network: Option<Network>,
+    /// Fees of the other transactions of the block, claimed on top of the subsidy.
+    pub fees: Option<Amount>,
+    /// Params of the single output; its value is always subsidy plus fees.
+    /// Defaults to P2WPKH paying to `private_key`.
+    pub output: Option<OutputParams>,
+    pub private_key: Option<PrivateKey>,
+    /// Add the 32-byte witness reserved value a witness commitment needs.
+    pub segwit: bool,
+}
+
+/// Confirmations a coinbase output needs before it can be spent.
+pub const COINBASE_MATURITY: u32 = 100;
+
+/// Blocks between subsidy halvings.
+pub fn halving_interval(network: Network) -> u32 {
+    match network {
+        Network::Regtest => 150,
+        _ => 210_000,
+    }
+}
+
+/// New coins a block at `height` may create, as in Bitcoin Core's
+/// `GetBlockSubsidy`.
+pub fn block_subsidy(height: u32, network: Network) -> Amount {
+    let halvings = height / halving_interval(network);
+    if halvings >= 64 {
+        return Amount::ZERO;
+    }
+    Amount::from_sat(INITIAL_SUBSIDY.to_sat() >> halvings)
+}
+
+pub trait RandomCoinbase {
+    fn random_coinbase(params: CoinbaseParams) -> Transaction;
+}
+
+impl RandomCoinbase for Transaction {
+    fn random_coinbase(params: CoinbaseParams) -> Transaction {
+        let height = params
+            .height
+            .unwrap_or_else(|| rng::rng().random_range(1..10_000_000));
+        let network = params.network.unwrap_or(Network::Regtest);
+        let private_key = params.private_key.unwrap_or_else(rng::private_key);
+
+        // Height first, then a few extra bytes so even OP_1..OP_16 heights
+        // meet the 2-byte minimum script_sig length.
+        let extra_nonce: [u8; 4] = rng::rng().random();
+        let script_sig = ScriptBuf::builder()
+            .push_int(height as i64)
+            .push_slice(extra_nonce)
+            .into_script();
+
+        let witness = if params.segwit {
+            Witness::from_slice(&[[0u8; 32]])
+        } else {
+            Witness::new()
+        };
+
+        let mut output_params = params.output.unwrap_or_else(|| OutputParams {
+            script_params: Some(ScriptParams {
+                script_type: Some(ScriptTypes::P2WPKH),
+                private_key: Some(private_key),
+            }),
+            ..Default::default()
+        });
+        output_params.private_key.get_or_insert(private_key);
+        output_params.value =
+            Some(block_subsidy(height, network) + params.fees.unwrap_or(Amount::ZERO));
+
+        Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: OutPoint::null(),
+                script_sig,
+                sequence: Sequence::MAX,
+                witness,
+            }],
+            output: vec![TxOut::random(output_params).0],
+        }
+    }
+}
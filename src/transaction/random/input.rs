@@ -1,17 +1,18 @@
 use bitcoin::{
-    hashes::Hash, NetworkKind, OutPoint, PrivateKey, ScriptBuf, Sequence, Transaction, TxIn, Txid,
-    Witness,
+    hashes::Hash, Amount, OutPoint, PrivateKey, ScriptBuf, Sequence, Transaction, TxIn,
+    TxOut, Txid, Witness,
 };
-use secp256k1::rand::{self, Rng};
+use rand::Rng;
 
-use crate::transaction::random::witness::{RandomWitness, WitnessParams};
+use crate::rng;
 
 use super::{
+    output::random_amount,
     script::{RandomScript, ScriptParams, ScriptTypes},
     transaction::{RandomTransacion, TxParams},
 };
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct InputParams {
     pub outpoint: Option<OutPoint>,
     pub script: Option<(ScriptBuf, ScriptTypes)>,
@@ -19,72 +20,103 @@ pub struct InputParams {
     pub witness: Option<Witness>,
     pub script_params: Option<ScriptParams>,
     pub private_key: Option<PrivateKey>,
+    /// Value of the previous output being spent.
+    pub value: Option<Amount>,
+}
+
+/// A generated input together with the previous output it spends.
+///
+/// Signatures commit to the whole spending transaction, so the input is left
+/// unsigned here and signed by `Transaction::random` once every input and
+/// output is in place.
+#[derive(Debug, Clone)]
+pub struct InputInfo {
+    pub txin: TxIn,
+    pub prevout: TxOut,
+    pub script_type: ScriptTypes,
+    pub private_key: PrivateKey,
+    pub needs_signature: bool,
 }
 
 pub trait RandomInput {
-    fn random(params: InputParams) -> TxIn;
+    fn random(params: InputParams) -> InputInfo;
 }
 
 impl RandomInput for TxIn {
-    fn random(params: InputParams) -> TxIn {
-        let mut witness_params = WitnessParams::default();
-
+    fn random(params: InputParams) -> InputInfo {
         let private_key = params
             .private_key
-            .unwrap_or_else(|| PrivateKey::generate(NetworkKind::Main));
-        witness_params.private_key = Some(private_key);
+            .unwrap_or_else(rng::private_key);
 
         let (script_buf, script_type) = params.script.unwrap_or_else(|| {
-            ScriptBuf::random(params.script_params.unwrap_or(ScriptParams {
-                script_type: None,
-                private_key: Some(private_key),
-            }))
+            let mut script_params = params.script_params.unwrap_or_default();
+            script_params.private_key.get_or_insert(private_key);
+            ScriptBuf::random(script_params)
         });
-        witness_params.script = Some((script_buf.clone(), script_type.clone()));
 
-        let outpoint = params.outpoint.unwrap_or_else(|| {
-            let mut random_tx_params = TxParams::default();
-            let random_input_params = InputParams {
-                witness: Some(Witness::default()),
-                outpoint: Some(OutPoint {
-                    txid: Txid::all_zeros(),
-                    vout: rand::thread_rng().gen::<u32>(),
-                }),
-                ..Default::default()
-            };
+        let value = params.value.unwrap_or_else(random_amount);
 
-            random_tx_params.input = Some(random_input_params);
+        let outpoint = params
+            .outpoint
+            .unwrap_or_else(|| funding_outpoint(&script_buf, value));
 
-            let random_input_tx = Transaction::random(random_tx_params);
+        let prevout = TxOut {
+            value,
+            script_pubkey: script_buf.clone(),
+        };
 
-            witness_params.transaction = Some(random_input_tx.clone());
-
-            let vout = rand::thread_rng().gen_range(0..random_input_tx.output.len());
-            witness_params.vout = Some(vout);
-
-            OutPoint {
-                txid: random_input_tx.compute_txid(),
-                vout: vout.try_into().unwrap(),
-            }
-        });
-
-        let witness = params.witness.unwrap_or_else(|| match script_type {
-            ScriptTypes::P2WPKH
-            | ScriptTypes::P2WSH
-            | ScriptTypes::P2TR
-            | ScriptTypes::P2TWEAKEDTR => Witness::random(witness_params),
-            _ => Witness::default(),
-        });
+        // Coinbase inputs and inputs with a caller-provided witness are kept as is.
+        let needs_signature = params.witness.is_none() && !outpoint.is_null();
+        let script_sig = if needs_signature {
+            ScriptBuf::new()
+        } else {
+            script_buf
+        };
 
         let sequence = params
             .sequence
-            .unwrap_or_else(|| Sequence(rand::thread_rng().gen::<u32>()));
+            .unwrap_or_else(|| Sequence(rng::rng().random::<u32>()));
 
-        TxIn {
+        let txin = TxIn {
             previous_output: outpoint,
-            script_sig: script_buf,
+            script_sig,
             sequence,
-            witness,
+            witness: params.witness.unwrap_or_default(),
+        };
+
+        InputInfo {
+            txin,
+            prevout,
+            script_type,
+            private_key,
+            needs_signature,
         }
     }
 }
+
+/// Output of a fabricated funding transaction paying `value` to
+/// `script_pubkey`. Its txid commits to the prevout we sign against, so the
+/// outpoint changes with the value.
+pub fn funding_outpoint(script_pubkey: &ScriptBuf, value: Amount) -> OutPoint {
+    let random_tx_params = TxParams {
+        input: Some(InputParams {
+            witness: Some(Witness::default()),
+            outpoint: Some(OutPoint {
+                txid: Txid::all_zeros(),
+                vout: rng::rng().random::<u32>(),
+            }),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let mut random_input_tx = Transaction::random(random_tx_params).expect("inputs of random value cover any fee");
+    let vout = rng::rng().random_range(0..random_input_tx.output.len());
+    random_input_tx.output[vout].script_pubkey = script_pubkey.clone();
+    random_input_tx.output[vout].value = value;
+
+    OutPoint {
+        txid: random_input_tx.compute_txid(),
+        vout: vout.try_into().unwrap(),
+    }
+}
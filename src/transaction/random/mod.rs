@@ -1,7 +1,11 @@
+pub mod coinbase;
 pub mod input;
 pub mod locktime;
+pub mod multisig;
 pub mod output;
 pub mod script;
+pub mod script_sig;
+pub mod taproot;
 pub mod transaction;
 pub mod version;
 pub mod witness;
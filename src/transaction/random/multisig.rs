@@ -0,0 +1,178 @@
+use std::str::FromStr;
+
+use bitcoin::{
+    ecdsa::Signature,
+    opcodes::{all::OP_CHECKMULTISIG, OP_0},
+    script::{Builder, PushBytesBuf},
+    secp256k1::{Message, Secp256k1},
+    sighash::{EcdsaSighashType, SighashCache},
+    Amount, PrivateKey, PublicKey, ScriptBuf, Transaction, Witness,
+};
+
+use crate::rng;
+
+/// Most keys OP_CHECKMULTISIG accepts.
+pub const MAX_PUBKEYS_PER_MULTISIG: usize = 20;
+
+/// Most keys a P2SH redeem script fits in its 520-byte push.
+pub const MAX_P2SH_MULTISIG_KEYS: usize = 15;
+
+/// How an m-of-n multisig script is paid to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MultisigWrapping {
+    /// The script itself as the output
+    Bare,
+    /// Hashed into a P2SH output, revealed as the redeem script
+    P2SH,
+    /// Hashed into a P2WSH output, revealed as the witness script
+    P2WSH,
+    /// P2WSH program hashed into a P2SH output
+    P2SHP2WSH,
+}
+
+impl MultisigWrapping {
+    pub const ALL: [MultisigWrapping; 4] = [Self::Bare, Self::P2SH, Self::P2WSH, Self::P2SHP2WSH];
+
+    /// Name accepted on the command line
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Bare => "bare",
+            Self::P2SH => "p2sh",
+            Self::P2WSH => "p2wsh",
+            Self::P2SHP2WSH => "p2sh-p2wsh",
+        }
+    }
+
+    /// Whether the signatures go in the witness rather than the script_sig
+    pub fn is_segwit(&self) -> bool {
+        matches!(self, Self::P2WSH | Self::P2SHP2WSH)
+    }
+}
+
+impl FromStr for MultisigWrapping {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|wrapping| wrapping.name() == value)
+            .ok_or_else(|| {
+                let names: Vec<_> = Self::ALL.iter().map(MultisigWrapping::name).collect();
+                format!("unknown multisig wrapping '{value}', expected one of {}", names.join(", "))
+            })
+    }
+}
+
+/// An m-of-n OP_CHECKMULTISIG script with its keys
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Multisig {
+    /// Keys in the order the script lists them.
+    pub keys: Vec<PrivateKey>,
+    /// How many of the keys, from the first, sign a spend.
+    pub threshold: usize,
+    pub wrapping: MultisigWrapping,
+}
+
+impl Multisig {
+    /// `threshold` of `keys` fresh keys
+    pub fn new(threshold: usize, keys: usize, wrapping: MultisigWrapping) -> Self {
+        let keys = (0..keys.max(1)).map(|_| rng::private_key()).collect();
+        Self { keys, threshold, wrapping }
+    }
+
+    /// 1-of-1 of `key`, which the plain P2SH and P2WSH script types pay to
+    pub fn single(key: PrivateKey, wrapping: MultisigWrapping) -> Self {
+        Self { keys: vec![key], threshold: 1, wrapping }
+    }
+
+    /// `OP_m <pubkeys> OP_n OP_CHECKMULTISIG`, the redeem or witness script
+    pub fn script(&self) -> ScriptBuf {
+        let secp = Secp256k1::new();
+        let mut builder = Builder::new().push_int(self.threshold as i64);
+        for key in &self.keys {
+            builder = builder.push_key(&PublicKey::from_private_key(&secp, key));
+        }
+        builder
+            .push_int(self.keys.len() as i64)
+            .push_opcode(OP_CHECKMULTISIG)
+            .into_script()
+    }
+
+    /// Redeem script of the P2SH wrappings: the multisig script, or the
+    /// P2WSH program paying to it.
+    pub fn redeem_script(&self) -> Option<ScriptBuf> {
+        match self.wrapping {
+            MultisigWrapping::Bare | MultisigWrapping::P2WSH => None,
+            MultisigWrapping::P2SH => Some(self.script()),
+            MultisigWrapping::P2SHP2WSH => Some(ScriptBuf::new_p2wsh(&self.script().wscript_hash())),
+        }
+    }
+
+    pub fn script_pubkey(&self) -> ScriptBuf {
+        match self.wrapping {
+            MultisigWrapping::Bare => self.script(),
+            MultisigWrapping::P2WSH => ScriptBuf::new_p2wsh(&self.script().wscript_hash()),
+            MultisigWrapping::P2SH | MultisigWrapping::P2SHP2WSH => {
+                let redeem_script = self.redeem_script().expect("P2SH wrappings have one");
+                ScriptBuf::new_p2sh(&redeem_script.script_hash())
+            }
+        }
+    }
+
+    /// Signed script_sig: `OP_0 <sigs>` for bare outputs, followed by the
+    /// redeem script push for P2SH, and only that push for P2SH-P2WSH.
+    pub fn script_sig(&self, transaction: &Transaction, input_index: usize) -> ScriptBuf {
+        let mut builder = Builder::new();
+        if !self.wrapping.is_segwit() {
+            let sighash = SighashCache::new(transaction)
+                .legacy_signature_hash(input_index, &self.script(), EcdsaSighashType::All.to_u32())
+                .unwrap();
+            // OP_CHECKMULTISIG pops one item more than it needs
+            builder = builder.push_opcode(OP_0);
+            for sig in self.signatures(&sighash[..]) {
+                builder = builder.push_slice(sig.serialize());
+            }
+        }
+        if let Some(redeem_script) = self.redeem_script() {
+            let push = PushBytesBuf::try_from(redeem_script.into_bytes())
+                .expect("redeem scripts fit in a push");
+            builder = builder.push_slice(push);
+        }
+        builder.into_script()
+    }
+
+    /// Signed witness of the segwit wrappings: an empty item for the extra
+    /// pop, the signatures and the witness script.
+    pub fn witness(&self, transaction: &Transaction, input_index: usize, amount: Amount) -> Witness {
+        if !self.wrapping.is_segwit() {
+            return Witness::new();
+        }
+        let script = self.script();
+        let sighash = SighashCache::new(transaction)
+            .p2wsh_signature_hash(input_index, &script, amount, EcdsaSighashType::All)
+            .unwrap();
+
+        let mut witness = Witness::new();
+        witness.push([]);
+        for sig in self.signatures(&sighash[..]) {
+            witness.push(sig.to_vec());
+        }
+        witness.push(script.as_bytes());
+        witness
+    }
+
+    // Signatures of the first `threshold` keys, in the order the script
+    // lists them, as OP_CHECKMULTISIG matches them in order
+    fn signatures(&self, sighash: &[u8]) -> Vec<Signature> {
+        let secp = Secp256k1::new();
+        let message = Message::from_digest_slice(sighash).unwrap();
+        self.keys
+            .iter()
+            .take(self.threshold)
+            .map(|key| Signature {
+                signature: secp.sign_ecdsa(&message, &key.inner),
+                sighash_type: EcdsaSighashType::All,
+            })
+            .collect()
+    }
+}
@@ -1,9 +1,11 @@
-use bitcoin::{Amount, NetworkKind, PrivateKey, ScriptBuf, TxOut};
-use secp256k1::rand::{self, Rng};
+use bitcoin::{Amount, PrivateKey, ScriptBuf, TxOut};
+use rand::Rng;
+
+use crate::rng;
 
 use super::script::{RandomScript, ScriptParams, ScriptTypes};
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct OutputParams {
     pub value: Option<Amount>,
     pub script_params: Option<ScriptParams>,
@@ -15,6 +17,11 @@ pub struct OutputInfo {
     pub script_type: ScriptTypes,
 }
 
+/// Random amount between 1,000 sats and 1 BTC, far enough from MAX_MONEY
+/// that summing a handful of them never overflows it.
+pub fn random_amount() -> Amount {
+    Amount::from_sat(rng::rng().random_range(1_000..=100_000_000))
+}
 
 pub trait RandomOutput {
     fn random(params: OutputParams) -> (TxOut, ScriptTypes);
@@ -24,11 +31,11 @@ impl RandomOutput for TxOut {
     fn random(params: OutputParams) -> (TxOut, ScriptTypes) {
         let amount = params
             .value
-            .unwrap_or_else(|| Amount::from_sat(rand::thread_rng().gen::<u64>()));
+            .unwrap_or_else(random_amount);
 
         let private_key = params
             .private_key
-            .unwrap_or_else(|| PrivateKey::generate(NetworkKind::Main));
+            .unwrap_or_else(rng::private_key);
 
         let script_params = params.script_params.unwrap_or(ScriptParams {
             script_type: None,
@@ -1,23 +1,64 @@
 use bitcoin::{
-    hashes::Hash,
     key::{Keypair, TweakedKeypair, TweakedPublicKey},
     secp256k1::Secp256k1,
-    NetworkKind, PrivateKey, PublicKey, ScriptBuf, ScriptHash, WScriptHash, XOnlyPublicKey,
+    PrivateKey, PublicKey, ScriptBuf, XOnlyPublicKey,
 };
-use secp256k1::rand::{self, Rng};
+use rand::Rng;
 
-#[derive(Debug, Clone)]
+use crate::rng;
+
+use super::multisig::{Multisig, MultisigWrapping};
+use super::taproot::TapScriptSpend;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ScriptTypes {
     P2PK,
     P2PKH,
+    /// P2SH paying to a 1-of-1 multisig of the key
     P2SH,
     P2TR,
     P2TWEAKEDTR,
+    /// P2TR output committing to a script tree, spent through one of its leaves
+    P2TRSCRIPTPATH(TapScriptSpend),
     P2WPKH,
+    /// P2WSH paying to a 1-of-1 multisig of the key
     P2WSH,
+    /// P2WPKH program wrapped in P2SH
+    P2SHP2WPKH,
+    /// P2WSH program paying to a 1-of-1 multisig of the key, wrapped in P2SH
+    P2SHP2WSH,
+    /// m-of-n multisig, bare or wrapped in P2SH, P2WSH or both
+    MULTISIG(Multisig),
+}
+
+impl ScriptTypes {
+    /// Multisig script the type pays to, with `private_key` as the key of
+    /// the plain P2SH and P2WSH types
+    pub fn multisig(&self, private_key: PrivateKey) -> Option<Multisig> {
+        match self {
+            Self::P2SH => Some(Multisig::single(private_key, MultisigWrapping::P2SH)),
+            Self::P2WSH => Some(Multisig::single(private_key, MultisigWrapping::P2WSH)),
+            Self::P2SHP2WSH => Some(Multisig::single(private_key, MultisigWrapping::P2SHP2WSH)),
+            Self::MULTISIG(multisig) => Some(multisig.clone()),
+            _ => None,
+        }
+    }
+
+    /// Script a P2SH output of the type commits to, revealed in the
+    /// script_sig; the witness program for nested segwit
+    pub fn redeem_script(&self, private_key: PrivateKey) -> Option<ScriptBuf> {
+        match self {
+            Self::P2SHP2WPKH => Some(ScriptBuf::new_p2wpkh(
+                &PublicKey::from_private_key(&Secp256k1::new(), &private_key)
+                    .wpubkey_hash()
+                    .unwrap(),
+            )),
+            _ => self.multisig(private_key)?.redeem_script(),
+        }
+    }
 }
 
-#[derive(Default)]
+#[derive(Default, Clone)]
 pub struct ScriptParams {
     pub script_type: Option<ScriptTypes>,
     pub private_key: Option<PrivateKey>,
@@ -32,19 +73,22 @@ impl RandomScript for ScriptBuf {
         let script_type =
             params
                 .script_type
-                .unwrap_or_else(|| match rand::thread_rng().gen_range(0..6) {
+                .unwrap_or_else(|| match rng::rng().random_range(0..9) {
                     0 => ScriptTypes::P2PK,
                     1 => ScriptTypes::P2PKH,
                     2 => ScriptTypes::P2SH,
                     3 => ScriptTypes::P2TR,
                     4 => ScriptTypes::P2TWEAKEDTR,
                     5 => ScriptTypes::P2WPKH,
-                    _ => ScriptTypes::P2WSH,
+                    6 => ScriptTypes::P2SHP2WPKH,
+                    7 => ScriptTypes::P2SHP2WSH,
+                    8 => ScriptTypes::P2WSH,
+                    _ => unreachable!(),
                 });
 
         let private_key = params
             .private_key
-            .unwrap_or_else(|| PrivateKey::generate(NetworkKind::Main));
+            .unwrap_or_else(rng::private_key);
 
         let script = match script_type {
             ScriptTypes::P2PK => ScriptBuf::new_p2pk(&PublicKey::from_private_key(
@@ -54,27 +98,39 @@ impl RandomScript for ScriptBuf {
             ScriptTypes::P2PKH => ScriptBuf::new_p2pkh(
                 &PublicKey::from_private_key(&Secp256k1::new(), &private_key).pubkey_hash(),
             ),
-            ScriptTypes::P2SH => ScriptBuf::new_p2sh(&ScriptHash::all_zeros()),
             ScriptTypes::P2TR => ScriptBuf::new_p2tr(
                 &Secp256k1::new(),
-                XOnlyPublicKey::from_keypair(&Keypair::new(
+                XOnlyPublicKey::from_keypair(&Keypair::from_secret_key(
                     &Secp256k1::new(),
-                    &mut rand::thread_rng(),
+                    &private_key.inner,
                 ))
                 .0,
                 None,
             ),
             ScriptTypes::P2TWEAKEDTR => ScriptBuf::new_p2tr_tweaked(
                 TweakedPublicKey::from_keypair(TweakedKeypair::dangerous_assume_tweaked(
-                    Keypair::new(&Secp256k1::new(), &mut rand::thread_rng()),
+                    Keypair::from_secret_key(&Secp256k1::new(), &private_key.inner),
                 )),
             ),
+            ScriptTypes::P2TRSCRIPTPATH(ref spend) => spend.script_pubkey(),
             ScriptTypes::P2WPKH => ScriptBuf::new_p2wpkh(
                 &PublicKey::from_private_key(&Secp256k1::new(), &private_key)
                     .wpubkey_hash()
                     .unwrap(),
             ),
-            ScriptTypes::P2WSH => ScriptBuf::new_p2wsh(&WScriptHash::all_zeros()),
+            ScriptTypes::P2SHP2WPKH => ScriptBuf::new_p2sh(
+                &script_type
+                    .redeem_script(private_key)
+                    .expect("nested segwit has a redeem script")
+                    .script_hash(),
+            ),
+            ScriptTypes::P2SH
+            | ScriptTypes::P2WSH
+            | ScriptTypes::P2SHP2WSH
+            | ScriptTypes::MULTISIG(_) => script_type
+                .multisig(private_key)
+                .expect("the type pays to a multisig")
+                .script_pubkey(),
         };
         (script, script_type)
     }
@@ -0,0 +1,53 @@
+use bitcoin::{
+    ecdsa::Signature,
+    script::PushBytesBuf,
+    secp256k1::{Message, Secp256k1},
+    sighash::{EcdsaSighashType, SighashCache},
+    PrivateKey, PublicKey, ScriptBuf, Transaction,
+};
+
+use super::script::ScriptTypes;
+
+/// Build the signed `script_sig` for a legacy (pre-segwit) input, or the
+/// redeem script push of a P2SH-wrapped segwit one.
+///
+/// Returns an empty script for types that are not spent through `script_sig`.
+pub fn sign_legacy_input(
+    transaction: &Transaction,
+    input_index: usize,
+    script_pubkey: &ScriptBuf,
+    script_type: &ScriptTypes,
+    private_key: &PrivateKey,
+) -> ScriptBuf {
+    if let Some(multisig) = script_type.multisig(*private_key) {
+        return multisig.script_sig(transaction, input_index);
+    }
+    if let Some(redeem_script) = script_type.redeem_script(*private_key) {
+        let push = PushBytesBuf::try_from(redeem_script.into_bytes())
+            .expect("redeem scripts fit in a push");
+        return ScriptBuf::builder().push_slice(push).into_script();
+    }
+    if !matches!(script_type, ScriptTypes::P2PK | ScriptTypes::P2PKH) {
+        return ScriptBuf::new();
+    }
+
+    let sighash = SighashCache::new(transaction)
+        .legacy_signature_hash(input_index, script_pubkey, EcdsaSighashType::All.to_u32())
+        .unwrap();
+
+    let sig = Signature {
+        signature: Secp256k1::new().sign_ecdsa(
+            &Message::from_digest_slice(&sighash[..]).unwrap(),
+            &private_key.inner,
+        ),
+        sighash_type: EcdsaSighashType::All,
+    };
+
+    let builder = ScriptBuf::builder().push_slice(sig.serialize());
+    match script_type {
+        ScriptTypes::P2PKH => builder
+            .push_key(&PublicKey::from_private_key(&Secp256k1::new(), private_key))
+            .into_script(),
+        _ => builder.into_script(),
+    }
+}
@@ -0,0 +1,236 @@
+use std::str::FromStr;
+
+use bitcoin::{
+    key::Keypair,
+    opcodes::all::{OP_CHECKSIG, OP_CHECKSIGADD, OP_NUMEQUAL},
+    script::Builder,
+    secp256k1::{Message, Secp256k1},
+    sighash::{Prevouts, SighashCache, TapSighashType},
+    taproot::{self, ControlBlock, LeafVersion, TapLeafHash, TaprootBuilder, TaprootSpendInfo},
+    PrivateKey, ScriptBuf, Transaction, TxOut, Witness, XOnlyPublicKey,
+};
+use rand::Rng;
+
+use crate::rng;
+
+/// Kind of leaf in a taproot script tree
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TapLeafParams {
+    /// `<key> OP_CHECKSIG`
+    CheckSig,
+    /// `threshold` of `keys` signatures, counted with OP_CHECKSIGADD
+    Multi { threshold: usize, keys: usize },
+    /// A caller-provided script, spent with nothing but itself and the
+    /// control block on the stack
+    Script(ScriptBuf),
+}
+
+impl FromStr for TapLeafParams {
+    type Err = String;
+
+    /// Parses `checksig`, `multi:<threshold>-of-<keys>` or `script:<hex>`.
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value == "checksig" {
+            return Ok(Self::CheckSig);
+        }
+        if let Some(multi) = value.strip_prefix("multi:") {
+            let (threshold, keys) = multi
+                .split_once("-of-")
+                .ok_or_else(|| format!("invalid multi leaf '{value}', expected multi:<k>-of-<n>"))?;
+            let threshold = threshold.parse().map_err(|e| format!("invalid threshold: {e}"))?;
+            let keys = keys.parse().map_err(|e| format!("invalid key count: {e}"))?;
+            if threshold == 0 || threshold > keys {
+                return Err(format!("invalid multi leaf '{value}', need 1 <= k <= n"));
+            }
+            return Ok(Self::Multi { threshold, keys });
+        }
+        if let Some(script) = value.strip_prefix("script:") {
+            return ScriptBuf::from_hex(script)
+                .map(Self::Script)
+                .map_err(|e| format!("invalid leaf script: {e}"));
+        }
+        Err(format!(
+            "unknown leaf '{value}', expected checksig, multi:<k>-of-<n> or script:<hex>"
+        ))
+    }
+}
+
+/// A tapscript leaf with the keys it checks
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TapLeaf {
+    pub script: ScriptBuf,
+    pub version: LeafVersion,
+    /// Keys in the order the script checks them.
+    pub keys: Vec<PrivateKey>,
+    /// How many of the keys, from the first, sign a spend.
+    pub threshold: usize,
+}
+
+impl TapLeaf {
+    /// Leaf of the given kind with fresh keys
+    pub fn new(params: TapLeafParams) -> Self {
+        match params {
+            TapLeafParams::CheckSig => {
+                let key = rng::private_key();
+                let script = Builder::new()
+                    .push_x_only_key(&x_only(&key))
+                    .push_opcode(OP_CHECKSIG)
+                    .into_script();
+                Self { script, version: LeafVersion::TapScript, keys: vec![key], threshold: 1 }
+            }
+            TapLeafParams::Multi { threshold, keys } => {
+                let keys: Vec<PrivateKey> = (0..keys.max(1)).map(|_| rng::private_key()).collect();
+                let mut builder = Builder::new();
+                for (position, key) in keys.iter().enumerate() {
+                    let opcode = if position == 0 { OP_CHECKSIG } else { OP_CHECKSIGADD };
+                    builder = builder.push_x_only_key(&x_only(key)).push_opcode(opcode);
+                }
+                let script = builder
+                    .push_int(threshold as i64)
+                    .push_opcode(OP_NUMEQUAL)
+                    .into_script();
+                Self { script, version: LeafVersion::TapScript, keys, threshold }
+            }
+            TapLeafParams::Script(script) => Self {
+                script,
+                version: LeafVersion::TapScript,
+                keys: vec![],
+                threshold: 0,
+            },
+        }
+    }
+
+    pub fn leaf_hash(&self) -> TapLeafHash {
+        TapLeafHash::from_script(&self.script, self.version)
+    }
+}
+
+#[derive(Default, Clone)]
+pub struct TapTreeParams {
+    pub internal_key: Option<PrivateKey>,
+    /// Defaults to 1 to 4 random checksig and multi leaves.
+    pub leaves: Option<Vec<TapLeafParams>>,
+    /// Leaf the output is spent through; defaults to a random one.
+    pub leaf_index: Option<usize>,
+}
+
+/// A P2TR output committing to a script tree, and the leaf an input spends
+/// it through
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TapScriptSpend {
+    pub internal_key: PrivateKey,
+    pub leaves: Vec<TapLeaf>,
+    pub leaf_index: usize,
+}
+
+pub trait RandomTapScriptSpend {
+    fn random(params: TapTreeParams) -> TapScriptSpend;
+}
+
+impl RandomTapScriptSpend for TapScriptSpend {
+    fn random(params: TapTreeParams) -> TapScriptSpend {
+        let internal_key = params.internal_key.unwrap_or_else(rng::private_key);
+        let leaves: Vec<TapLeaf> = params
+            .leaves
+            .unwrap_or_else(|| {
+                (0..rng::rng().random_range(1..=4))
+                    .map(|_| match rng::rng().random_range(0..2) {
+                        0 => TapLeafParams::CheckSig,
+                        _ => {
+                            let keys = rng::rng().random_range(2..=3);
+                            let threshold = rng::rng().random_range(1..=keys);
+                            TapLeafParams::Multi { threshold, keys }
+                        }
+                    })
+                    .collect()
+            })
+            .into_iter()
+            .map(TapLeaf::new)
+            .collect();
+        let leaf_index = params
+            .leaf_index
+            .unwrap_or_else(|| rng::rng().random_range(0..leaves.len()));
+
+        TapScriptSpend { internal_key, leaves, leaf_index }
+    }
+}
+
+impl TapScriptSpend {
+    /// Tree as balanced as the number of leaves allows: with `2^d` slots at
+    /// the deepest level `d`, the first leaves pair up at depth `d` and the
+    /// rest sit one level up, in order.
+    pub fn spend_info(&self) -> TaprootSpendInfo {
+        let count = self.leaves.len();
+        let depth = count.next_power_of_two().trailing_zeros() as u8;
+        let deepest = 2 * count - count.next_power_of_two();
+
+        let mut builder = TaprootBuilder::new();
+        for (index, leaf) in self.leaves.iter().enumerate() {
+            let leaf_depth = if index < deepest { depth } else { depth - 1 };
+            builder = builder
+                .add_leaf_with_ver(leaf_depth, leaf.script.clone(), leaf.version)
+                .expect("leaves are added depth first");
+        }
+        builder
+            .finalize(&Secp256k1::new(), x_only(&self.internal_key))
+            .expect("the tree is complete")
+    }
+
+    pub fn script_pubkey(&self) -> ScriptBuf {
+        ScriptBuf::new_p2tr_tweaked(self.spend_info().output_key())
+    }
+
+    pub fn leaf(&self) -> &TapLeaf {
+        &self.leaves[self.leaf_index]
+    }
+
+    /// Control block proving the spent leaf is in the tree
+    pub fn control_block(&self) -> ControlBlock {
+        self.spend_info()
+            .control_block(&(self.leaf().script.clone(), self.leaf().version))
+            .expect("the spent leaf is in the tree")
+    }
+
+    /// Witness spending through the leaf: its signatures in the reverse of
+    /// the order the script checks them, with empty ones for the keys past
+    /// the threshold, then the script and the control block.
+    pub fn witness(&self, transaction: &Transaction, input_index: usize, prevouts: &[TxOut]) -> Witness {
+        let leaf = self.leaf();
+        let sighash = SighashCache::new(transaction)
+            .taproot_script_spend_signature_hash(
+                input_index,
+                &Prevouts::All(prevouts),
+                leaf.leaf_hash(),
+                TapSighashType::Default,
+            )
+            .unwrap();
+        let message = Message::from_digest_slice(&sighash[..]).unwrap();
+
+        let secp = Secp256k1::new();
+        let mut witness = Witness::new();
+        for (position, key) in leaf.keys.iter().enumerate().rev() {
+            if position >= leaf.threshold {
+                witness.push([]);
+                continue;
+            }
+            let sig = taproot::Signature {
+                signature: secp.sign_schnorr_with_aux_rand(
+                    &message,
+                    &Keypair::from_secret_key(&secp, &key.inner),
+                    &rng::rng().random::<[u8; 32]>(),
+                ),
+                sighash_type: TapSighashType::Default,
+            };
+            witness.push(sig.to_vec());
+        }
+        witness.push(leaf.script.as_bytes());
+        witness.push(self.control_block().serialize());
+        witness
+    }
+}
+
+fn x_only(key: &PrivateKey) -> XOnlyPublicKey {
+    Keypair::from_secret_key(&Secp256k1::new(), &key.inner)
+        .x_only_public_key()
+        .0
+}
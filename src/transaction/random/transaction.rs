@@ -1,61 +1,308 @@
 use super::{
-    input::{InputParams, RandomInput},
+    input::{funding_outpoint, InputInfo, InputParams, RandomInput},
     locktime::RandomLockTime,
     output::{OutputParams, RandomOutput},
+    script::ScriptTypes,
+    script_sig::sign_legacy_input,
     version::RandomVersion,
+    witness::{RandomWitness, WitnessParams},
 };
 use bitcoin::{
-    absolute::LockTime, transaction::Version, NetworkKind, PrivateKey, Transaction, TxIn, TxOut,ScriptBuf,consensus::Encodable,
+    absolute::LockTime, transaction::Version, Amount, FeeRate, PrivateKey, Transaction, TxIn, TxOut,ScriptBuf,Witness,
 };
+use rand::Rng;
 
+use crate::{rng, Error, Result};
+use std::ops::RangeInclusive;
+
+/// Number of inputs or outputs to generate.
+#[derive(Debug, Clone)]
+pub enum Count {
+    Fixed(usize),
+    Range(RangeInclusive<usize>),
+}
+
+impl Count {
+    pub fn resolve(&self) -> usize {
+        match self {
+            Count::Fixed(count) => *count,
+            Count::Range(range) => rng::rng().random_range(range.clone()),
+        }
+    }
+}
+
+/// Fee paid by a generated transaction, taken out of the value of its inputs.
+#[derive(Debug, Clone, Copy)]
+pub enum Fee {
+    Absolute(Amount),
+    Rate(FeeRate),
+}
 
 #[derive(Default)]
 pub struct TxParams {
     pub version: Option<Version>,
     pub lock_time: Option<LockTime>,
+    /// Params used for every input slot not covered by `inputs`.
     pub input: Option<InputParams>,
+    /// Params used for every output slot not covered by `outputs`.
     pub output: Option<OutputParams>,
+    /// Per-slot input params; slot `i` uses `inputs[i]` when present.
+    pub inputs: Option<Vec<InputParams>>,
+    /// Per-slot output params; slot `i` uses `outputs[i]` when present.
+    pub outputs: Option<Vec<OutputParams>>,
+    /// Defaults to the length of `inputs`, or 1.
+    pub input_count: Option<Count>,
+    /// Defaults to the length of `outputs`, or 1.
+    pub output_count: Option<Count>,
     pub private_key: Option<PrivateKey>,
     pub block_height: Option<u32>,
+    /// Defaults to a random rate between 1 and 20 sat/vB.
+    pub fee: Option<Fee>,
+    /// Reseeds the shared generator before anything is drawn.
+    pub seed: Option<u64>,
 }
 
+/// A generated transaction together with the outputs its inputs spend.
+#[derive(Debug, Clone)]
+pub struct TxInfo {
+    pub transaction: Transaction,
+    pub prevouts: Vec<TxOut>,
+}
 
+impl TxInfo {
+    /// Value of the spent outputs minus the value created, or `None` if the
+    /// transaction creates more than it spends.
+    pub fn fee(&self) -> Option<Amount> {
+        let input_value: Amount = self.prevouts.iter().map(|prevout| prevout.value).sum();
+        let output_value: Amount = self.transaction.output.iter().map(|output| output.value).sum();
+        input_value.checked_sub(output_value)
+    }
+}
 
+/// Generation fails only when caller-fixed input values cannot cover the
+/// caller-fixed output values plus the fee; inputs of random value are
+/// raised to cover them.
 pub trait RandomTransacion {
-    fn random(params: TxParams) -> Transaction;
+    fn random(params: TxParams) -> Result<Transaction>;
+    fn random_with_info(params: TxParams) -> Result<TxInfo>;
 }
 
 impl RandomTransacion for Transaction {
-    fn random(params: TxParams) -> Transaction {
+    fn random(params: TxParams) -> Result<Transaction> {
+        Ok(Self::random_with_info(params)?.transaction)
+    }
+
+    fn random_with_info(params: TxParams) -> Result<TxInfo> {
+        if let Some(seed) = params.seed {
+            rng::set_seed(seed);
+        }
+
         let private_key = params
             .private_key
-            .unwrap_or_else(|| PrivateKey::generate(NetworkKind::Main));
+            .unwrap_or_else(rng::private_key);
 
-        let mut input_params = params.input.unwrap_or_default();
-        let mut output_params = params.output.unwrap_or_default();
+        let input_count = resolve_count(params.input_count, params.inputs.as_ref());
+        let output_count = resolve_count(params.output_count, params.outputs.as_ref());
 
-        input_params.private_key = Some(private_key);
-        output_params.private_key = Some(private_key);
+        let input_template = params.input.unwrap_or_default();
+        let mut input_slots = params.inputs.unwrap_or_default().into_iter();
+        let (mut input_infos, input_params): (Vec<InputInfo>, Vec<InputParams>) = (0..input_count)
+            .map(|_| {
+                let mut input_params = input_slots
+                    .next()
+                    .unwrap_or_else(|| input_template.clone());
+                input_params.private_key.get_or_insert(private_key);
+                (TxIn::random(input_params.clone()), input_params)
+            })
+            .unzip();
 
-        let mut input_info = TxIn::random(input_params);
-        if let Some(height) = params.block_height {
-            input_info.script_sig = prepend_bip34_height(input_info.script_sig, height);
+        if let (Some(height), Some(first)) = (params.block_height, input_infos.first_mut()) {
+            first.txin.script_sig = prepend_bip34_height(first.txin.script_sig.clone(), height);
         }
-        let output_info = TxOut::random(output_params);
 
-        Transaction {
+        let output_template = params.output.unwrap_or_default();
+        let mut output_slots = params.outputs.unwrap_or_default().into_iter();
+        let (output, fixed_value): (Vec<TxOut>, Vec<bool>) = (0..output_count)
+            .map(|_| {
+                let mut output_params = output_slots
+                    .next()
+                    .unwrap_or_else(|| output_template.clone());
+                output_params.private_key.get_or_insert(private_key);
+                let fixed = output_params.value.is_some();
+                (TxOut::random(output_params).0, fixed)
+            })
+            .unzip();
+
+        let mut transaction = Transaction {
             version: params.version.unwrap_or_else(Version::random),
             lock_time: params.lock_time.unwrap_or_else(LockTime::random),
-            input: vec![input_info.clone()],
-            output: vec![output_info.0.clone()],
+            input: input_infos.iter().map(|info| info.txin.clone()).collect(),
+            output,
+        };
+
+        let fixed_total: Amount = transaction
+            .output
+            .iter()
+            .zip(&fixed_value)
+            .filter(|(_, fixed)| **fixed)
+            .map(|(output, _)| output.value)
+            .sum();
+
+        let fee = params.fee.unwrap_or_else(|| {
+            Fee::Rate(FeeRate::from_sat_per_vb_unchecked(
+                rng::rng().random_range(1..=20),
+            ))
+        });
+
+        // A feerate depends on the signed size, which depends on the output
+        // values through the signatures, so settle the fee by re-signing
+        // until the paid fee covers the final vsize.
+        let mut fee_amount = match fee {
+            Fee::Absolute(amount) => amount,
+            Fee::Rate(_) => Amount::ZERO,
+        };
+        loop {
+            cover_outputs(
+                &mut transaction,
+                &mut input_infos,
+                &input_params,
+                fixed_total + fee_amount,
+            )?;
+            let input_value: Amount = input_infos.iter().map(|info| info.prevout.value).sum();
+            distribute_value(
+                &mut transaction.output,
+                &fixed_value,
+                input_value - fixed_total - fee_amount,
+            );
+            let prevouts: Vec<TxOut> = input_infos
+                .iter()
+                .map(|info| info.prevout.clone())
+                .collect();
+            sign_inputs(&mut transaction, &input_infos, &prevouts);
+
+            let Fee::Rate(rate) = fee else { break };
+            let required = rate
+                .fee_vb(transaction.vsize() as u64)
+                .expect("fee rate overflow");
+            if fee_amount >= required {
+                break;
+            }
+            fee_amount = required;
         }
+
+        Ok(TxInfo {
+            transaction,
+            prevouts: input_infos.into_iter().map(|info| info.prevout).collect(),
+        })
     }
 }
 
+// Raise the first input whose value the caller left random until the inputs
+// cover `required`, funding it again if its outpoint was fabricated too.
+// Caller-fixed values that fall short are a contradiction in the params.
+fn cover_outputs(
+    transaction: &mut Transaction,
+    inputs: &mut [InputInfo],
+    params: &[InputParams],
+    required: Amount,
+) -> Result<()> {
+    let input_value: Amount = inputs.iter().map(|info| info.prevout.value).sum();
+    if input_value >= required {
+        return Ok(());
+    }
+    let Some(index) = params.iter().position(|params| params.value.is_none()) else {
+        return Err(Error::InvalidConfig(format!(
+            "inputs worth {input_value} cannot cover fixed outputs and fee of {required}"
+        )));
+    };
+
+    let info = &mut inputs[index];
+    info.prevout.value += required - input_value;
+    if params[index].outpoint.is_none() {
+        info.txin.previous_output =
+            funding_outpoint(&info.prevout.script_pubkey, info.prevout.value);
+        transaction.input[index].previous_output = info.txin.previous_output;
+    }
+    Ok(())
+}
+
+// Split `remaining` randomly across the outputs whose value was not set
+// by the caller, so the transaction never creates money.
+fn distribute_value(outputs: &mut [TxOut], fixed_value: &[bool], remaining: Amount) {
+    let remaining = remaining.to_sat();
+
+    let free: Vec<&mut TxOut> = outputs
+        .iter_mut()
+        .zip(fixed_value)
+        .filter(|(_, fixed)| !**fixed)
+        .map(|(output, _)| output)
+        .collect();
+    if free.is_empty() {
+        return;
+    }
+
+    let mut cuts: Vec<u64> = (1..free.len())
+        .map(|_| rng::rng().random_range(0..=remaining))
+        .collect();
+    cuts.push(remaining);
+    cuts.sort_unstable();
+
+    let mut previous = 0;
+    for (output, cut) in free.into_iter().zip(cuts) {
+        output.value = Amount::from_sat(cut - previous);
+        previous = cut;
+    }
+}
+
+fn resolve_count<T>(count: Option<Count>, slots: Option<&Vec<T>>) -> usize {
+    count
+        .map(|count| count.resolve())
+        .unwrap_or_else(|| slots.map_or(1, Vec::len))
+}
+
+// Every signature commits to the final transaction, so signing happens once
+// all inputs and outputs are in place. Neither legacy nor segwit sighashes
+// cover other inputs' script_sig or witness, so inputs can be signed in order.
+fn sign_inputs(transaction: &mut Transaction, inputs: &[InputInfo], prevouts: &[TxOut]) {
+    for (index, info) in inputs.iter().enumerate() {
+        if !info.needs_signature {
+            continue;
+        }
+
+        // Nested segwit and wrapped multisig fill both the script_sig and
+        // the witness
+        let legacy = matches!(
+            info.script_type,
+            ScriptTypes::P2PK | ScriptTypes::P2PKH | ScriptTypes::P2SH
+        );
+        let nested = matches!(
+            info.script_type,
+            ScriptTypes::P2SHP2WPKH | ScriptTypes::P2SHP2WSH | ScriptTypes::MULTISIG(_)
+        );
+        if legacy || nested {
+            transaction.input[index].script_sig = sign_legacy_input(
+                transaction,
+                index,
+                &info.prevout.script_pubkey,
+                &info.script_type,
+                &info.private_key,
+            );
+        }
+        if !legacy {
+            transaction.input[index].witness = Witness::random(WitnessParams {
+                transaction: Some(transaction.clone()),
+                input_index: Some(index),
+                prevouts: Some(prevouts.to_vec()),
+                script: Some((info.prevout.script_pubkey.clone(), info.script_type.clone())),
+                private_key: Some(info.private_key),
+            });
+        }
+    }
+}
+
+// BIP34 requires the height as the first push of the coinbase script_sig.
 fn prepend_bip34_height(script: ScriptBuf, height: u32) -> ScriptBuf {
-    let mut height_bytes = vec![];
-    bitcoin::consensus::encode::VarInt(height as u64).consensus_encode(&mut height_bytes).unwrap();
-    let mut new_bytes = height_bytes;
+    let mut new_bytes = ScriptBuf::builder().push_int(height as i64).into_bytes();
     new_bytes.extend_from_slice(script.as_bytes());
     ScriptBuf::from_bytes(new_bytes)
-}
\ No newline at end of file
+}
@@ -1,5 +1,7 @@
 use bitcoin::blockdata::transaction::Version;
-use secp256k1::rand::{self, Rng};
+use rand::Rng;
+
+use crate::rng;
 
 pub trait RandomVersion {
     fn random() -> Version;
@@ -8,14 +10,14 @@ pub trait RandomVersion {
 impl RandomVersion for Version {
     fn random() -> Version {
         // Random standard
-        if rand::thread_rng().gen_bool(0.5) {
-            if rand::thread_rng().gen_bool(0.5) {
+        if rng::rng().random_bool(0.5) {
+            if rng::rng().random_bool(0.5) {
                 return Version::ONE;
             }
             return Version::TWO;
         }
 
         // Random non_standard
-        Version::non_standard(rand::thread_rng().gen::<i32>())
+        Version::non_standard(rng::rng().random::<i32>())
     }
 }
@@ -1,31 +1,38 @@
 use bitcoin::{
     ecdsa::Signature,
     hashes::Hash,
-    key::Keypair,
+    key::{Keypair, TapTweak},
     secp256k1::{Message, Secp256k1},
     sighash::{EcdsaSighashType, SighashCache},
     sighash::{Prevouts, TapSighashType},
-    NetworkKind,
     OutPoint,
     PrivateKey,
     PublicKey,
     ScriptBuf,
     Transaction,
+    TxOut,
     Txid,
     Witness,
 };
-use secp256k1::rand::{self, Rng};
+use rand::Rng;
+
+use crate::rng;
 
 use crate::transaction::random::{
     input::InputParams,
+    output::random_amount,
     script::{RandomScript, ScriptParams, ScriptTypes},
     transaction::{RandomTransacion, TxParams},
 };
 
 #[derive(Default, Debug, Clone)]
 pub struct WitnessParams {
+    /// Spending transaction the signature commits to.
     pub transaction: Option<Transaction>,
-    pub vout: Option<usize>,
+    /// Index of the input being signed.
+    pub input_index: Option<usize>,
+    /// Outputs spent by every input of `transaction`, in input order.
+    pub prevouts: Option<Vec<TxOut>>,
     pub script: Option<(ScriptBuf, ScriptTypes)>,
     pub private_key: Option<PrivateKey>,
 }
@@ -36,65 +43,65 @@ pub trait RandomWitness {
 
 impl RandomWitness for Witness {
     fn random(params: WitnessParams) -> Witness {
+        let private_key = params
+            .private_key
+            .unwrap_or_else(rng::private_key);
+
+        let (script, script_type) = params.script.unwrap_or_else(|| {
+            ScriptBuf::random(ScriptParams {
+                script_type: Some(match rng::rng().random_range(0..6) {
+                    0 => ScriptTypes::P2TR,
+                    1 => ScriptTypes::P2TWEAKEDTR,
+                    2 => ScriptTypes::P2WPKH,
+                    3 => ScriptTypes::P2SHP2WPKH,
+                    4 => ScriptTypes::P2SHP2WSH,
+                    _ => ScriptTypes::P2WSH,
+                }),
+                private_key: Some(private_key),
+            })
+        });
+
         let transaction = params.transaction.unwrap_or_else(|| {
-            let mut random_tx_params = TxParams::default();
-            let mut random_input_params = InputParams {
-                witness: Some(Witness::default()),
-                outpoint: Some(OutPoint {
-                    txid: Txid::all_zeros(),
-                    vout: rand::thread_rng().gen::<u32>(),
+            let random_tx_params = TxParams {
+                input: Some(InputParams {
+                    witness: Some(Witness::default()),
+                    outpoint: Some(OutPoint {
+                        txid: Txid::all_zeros(),
+                        vout: rng::rng().random::<u32>(),
+                    }),
+                    ..Default::default()
                 }),
                 ..Default::default()
             };
 
-            random_input_params.witness = Some(Witness::default());
-            random_input_params.outpoint = Some(OutPoint {
-                txid: Txid::all_zeros(),
-                vout: rand::thread_rng().gen::<u32>(),
-            });
-
-            random_tx_params.input = Some(random_input_params);
-
-            Transaction::random(random_tx_params)
+            Transaction::random(random_tx_params).expect("inputs of random value cover any fee")
         });
 
-        let vout = params
-            .vout
-            .unwrap_or_else(|| rand::thread_rng().gen_range(0..transaction.output.len()));
-
-        let amount = transaction.output[vout].value;
-
-        let (script, script_type) = params.script.unwrap_or_else(|| {
-            let mut script_params = ScriptParams {
-            script_type: Some(match rand::thread_rng().gen_range(0..3) {
-                0 => ScriptTypes::P2TR,
-                1 => ScriptTypes::P2TWEAKEDTR,
-                2 => ScriptTypes::P2WPKH,
-                _ => ScriptTypes::P2WSH,
-            }),
-            ..Default::default()
-};
-
-            script_params.script_type = Some(match rand::thread_rng().gen_range(0..3) {
-                0 => ScriptTypes::P2TR,
-                1 => ScriptTypes::P2TWEAKEDTR,
-                2 => ScriptTypes::P2WPKH,
-                _ => ScriptTypes::P2WSH,
-            });
-
-            ScriptBuf::random(script_params)
+        let input_index = params
+            .input_index
+            .unwrap_or_else(|| rng::rng().random_range(0..transaction.input.len()));
+
+        let prevouts = params.prevouts.unwrap_or_else(|| {
+            transaction
+                .input
+                .iter()
+                .map(|_| TxOut {
+                    value: random_amount(),
+                    script_pubkey: script.clone(),
+                })
+                .collect()
         });
 
-        let private_key = params
-            .private_key
-            .unwrap_or_else(|| PrivateKey::generate(NetworkKind::Main));
+        let amount = prevouts[input_index].value;
 
         let pub_key = PublicKey::from_private_key(&Secp256k1::new(), &private_key);
 
         match script_type {
-            ScriptTypes::P2WPKH => {
+            ScriptTypes::P2WPKH | ScriptTypes::P2SHP2WPKH => {
+                // Nested outputs sign for the program their redeem script holds
+                let program = ScriptBuf::new_p2wpkh(&pub_key.wpubkey_hash().unwrap());
                 let sighash = SighashCache::new(&transaction)
-                    .p2wpkh_signature_hash(vout, &script, amount, EcdsaSighashType::All)
+                    .p2wpkh_signature_hash(input_index, &program, amount, EcdsaSighashType::All)
                     .unwrap();
 
                 let sig = Signature {
@@ -108,42 +115,33 @@ impl RandomWitness for Witness {
                 Witness::p2wpkh(&sig, &pub_key.inner)
             }
 
-            ScriptTypes::P2WSH => {
-                let sighash = SighashCache::new(&transaction)
-                    .p2wsh_signature_hash(vout, &script, amount, EcdsaSighashType::All)
-                    .unwrap();
-
-                let sig = Signature {
-                    signature: Secp256k1::new().sign_ecdsa(
-                        &Message::from_digest_slice(&sighash[..]).unwrap(),
-                        &private_key.inner,
-                    ),
-                    sighash_type: EcdsaSighashType::All,
-                };
-
-                let mut sig_ser = sig.serialize().to_vec();
-                sig_ser.push(EcdsaSighashType::All as u8);
-
-                let mut witness = Witness::new();
-                witness.push(sig_ser);
-                witness.push(script.as_bytes());
-
-                witness
-            }
+            ScriptTypes::P2WSH | ScriptTypes::P2SHP2WSH | ScriptTypes::MULTISIG(_) => script_type
+                .multisig(private_key)
+                .expect("the type pays to a multisig")
+                .witness(&transaction, input_index, amount),
 
             ScriptTypes::P2TR | ScriptTypes::P2TWEAKEDTR => {
                 let sighash = SighashCache::new(&transaction)
                     .taproot_key_spend_signature_hash(
-                        vout,
-                        &Prevouts::All(&[transaction.tx_out(vout).unwrap()]),
+                        input_index,
+                        &Prevouts::All(&prevouts),
                         TapSighashType::Default,
                     )
                     .unwrap();
 
+                let secp = Secp256k1::new();
+                let keypair = Keypair::from_secret_key(&secp, &private_key.inner);
+                // P2TR outputs commit to the BIP86-tweaked key, P2TWEAKEDTR ones to the raw key.
+                let keypair = match script_type {
+                    ScriptTypes::P2TR => keypair.tap_tweak(&secp, None).to_keypair(),
+                    _ => keypair,
+                };
+
                 let sig = bitcoin::taproot::Signature {
-                    signature: Secp256k1::new().sign_schnorr(
+                    signature: secp.sign_schnorr_with_aux_rand(
                         &Message::from_digest_slice(&sighash[..]).unwrap(),
-                        &Keypair::from_secret_key(&Secp256k1::new(), &private_key.inner),
+                        &keypair,
+                        &rng::rng().random::<[u8; 32]>(),
                     ),
                     sighash_type: TapSighashType::Default,
                 };
@@ -151,6 +149,8 @@ impl RandomWitness for Witness {
                 Witness::p2tr_key_spend(&sig)
             }
 
+            ScriptTypes::P2TRSCRIPTPATH(spend) => spend.witness(&transaction, input_index, &prevouts),
+
             _ => Witness::default(),
         }
     }